@@ -0,0 +1,55 @@
+use std::io::Cursor;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tokenize::lexer::{Lexer, StreamLexer};
+use tokenize::parser::SyntaxParser;
+
+// Programa Triangle sintético con `n` grupos de declaraciones y comandos.
+fn synthetic_program(n: usize) -> String {
+    let mut decls = Vec::new();
+    for i in 0..n {
+        decls.push(format!("   const c{i} ~ ord('a') - ord('A') + {i}"));
+        decls.push(format!("   var v{i} : Integer"));
+        decls.push(format!(
+            "   ! comentario\n   func f{i} (var x : Char) : Boolean ~\n       (ord('A') <= ord(x)) /\\ (ord(x) <= ord('Z'))"
+        ));
+    }
+    let mut src = format!("let\n{}\n", decls.join(";\n"));
+    src.push_str("in\n  begin\n");
+    for i in 0..n {
+        src.push_str(&format!("   v{i} := v{i} + c{i};\n"));
+        src.push_str(&format!("   begin if f{i}(current) then put(chr(ord(current) + c{i})) else v{i} := 3 end;\n"));
+    }
+    src.push_str("   puteol()\n  end\n");
+    src
+}
+
+fn bench_lexers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+    for n in [100, 1_000, 10_000] {
+        let src = synthetic_program(n);
+        group.throughput(Throughput::Bytes(src.len() as u64));
+        group.bench_with_input(BenchmarkId::new("str", n), &src, |b, src| {
+            b.iter(|| Lexer::new(black_box(src)).count())
+        });
+        group.bench_with_input(BenchmarkId::new("stream", n), &src, |b, src| {
+            b.iter(|| StreamLexer::new(Cursor::new(black_box(src.as_bytes()))).count())
+        });
+    }
+    group.finish();
+}
+
+fn bench_parser(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for n in [100, 1_000, 10_000] {
+        let src = synthetic_program(n);
+        group.throughput(Throughput::Bytes(src.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &src, |b, src| {
+            b.iter(|| SyntaxParser::new(black_box(src)).parse_program().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_lexers, bench_parser);
+criterion_main!(benches);
@@ -0,0 +1,127 @@
+// Anfitrión para los módulos que genera `triangle compile -t wasm`:
+//     node runtime/triangle-host.js programa.wasm < entrada
+// Provee la entrada y salida de "env" y reporta los errores de ejecución.
+'use strict';
+const fs = require('fs');
+
+const MAXINT = 32767;
+const ERRORS = [
+  'integer overflow',
+  'division by zero',
+  'array index out of range',
+  'invalid character code',
+  'stack overflow',
+  'invalid integer in input',
+];
+
+if (process.argv.length !== 3) {
+  process.stderr.write('usage: node triangle-host.js <module.wasm>\n');
+  process.exit(2);
+}
+
+// La entrada se lee de a bloques recién cuando el programa la pide, y la
+// salida se escribe al llenarse el búfer, antes de leer y al terminar.
+const BUFFER = 4096;
+const inbuf = Buffer.alloc(BUFFER);
+let inpos = 0;
+let inlen = 0;
+let output = [];
+let outlen = 0;
+
+const flush = () => {
+  if (outlen > 0) fs.writeSync(1, Buffer.concat(output));
+  output = [];
+  outlen = 0;
+};
+
+const write = (text) => {
+  const bytes = Buffer.from(text);
+  output.push(bytes);
+  outlen += bytes.length;
+  if (outlen >= BUFFER) flush();
+};
+
+// Próximo byte de la entrada sin consumirlo, o -1 al final.
+const peek = () => {
+  if (inpos < inlen) return inbuf[inpos];
+  flush();
+  inpos = 0;
+  for (;;) {
+    try {
+      inlen = fs.readSync(0, inbuf, 0, BUFFER, null);
+      break;
+    } catch (e) {
+      if (e.code === 'EAGAIN') continue;
+      inlen = 0;
+      break;
+    }
+  }
+  return inlen > 0 ? inbuf[0] : -1;
+};
+
+const next = () => {
+  const b = peek();
+  if (b >= 0) inpos++;
+  return b;
+};
+
+class RuntimeError extends Error {}
+
+const isSpace = (b) => b === 0x20 || (b >= 0x09 && b <= 0x0d);
+const isDigit = (b) => b >= 0x30 && b <= 0x39;
+
+// `get` decodifica UTF-8, como escribe `put`; una secuencia inválida da U+FFFD.
+const getchar = () => {
+  const lead = next();
+  if (lead < 0x80) return lead;
+  const n = lead < 0xc0 ? 0 : lead < 0xe0 ? 1 : lead < 0xf0 ? 2 : lead < 0xf8 ? 3 : 0;
+  if (n === 0) return 0xfffd;
+  let code = lead & (0x3f >> n);
+  for (let i = 0; i < n; i++) {
+    if ((peek() & 0xc0) !== 0x80) return 0xfffd;
+    code = (code << 6) | (next() & 0x3f);
+  }
+  const min = [0, 0x80, 0x800, 0x10000][n];
+  return code < min || code > 0x10ffff || (code >= 0xd800 && code <= 0xdfff) ? 0xfffd : code;
+};
+
+const env = {
+  get: getchar,
+  put: (c) => write(String.fromCodePoint(c)),
+  getint: (row, col) => {
+    while (isSpace(peek())) inpos++;
+    let text = '';
+    if (peek() === 0x2b || peek() === 0x2d) text += String.fromCharCode(next());
+    while (isDigit(peek())) text += String.fromCharCode(next());
+    const value = /\d/.test(text) ? Number(text) : NaN;
+    if (Number.isNaN(value) || Math.abs(value) > MAXINT) env.error(5, row, col);
+    return value;
+  },
+  putint: (n) => write(String(n)),
+  geteol: () => {
+    for (let b = next(); b !== -1 && b !== 0x0a; b = next());
+  },
+  puteol: () => write('\n'),
+  eof: () => (peek() < 0 ? 1 : 0),
+  eol: () => (peek() === 0x0a ? 1 : 0),
+  error: (kind, row, col) => {
+    throw new RuntimeError(`${ERRORS[kind]} at row ${row}, col ${col}`);
+  },
+};
+
+WebAssembly.instantiate(fs.readFileSync(process.argv[2]), { env })
+  .then(({ instance }) => {
+    try {
+      instance.exports.main();
+    } catch (e) {
+      flush();
+      if (!(e instanceof RuntimeError)) throw e;
+      process.stderr.write(`${process.argv[2]}: Runtime error: ${e.message}\n`);
+      process.exit(1);
+    }
+    flush();
+  })
+  .catch((e) => {
+    process.stderr.write(`${process.argv[2]}: ${e.message}\n`);
+    process.exit(1);
+  });
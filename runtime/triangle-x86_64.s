@@ -0,0 +1,426 @@
+# Runtime de Triangle para Linux x86-64, sin libc: `triangle compile -t x86-64`
+# lo agrega al final de cada programa, así que basta con
+#     as -o programa.o programa.s && ld -o programa programa.o
+# El programa compilado es `tri_main`. Las rutinas de este archivo pueden usar
+# rax, rcx, rdx, rsi, rdi y r8-r11; los chequeos reciben el valor en rax (y el
+# segundo operando en rcx) y la posición en rsi (fila) y rdx (columna).
+
+        .intel_syntax noprefix
+
+        .bss
+tri_inbuf:      .skip 4096
+tri_outbuf:     .skip 4096
+
+        .data
+tri_inpos:      .quad 0
+tri_inlen:      .quad 0
+tri_outlen:     .quad 0
+tri_outfd:      .quad 1
+tri_depth:      .quad 0
+tri_messages:
+        .quad tri_msg_overflow, tri_msg_divzero, tri_msg_index
+        .quad tri_msg_chr, tri_msg_stack, tri_msg_getint
+
+        .section .rodata
+tri_msg_runtime:        .asciz "Runtime error: "
+tri_msg_row:            .asciz " at row "
+tri_msg_col:            .asciz ", col "
+tri_msg_overflow:       .asciz "integer overflow"
+tri_msg_divzero:        .asciz "division by zero"
+tri_msg_index:          .asciz "array index out of range"
+tri_msg_chr:            .asciz "invalid character code"
+tri_msg_stack:          .asciz "stack overflow"
+tri_msg_getint:         .asciz "invalid integer in input"
+
+        .text
+        .globl _start
+_start:
+        call tri_main
+        call tri_flush
+        mov eax, 60
+        xor edi, edi
+        syscall
+
+# Errores de ejecución: rdi es el índice en tri_messages.
+tri_error:
+        mov rbx, rdi
+        mov r12, rsi
+        mov r13, rdx
+        call tri_flush
+        mov qword ptr [rip+tri_outfd], 2
+        lea rdi, [rip+tri_msg_runtime]
+        call tri_puts
+        lea rax, [rip+tri_messages]
+        mov rdi, [rax+rbx*8]
+        call tri_puts
+        lea rdi, [rip+tri_msg_row]
+        call tri_puts
+        mov rdi, r12
+        call tri_putint
+        lea rdi, [rip+tri_msg_col]
+        call tri_puts
+        mov rdi, r13
+        call tri_putint
+        mov edi, 10
+        call tri_putc
+        call tri_flush
+        mov eax, 60
+        mov edi, 1
+        syscall
+
+tri_check:
+        cmp rax, 32767
+        jg 1f
+        cmp rax, -32767
+        jl 1f
+        ret
+1:      xor edi, edi
+        jmp tri_error
+
+tri_div:
+        test rcx, rcx
+        jz tri_divzero
+        mov r8, rdx
+        cqo
+        idiv rcx
+        mov rdx, r8
+        jmp tri_check
+
+# Resto siempre no negativo, como en el intérprete.
+tri_mod:
+        test rcx, rcx
+        jz tri_divzero
+        mov r8, rdx
+        cqo
+        idiv rcx
+        mov rax, rdx
+        mov rdx, r8
+        test rax, rax
+        jns 1f
+        mov r9, rcx
+        neg r9
+        cmovs r9, rcx
+        add rax, r9
+1:      ret
+
+tri_divzero:
+        mov edi, 1
+        jmp tri_error
+
+tri_index:
+        test rax, rax
+        js 1f
+        cmp rax, rcx
+        jge 1f
+        ret
+1:      mov edi, 2
+        jmp tri_error
+
+tri_chr:
+        test rax, rax
+        js 1f
+        cmp rax, 0x10FFFF
+        jg 1f
+        cmp rax, 0xD800
+        jl 2f
+        cmp rax, 0xDFFF
+        jle 1f
+2:      ret
+1:      mov edi, 3
+        jmp tri_error
+
+tri_enter:
+        inc qword ptr [rip+tri_depth]
+        cmp qword ptr [rip+tri_depth], 2000
+        jg 1f
+        ret
+1:      mov edi, 4
+        jmp tri_error
+
+tri_leave:
+        dec qword ptr [rip+tri_depth]
+        ret
+
+# Entrada: el próximo byte en rax sin consumirlo, o -1 al final.
+tri_peek:
+        mov rax, [rip+tri_inpos]
+        cmp rax, [rip+tri_inlen]
+        jb 1f
+        xor eax, eax
+        xor edi, edi
+        lea rsi, [rip+tri_inbuf]
+        mov edx, 4096
+        syscall
+        mov qword ptr [rip+tri_inpos], 0
+        test rax, rax
+        jle 2f
+        mov [rip+tri_inlen], rax
+        xor eax, eax
+1:      lea rcx, [rip+tri_inbuf]
+        movzx eax, byte ptr [rcx+rax]
+        ret
+2:      mov qword ptr [rip+tri_inlen], 0
+        mov rax, -1
+        ret
+
+tri_getc:
+        call tri_peek
+        test rax, rax
+        js 1f
+        inc qword ptr [rip+tri_inpos]
+1:      ret
+
+# `get`: decodifica UTF-8, como escribe tri_put; una secuencia inválida da
+# U+FFFD. r8 acumula el código, r9 cuenta los bytes que faltan y r10 es el
+# menor código sin forma más corta.
+tri_getchar:
+        call tri_getc
+        test rax, rax
+        js 4f
+        cmp rax, 0x80
+        jb 4f
+        cmp rax, 0xC0
+        jb 3f
+        cmp rax, 0xE0
+        jb 5f
+        cmp rax, 0xF0
+        jb 6f
+        cmp rax, 0xF8
+        jae 3f
+        mov r8, rax
+        and r8, 0x07
+        mov r9, 3
+        mov r10, 0x10000
+        jmp 1f
+5:      mov r8, rax
+        and r8, 0x1F
+        mov r9, 1
+        mov r10, 0x80
+        jmp 1f
+6:      mov r8, rax
+        and r8, 0x0F
+        mov r9, 2
+        mov r10, 0x800
+1:      call tri_peek
+        mov rcx, rax
+        and rcx, 0xC0
+        cmp rcx, 0x80
+        jne 3f
+        inc qword ptr [rip+tri_inpos]
+        shl r8, 6
+        and rax, 0x3F
+        or r8, rax
+        dec r9
+        jnz 1b
+        mov rax, r8
+        cmp rax, r10
+        jb 3f
+        cmp rax, 0x10FFFF
+        ja 3f
+        cmp rax, 0xD800
+        jb 4f
+        cmp rax, 0xDFFF
+        jbe 3f
+4:      ret
+3:      mov eax, 0xFFFD
+        ret
+
+tri_eof:
+        call tri_peek
+        shr rax, 63
+        ret
+
+tri_eol:
+        call tri_peek
+        cmp rax, 10
+        sete al
+        movzx eax, al
+        ret
+
+tri_geteol:
+        call tri_getc
+        cmp rax, -1
+        je 1f
+        cmp rax, 10
+        jne tri_geteol
+1:      ret
+
+# Salta espacios y lee un entero con signo opcional; fuera de rango o sin
+# dígitos es un error.
+tri_getint:
+        push rbx
+        push r12
+        push r13
+        push r14
+        push r15
+        mov r13, rsi
+        mov r14, rdx
+1:      call tri_peek
+        cmp rax, 32
+        je 2f
+        cmp rax, 9
+        jl 3f
+        cmp rax, 13
+        jg 3f
+2:      inc qword ptr [rip+tri_inpos]
+        jmp 1b
+3:      xor ebx, ebx
+        xor r12d, r12d
+        xor r15d, r15d
+        cmp rax, '-'
+        jne 4f
+        mov ebx, 1
+        jmp 5f
+4:      cmp rax, '+'
+        jne 6f
+5:      inc qword ptr [rip+tri_inpos]
+6:      call tri_peek
+        cmp rax, '0'
+        jl 7f
+        cmp rax, '9'
+        jg 7f
+        inc qword ptr [rip+tri_inpos]
+        inc r15
+        imul r12, r12, 10
+        lea r12, [r12+rax-48]
+        cmp r12, 32768
+        jle 6b
+        mov r12d, 32768
+        jmp 6b
+7:      test r15, r15
+        jz 8f
+        cmp r12, 32767
+        jg 8f
+        mov rax, r12
+        test ebx, ebx
+        jz 9f
+        neg rax
+9:      pop r15
+        pop r14
+        pop r13
+        pop r12
+        pop rbx
+        ret
+8:      mov edi, 5
+        mov rsi, r13
+        mov rdx, r14
+        jmp tri_error
+
+# Salida con buffer hacia tri_outfd.
+tri_flush:
+        push rbx
+        xor ebx, ebx
+1:      mov rdx, [rip+tri_outlen]
+        sub rdx, rbx
+        jle 2f
+        lea rsi, [rip+tri_outbuf]
+        add rsi, rbx
+        mov rdi, [rip+tri_outfd]
+        mov eax, 1
+        syscall
+        test rax, rax
+        jle 2f
+        add rbx, rax
+        jmp 1b
+2:      mov qword ptr [rip+tri_outlen], 0
+        pop rbx
+        ret
+
+tri_putc:
+        mov rax, [rip+tri_outlen]
+        cmp rax, 4096
+        jb 1f
+        push rdi
+        call tri_flush
+        pop rdi
+        xor eax, eax
+1:      lea rcx, [rip+tri_outbuf]
+        mov [rcx+rax], dil
+        inc rax
+        mov [rip+tri_outlen], rax
+        ret
+
+tri_puts:
+        push rbx
+        mov rbx, rdi
+1:      movzx edi, byte ptr [rbx]
+        test edi, edi
+        jz 2f
+        call tri_putc
+        inc rbx
+        jmp 1b
+2:      pop rbx
+        ret
+
+# Escribe el carácter rdi en UTF-8.
+tri_put:
+        cmp rdi, 0x80
+        jb tri_putc
+        push rbx
+        mov rbx, rdi
+        cmp rbx, 0x800
+        jae 1f
+        shr rdi, 6
+        or rdi, 0xC0
+        call tri_putc
+        jmp 4f
+1:      cmp rbx, 0x10000
+        jae 2f
+        shr rdi, 12
+        or rdi, 0xE0
+        call tri_putc
+        jmp 3f
+2:      shr rdi, 18
+        or rdi, 0xF0
+        call tri_putc
+        mov rdi, rbx
+        shr rdi, 12
+        and rdi, 0x3F
+        or rdi, 0x80
+        call tri_putc
+3:      mov rdi, rbx
+        shr rdi, 6
+        and rdi, 0x3F
+        or rdi, 0x80
+        call tri_putc
+4:      mov rdi, rbx
+        and rdi, 0x3F
+        or rdi, 0x80
+        call tri_putc
+        pop rbx
+        ret
+
+tri_putint:
+        push rbx
+        sub rsp, 32
+        mov rax, rdi
+        lea rbx, [rsp+32]
+        mov r8, 10
+        test rax, rax
+        jns 1f
+        neg rax
+1:      xor edx, edx
+        div r8
+        add dl, '0'
+        dec rbx
+        mov [rbx], dl
+        test rax, rax
+        jnz 1b
+        test rdi, rdi
+        jns 2f
+        dec rbx
+        mov byte ptr [rbx], '-'
+2:      lea rax, [rsp+32]
+        cmp rbx, rax
+        jae 3f
+        movzx edi, byte ptr [rbx]
+        inc rbx
+        call tri_putc
+        jmp 2b
+3:      add rsp, 32
+        pop rbx
+        ret
+
+tri_puteol:
+        mov edi, 10
+        jmp tri_putc
@@ -0,0 +1,398 @@
+use std::ops::Index;
+use std::slice;
+
+use crate::symbol::Symbol;
+use crate::token::{Span, TokenType};
+
+// Índice estable de un nodo dentro de un `Ast`. Los pasos de análisis guardan
+// sus resultados en tablas `HashMap<NodeId, T>` en lugar de modificar el árbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ASTNode {
+    Let(NodeId, NodeId),
+    Const(Symbol, NodeId),
+    Var(Symbol, NodeId),
+    Func(Symbol, Vec<NodeId>, NodeId, NodeId),
+    Proc(Symbol, Vec<NodeId>, NodeId),
+    Type(Symbol, NodeId),
+    Assign(NodeId, NodeId),
+    If(NodeId, TokenType, NodeId, TokenType, NodeId),
+    While(NodeId, NodeId),
+    Empty,
+    Call(Symbol, Vec<NodeId>),
+    Expression(NodeId),
+    Identifier(Symbol),
+    Number(i64),
+    Char(char),
+    Operator(Symbol, NodeId, NodeId),
+    Declaration(Vec<NodeId>),
+    Command(Vec<NodeId>),
+    TypeName(Symbol),
+    ArrayType(i64, NodeId),
+    RecordType(Vec<NodeId>),
+    FieldType(Symbol, NodeId),
+    Field(NodeId, Symbol),
+    Index(NodeId, NodeId),
+    Unary(Symbol, NodeId),
+    // Expresiones `let D in E` e `if E1 then E2 else E3`.
+    LetExpression(NodeId, NodeId),
+    IfExpression(NodeId, NodeId, NodeId),
+    ArrayAggregate(Vec<NodeId>),
+    RecordAggregate(Vec<NodeId>),
+    FieldInit(Symbol, NodeId),
+    // Parámetros formales: por valor, por referencia (`var`) y rutinas.
+    ValueParam(Symbol, NodeId),
+    VarParam(Symbol, NodeId),
+    ProcParam(Symbol, Vec<NodeId>),
+    FuncParam(Symbol, Vec<NodeId>, NodeId),
+    // Argumentos `var V`, `proc P` y `func F`; los demás son expresiones.
+    VarActual(NodeId),
+    ProcActual(Symbol),
+    FuncActual(Symbol),
+}
+
+impl ASTNode {
+    // Hijos en orden de aparición en el texto fuente.
+    pub fn children(&self) -> Children<'_> {
+        let (list, fixed): (&[NodeId], [Option<NodeId>; 3]) = match self {
+            ASTNode::Let(decl, cmd) => (&[], [Some(*decl), Some(*cmd), None]),
+            ASTNode::Const(_, expr) => (&[], [Some(*expr), None, None]),
+            ASTNode::Var(_, type_denoter) => (&[], [Some(*type_denoter), None, None]),
+            ASTNode::Func(_, params, return_type, body) => (params, [Some(*return_type), Some(*body), None]),
+            ASTNode::FuncParam(_, params, return_type) => (params, [Some(*return_type), None, None]),
+            ASTNode::ProcParam(_, params) => (params, [None; 3]),
+            ASTNode::Proc(_, params, body) => (params, [Some(*body), None, None]),
+            ASTNode::Type(_, denoter) => (&[], [Some(*denoter), None, None]),
+            ASTNode::Assign(vname, expr) => (&[], [Some(*vname), Some(*expr), None]),
+            ASTNode::If(cond, _, then_branch, _, else_branch) => {
+                (&[], [Some(*cond), Some(*then_branch), Some(*else_branch)])
+            }
+            ASTNode::While(cond, body) => (&[], [Some(*cond), Some(*body), None]),
+            ASTNode::LetExpression(decl, expr) => (&[], [Some(*decl), Some(*expr), None]),
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                (&[], [Some(*cond), Some(*then_expr), Some(*else_expr)])
+            }
+            ASTNode::Call(_, args) => (args, [None; 3]),
+            ASTNode::Expression(expr) => (&[], [Some(*expr), None, None]),
+            ASTNode::Operator(_, left, right) => (&[], [Some(*left), Some(*right), None]),
+            ASTNode::Declaration(items)
+            | ASTNode::Command(items)
+            | ASTNode::RecordType(items)
+            | ASTNode::ArrayAggregate(items)
+            | ASTNode::RecordAggregate(items) => (items, [None; 3]),
+            ASTNode::ArrayType(_, elem)
+            | ASTNode::FieldType(_, elem)
+            | ASTNode::Field(elem, _)
+            | ASTNode::Unary(_, elem)
+            | ASTNode::FieldInit(_, elem)
+            | ASTNode::ValueParam(_, elem)
+            | ASTNode::VarParam(_, elem)
+            | ASTNode::VarActual(elem) => (&[], [Some(*elem), None, None]),
+            ASTNode::Index(vname, index) => (&[], [Some(*vname), Some(*index), None]),
+            ASTNode::Empty
+            | ASTNode::Identifier(_)
+            | ASTNode::Number(_)
+            | ASTNode::Char(_)
+            | ASTNode::TypeName(_)
+            | ASTNode::ProcActual(_)
+            | ASTNode::FuncActual(_) => (&[], [None; 3]),
+        };
+        Children { list: list.iter(), fixed, next: 0 }
+    }
+}
+
+pub struct Children<'a> {
+    list: slice::Iter<'a, NodeId>,
+    fixed: [Option<NodeId>; 3],
+    next: usize,
+}
+
+impl Iterator for Children<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        if let Some(id) = self.list.next() {
+            return Some(*id);
+        }
+        while self.next < self.fixed.len() {
+            self.next += 1;
+            if let Some(id) = self.fixed[self.next - 1] {
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: ASTNode,
+    pub span: Span,
+    pub row: usize,
+    pub col: usize,
+    pub parent: Option<NodeId>,
+}
+
+// Árbol sintáctico guardado en un arena. Los hijos se crean antes que el padre,
+// así que `alloc` puede enlazar los padres en el momento.
+#[derive(Debug, Clone, Default)]
+pub struct Ast {
+    nodes: Vec<Node>,
+    root: Option<NodeId>,
+}
+
+impl Ast {
+    pub fn new() -> Self {
+        Ast::default()
+    }
+
+    pub fn alloc(&mut self, kind: ASTNode, span: Span, row: usize, col: usize) -> NodeId {
+        let id = NodeId(self.nodes.len() as u32);
+        for child in kind.children() {
+            self.nodes[child.index()].parent = Some(id);
+        }
+        self.nodes.push(Node { kind, span, row, col, parent: None });
+        id
+    }
+
+    pub fn root(&self) -> Option<NodeId> {
+        self.root
+    }
+
+    pub fn set_root(&mut self, root: NodeId) {
+        self.root = Some(root);
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len() as u32).map(NodeId)
+    }
+
+    pub fn kind(&self, id: NodeId) -> &ASTNode {
+        &self[id].kind
+    }
+
+    pub fn span(&self, id: NodeId) -> Span {
+        self[id].span
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self[id].parent
+    }
+
+    pub fn children(&self, id: NodeId) -> Children<'_> {
+        self[id].kind.children()
+    }
+
+    // El propio nodo y luego sus ancestros hasta la raíz.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(Some(id), move |&id| self.parent(id))
+    }
+
+    // Nodo más profundo cuyo rango contiene el offset, p. ej. la posición del
+    // cursor en un editor.
+    pub fn node_at(&self, offset: usize) -> Option<NodeId> {
+        let mut current = self.root.filter(|&root| self.span(root).contains(offset))?;
+        'descend: loop {
+            for child in self.children(current) {
+                if self.span(child).contains(offset) {
+                    current = child;
+                    continue 'descend;
+                }
+            }
+            return Some(current);
+        }
+    }
+}
+
+impl Index<NodeId> for Ast {
+    type Output = Node;
+
+    fn index(&self, id: NodeId) -> &Node {
+        &self.nodes[id.index()]
+    }
+}
+
+impl Ast {
+    pub fn to_custom_string(&self, id: NodeId) -> String {
+        match &self[id].kind {
+            ASTNode::Let(declarations, command) => format!(
+                "let(\n   {},\n   {}\n)",
+                self.to_custom_string(*declarations),
+                self.to_custom_string(*command)
+            ),
+            ASTNode::Const(name, value) => format!(
+                "const(\n   name(\"{}\"),\n   value(\n      {}\n   )\n)",
+                name,
+                self.to_custom_string(*value)
+            ),
+            ASTNode::Var(name, type_denoter) => format!(
+                "var(name(\"{}\"),{})",
+                name,
+                self.to_custom_string(*type_denoter)
+            ),
+            ASTNode::Func(name, params, return_type, body) => format!(
+                "func(\n   name(\"{}\"),\n   params([{}]),\n   type({}),\n   result(\n      {}\n   )\n)",
+                name,
+                params.iter().map(|p| self.to_custom_string(*p)).collect::<Vec<_>>().join(","),
+                self.to_custom_string(*return_type),
+                self.to_custom_string(*body)
+            ),
+            ASTNode::Assign(vname, expr) => format!(
+                "assign({}, {})",
+                match self.vname_path(*vname) {
+                    Some(path) => format!("{:?}", path),
+                    None => self.to_custom_string(*vname),
+                },
+                self.to_custom_string(*expr)
+            ),
+            ASTNode::If(cond, _, then_branch, _, else_branch) => format!(
+                "ifCmd(\n   cond({}),\n   then(\n      {}\n   ),\n   else({})\n)",
+                self.to_custom_string(*cond),
+                self.to_custom_string(*then_branch),
+                self.to_custom_string(*else_branch)
+            ),
+            ASTNode::Call(name, params) => format!(
+                "call(\"{}\",params([{}]))",
+                name,
+                params.iter().map(|p| self.to_custom_string(*p)).collect::<Vec<_>>().join(",")
+            ),
+            ASTNode::Operator(op, left, right) => format!(
+                "op(\n   {},\n   {},\n   {}\n)",
+                op,
+                self.to_custom_string(*left),
+                self.to_custom_string(*right)
+            ),
+            ASTNode::LetExpression(declarations, expr) => format!(
+                "letExp(\n   {},\n   {}\n)",
+                self.to_custom_string(*declarations),
+                self.to_custom_string(*expr)
+            ),
+            ASTNode::IfExpression(cond, then_expr, else_expr) => format!(
+                "ifExp(\n   cond({}),\n   then({}),\n   else({})\n)",
+                self.to_custom_string(*cond),
+                self.to_custom_string(*then_expr),
+                self.to_custom_string(*else_expr)
+            ),
+            ASTNode::Number(num) => format!("num({})", num),
+            ASTNode::Char(c) => format!("char({})", *c as u8),
+            ASTNode::Identifier(name) => format!("ref([\"{}\"])", name),
+            ASTNode::Field(record, field) => match self.vname_path(id) {
+                Some(path) => format!("ref({:?})", path),
+                None => format!("field({},\"{}\")", self.to_custom_string(*record), field),
+            },
+            ASTNode::Index(array, index) => format!(
+                "index({},{})",
+                self.to_custom_string(*array),
+                self.to_custom_string(*index)
+            ),
+            ASTNode::Declaration(declarations) => format!(
+                "declaration(\n   [{}]\n)",
+                declarations.iter().map(|d| self.to_custom_string(*d)).collect::<Vec<_>>().join(",\n   ")
+            ),
+            ASTNode::Command(commands) => format!(
+                "command(\n   [{}]\n)",
+                commands.iter().map(|c| self.to_custom_string(*c)).collect::<Vec<_>>().join(",\n   ")
+            ),
+            ASTNode::Expression(expr) => self.to_custom_string(*expr),
+            ASTNode::Proc(name, params, body) => format!(
+                "proc(\n   name(\"{}\"),\n   params([{}]),\n   body(\n      {}\n   )\n)",
+                name,
+                params.iter().map(|p| self.to_custom_string(*p)).collect::<Vec<_>>().join(","),
+                self.to_custom_string(*body)
+            ),
+            ASTNode::While(cond, body) => format!(
+                "whileCmd(\n   cond({}),\n   do(\n      {}\n   )\n)",
+                self.to_custom_string(*cond),
+                self.to_custom_string(*body)
+            ),
+            ASTNode::Empty => "emptyCmd".to_string(),
+            ASTNode::TypeName(name) => format!("typeName(\"{}\")", name),
+            ASTNode::Type(name, type_denoter) => format!(
+                "typeDecl(name(\"{}\"),{})",
+                name,
+                self.to_custom_string(*type_denoter)
+            ),
+            ASTNode::ArrayType(size, elem) => format!("arrayType({},{})", size, self.to_custom_string(*elem)),
+            ASTNode::RecordType(fields) => format!(
+                "recordType([{}])",
+                fields.iter().map(|f| self.to_custom_string(*f)).collect::<Vec<_>>().join(",")
+            ),
+            ASTNode::FieldType(name, type_denoter) => format!(
+                "field(name(\"{}\"),{})",
+                name,
+                self.to_custom_string(*type_denoter)
+            ),
+            ASTNode::Unary(op, operand) => format!(
+                "unaryOp(\n   {},\n   {}\n)",
+                op,
+                self.to_custom_string(*operand)
+            ),
+            ASTNode::ArrayAggregate(elems) => format!(
+                "array([{}])",
+                elems.iter().map(|e| self.to_custom_string(*e)).collect::<Vec<_>>().join(",")
+            ),
+            ASTNode::RecordAggregate(fields) => format!(
+                "record([{}])",
+                fields.iter().map(|f| self.to_custom_string(*f)).collect::<Vec<_>>().join(",")
+            ),
+            ASTNode::FieldInit(name, expr) => format!(
+                "fieldInit(name(\"{}\"),{})",
+                name,
+                self.to_custom_string(*expr)
+            ),
+            ASTNode::ValueParam(name, type_denoter) => format!(
+                "valueParam(name(\"{}\"),{})",
+                name,
+                self.to_custom_string(*type_denoter)
+            ),
+            ASTNode::VarParam(name, type_denoter) => format!(
+                "varParam(name(\"{}\"),{})",
+                name,
+                self.to_custom_string(*type_denoter)
+            ),
+            ASTNode::ProcParam(name, params) => format!(
+                "procParam(name(\"{}\"),params([{}]))",
+                name,
+                params.iter().map(|p| self.to_custom_string(*p)).collect::<Vec<_>>().join(",")
+            ),
+            ASTNode::FuncParam(name, params, return_type) => format!(
+                "funcParam(name(\"{}\"),params([{}]),type({}))",
+                name,
+                params.iter().map(|p| self.to_custom_string(*p)).collect::<Vec<_>>().join(","),
+                self.to_custom_string(*return_type)
+            ),
+            ASTNode::VarActual(vname) => format!("varActual({})", self.to_custom_string(*vname)),
+            ASTNode::ProcActual(name) => format!("procActual(\"{}\")", name),
+            ASTNode::FuncActual(name) => format!("funcActual(\"{}\")", name),
+        }
+    }
+
+    // Un V-name formado solo por identificadores y campos, p. ej. r.punto.x
+    pub fn vname_path(&self, id: NodeId) -> Option<Vec<Symbol>> {
+        match self.kind(id) {
+            ASTNode::Identifier(name) => Some(vec![*name]),
+            ASTNode::Field(record, field) => {
+                let mut path = self.vname_path(*record)?;
+                path.push(*field);
+                Some(path)
+            }
+            _ => None,
+        }
+    }
+}
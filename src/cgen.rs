@@ -0,0 +1,721 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::checker::TypeInfo;
+use crate::identification::{Decl, Identification};
+use crate::stdenv::{self, Primitive, Standard, MAXINT};
+use crate::types::{Param, Signature, Type};
+
+// Traductor a C99. C no tiene funciones anidadas, así que cada rutina (y el
+// programa principal) tiene un struct de marco con sus parámetros y todas las
+// variables y constantes de su cuerpo, más un puntero `up` al marco donde fue
+// declarada. Un nombre de un nivel exterior se alcanza con `fr->up->...`.
+// Los arreglos y registros son structs, así se copian con `=` y se pasan por
+// valor; todos los escalares son `long`. Un parámetro `var` es un puntero y
+// uno `proc` o `func` un `tri_closure` con la función y el marco `up` que
+// recibe; por eso las funciones reciben `up` como `void *`. Tras `up` cada
+// rutina recibe la fila y la columna de la llamada, para informar en ella el
+// desborde de la pila.
+pub fn emit(ast: &Ast, identification: &Identification, types: &TypeInfo) -> Result<String, String> {
+    let mut emitter = Emitter {
+        ast,
+        identification,
+        types,
+        frames: Vec::new(),
+        owners: HashMap::new(),
+        type_names: HashMap::new(),
+        typedefs: String::new(),
+        prototypes: String::new(),
+        functions: String::new(),
+        level: 0,
+        indent: 1,
+    };
+    let Some(root) = ast.root() else {
+        return Err("empty program".to_string());
+    };
+    emitter.frames.push(Frame { routine: None, members: Vec::new() });
+    emitter.collect(root, 0, 0);
+
+    let mut body = String::new();
+    emitter.command(&mut body, root)?;
+
+    let mut frames = String::new();
+    for i in 0..emitter.frames.len() {
+        let routine = emitter.frames[i].routine;
+        let mut members = String::new();
+        if let Some(routine) = routine {
+            let (parent, _) = emitter.owners[&routine];
+            let parent = emitter.frames[parent].routine;
+            writeln!(members, "    struct {} *up;", frame_name(ast, parent)).unwrap();
+        }
+        for member in emitter.frames[i].members.clone() {
+            let ty = emitter.member_type(member);
+            writeln!(members, "    {} {};", ty, emitter.member_name(member)).unwrap();
+        }
+        if members.is_empty() {
+            members.push_str("    char unused;\n");
+        }
+        writeln!(frames, "\nstruct {} {{\n{}}};", frame_name(ast, routine), members).unwrap();
+    }
+
+    let mut out = String::from(PRELUDE);
+    out.push_str(&emitter.typedefs);
+    out.push('\n');
+    for frame in &emitter.frames {
+        writeln!(out, "struct {};", frame_name(ast, frame.routine)).unwrap();
+    }
+    out.push_str(&frames);
+    out.push('\n');
+    out.push_str(&emitter.prototypes);
+    out.push_str(&emitter.functions);
+    writeln!(out, "\nint main(void) {{").unwrap();
+    writeln!(out, "    struct f_main frame = {{0}}, *fr = &frame;").unwrap();
+    writeln!(out, "    (void)fr;").unwrap();
+    out.push_str(&body);
+    writeln!(out, "    return 0;\n}}").unwrap();
+    Ok(out)
+}
+
+const PRELUDE: &str = r#"#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef struct {
+    void (*code)(void);
+    void *up;
+} tri_closure;
+
+static inline void tri_error(const char *message, int row, int col) {
+    fflush(stdout);
+    fprintf(stderr, "Runtime error: %s at row %d, col %d\n", message, row, col);
+    exit(1);
+}
+
+static inline long tri_check(long value, int row, int col) {
+    if (value > 32767 || value < -32767) tri_error("integer overflow", row, col);
+    return value;
+}
+
+static inline long tri_div(long left, long right, int row, int col) {
+    if (right == 0) tri_error("division by zero", row, col);
+    return tri_check(left / right, row, col);
+}
+
+static inline long tri_mod(long left, long right, int row, int col) {
+    long r;
+    if (right == 0) tri_error("division by zero", row, col);
+    r = left % right;
+    return r < 0 ? r + labs(right) : r;
+}
+
+static inline long tri_index(long index, long bound, int row, int col) {
+    if (index < 0 || index >= bound) tri_error("array index out of range", row, col);
+    return index;
+}
+
+static inline long tri_chr(long code, int row, int col) {
+    if (code < 0 || code > 0x10FFFF || (code >= 0xD800 && code <= 0xDFFF)) tri_error("invalid character code", row, col);
+    return code;
+}
+
+static inline long tri_peek(void) {
+    int c = getchar();
+    if (c != EOF) ungetc(c, stdin);
+    return c;
+}
+
+static inline long tri_eof(void) { return tri_peek() == EOF; }
+static inline long tri_eol(void) { return tri_peek() == '\n'; }
+
+static inline void tri_get(long *c) {
+    static const long min[] = {0, 0x80, 0x800, 0x10000};
+    int ch = getchar(), n;
+    long code;
+    if (ch < 0x80) {
+        *c = ch == EOF ? -1 : ch;
+        return;
+    }
+    *c = 0xFFFD;
+    n = ch < 0xC0 ? 0 : ch < 0xE0 ? 1 : ch < 0xF0 ? 2 : ch < 0xF8 ? 3 : 0;
+    if (n == 0) return;
+    code = ch & (0x3F >> n);
+    for (int i = 0; i < n; i++) {
+        if ((tri_peek() & 0xC0) != 0x80) return;
+        code = code << 6 | (getchar() & 0x3F);
+    }
+    if (code >= min[n] && code <= 0x10FFFF && (code < 0xD800 || code > 0xDFFF)) *c = code;
+}
+
+static inline void tri_geteol(void) {
+    int c;
+    while ((c = getchar()) != EOF && c != '\n') {
+    }
+}
+
+static inline void tri_getint(long *value, int row, int col) {
+    char text[32];
+    int n = 0, c;
+    while ((c = getchar()) == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\f' || c == '\v') {
+    }
+    if (c == '-' || c == '+') {
+        text[n++] = (char)c;
+        c = getchar();
+    }
+    while (c >= '0' && c <= '9' && n < 30) {
+        text[n++] = (char)c;
+        c = getchar();
+    }
+    if (c != EOF) ungetc(c, stdin);
+    text[n] = '\0';
+    if (n == 0 || (n == 1 && (text[0] == '-' || text[0] == '+')) || labs(strtol(text, NULL, 10)) > 32767)
+        tri_error("invalid integer in input", row, col);
+    *value = strtol(text, NULL, 10);
+}
+
+static inline void tri_put(long c) {
+    if (c < 0x80) {
+        putchar((int)c);
+    } else if (c < 0x800) {
+        putchar((int)(0xC0 | (c >> 6)));
+        putchar((int)(0x80 | (c & 0x3F)));
+    } else if (c < 0x10000) {
+        putchar((int)(0xE0 | (c >> 12)));
+        putchar((int)(0x80 | ((c >> 6) & 0x3F)));
+        putchar((int)(0x80 | (c & 0x3F)));
+    } else {
+        putchar((int)(0xF0 | (c >> 18)));
+        putchar((int)(0x80 | ((c >> 12) & 0x3F)));
+        putchar((int)(0x80 | ((c >> 6) & 0x3F)));
+        putchar((int)(0x80 | (c & 0x3F)));
+    }
+}
+
+static inline void tri_putint(long value) { printf("%ld", value); }
+static inline void tri_puteol(void) { putchar('\n'); }
+
+static int tri_depth;
+
+static inline void tri_enter(int row, int col) {
+    if (++tri_depth > 2000) tri_error("stack overflow", row, col);
+}
+"#;
+
+// Marco de una rutina (o del programa principal si `routine` es `None`).
+struct Frame {
+    routine: Option<NodeId>,
+    members: Vec<NodeId>,
+}
+
+struct Emitter<'a> {
+    ast: &'a Ast,
+    identification: &'a Identification,
+    types: &'a TypeInfo,
+    frames: Vec<Frame>,
+    // Para cada declaración: índice del marco que la contiene y su nivel.
+    owners: HashMap<NodeId, (usize, usize)>,
+    type_names: HashMap<Type, String>,
+    typedefs: String,
+    prototypes: String,
+    functions: String,
+    level: usize,
+    indent: usize,
+}
+
+fn frame_name(ast: &Ast, routine: Option<NodeId>) -> String {
+    match routine {
+        Some(id) => format!("f{}_{}", id.index(), routine_symbol(ast, id)),
+        None => "f_main".to_string(),
+    }
+}
+
+// Parte del nombre en C que viene del nombre de la rutina; un operador no es
+// un identificador válido.
+fn routine_symbol(ast: &Ast, id: NodeId) -> String {
+    match ast.kind(id) {
+        ASTNode::Func(name, ..) if name.is_operator() => "operator".to_string(),
+        ASTNode::Func(name, ..) | ASTNode::Proc(name, ..) => name.to_string(),
+        _ => unreachable!(),
+    }
+}
+
+impl Emitter<'_> {
+    fn error<T>(&self, id: NodeId, message: &str) -> Result<T, String> {
+        let node = &self.ast[id];
+        Err(format!("{} at row {}, col {}", message, node.row, node.col))
+    }
+
+    fn position(&self, id: NodeId) -> String {
+        let node = &self.ast[id];
+        format!("{}, {}", node.row, node.col)
+    }
+
+    // Asigna cada declaración al marco de la rutina que la contiene.
+    fn collect(&mut self, id: NodeId, frame: usize, level: usize) {
+        match self.ast.kind(id) {
+            ASTNode::Const(..) | ASTNode::Var(..) => {
+                self.owners.insert(id, (frame, level));
+                self.frames[frame].members.push(id);
+            }
+            ASTNode::Func(_, params, ..) | ASTNode::Proc(_, params, ..) => {
+                self.owners.insert(id, (frame, level));
+                let inner = self.frames.len();
+                self.frames.push(Frame { routine: Some(id), members: Vec::new() });
+                for &param in params {
+                    self.owners.insert(param, (inner, level + 1));
+                    self.frames[inner].members.push(param);
+                }
+                let skip = params.len();
+                for child in self.ast.children(id).skip(skip) {
+                    self.collect(child, inner, level + 1);
+                }
+                return;
+            }
+            _ => {}
+        }
+        for child in self.ast.children(id) {
+            self.collect(child, frame, level);
+        }
+    }
+
+    fn c_type(&mut self, ty: &Type) -> String {
+        match ty {
+            Type::Array(..) | Type::Record(..) => {}
+            _ => return "long".to_string(),
+        }
+        if let Some(name) = self.type_names.get(ty) {
+            return name.clone();
+        }
+        let body = match ty {
+            Type::Array(n, elem) => format!("    {} e[{}];\n", self.c_type(elem), n),
+            Type::Record(fields) => {
+                let mut body = String::new();
+                for (name, field) in fields {
+                    writeln!(body, "    {} f_{};", self.c_type(field), name).unwrap();
+                }
+                body
+            }
+            _ => unreachable!(),
+        };
+        let name = format!("t{}", self.type_names.len());
+        writeln!(self.typedefs, "\ntypedef struct {{\n{}}} {};", body, name).unwrap();
+        writeln!(
+            self.typedefs,
+            "static inline int eq_{0}({0} a, {0} b) {{ return memcmp(&a, &b, sizeof a) == 0; }}",
+            name
+        )
+        .unwrap();
+        self.type_names.insert(ty.clone(), name.clone());
+        name
+    }
+
+    fn member_name(&self, decl: NodeId) -> String {
+        let name = match self.ast.kind(decl) {
+            ASTNode::Const(name, _)
+            | ASTNode::Var(name, _)
+            | ASTNode::ValueParam(name, _)
+            | ASTNode::VarParam(name, _)
+            | ASTNode::ProcParam(name, _)
+            | ASTNode::FuncParam(name, ..) => *name,
+            _ => unreachable!(),
+        };
+        format!("v{}_{}", decl.index(), name)
+    }
+
+    // Tipo de C de un miembro del marco.
+    fn member_type(&mut self, decl: NodeId) -> String {
+        let ty = self.c_type(self.types.type_of(decl));
+        match self.ast.kind(decl) {
+            ASTNode::VarParam(..) => format!("{} *", ty),
+            ASTNode::ProcParam(..) | ASTNode::FuncParam(..) => "tri_closure".to_string(),
+            _ => ty,
+        }
+    }
+
+    // Tipo del puntero a una función con la firma dada, para llamar a una
+    // clausura.
+    fn function_type(&mut self, signature: &Signature) -> String {
+        let mut args = "void *, int, int".to_string();
+        for param in &signature.params {
+            let ty = match param {
+                Param::Value(ty) => self.c_type(ty),
+                Param::Var(ty) => format!("{} *", self.c_type(ty)),
+                Param::Routine(_) => "tri_closure".to_string(),
+            };
+            write!(args, ", {}", ty).unwrap();
+        }
+        match &signature.result {
+            Some(result) => format!("{} (*)({})", self.c_type(result), args),
+            None => format!("void (*)({})", args),
+        }
+    }
+
+    // Puntero al marco de nivel `level` visto desde el marco actual.
+    fn frame_at(&self, level: usize) -> String {
+        let mut path = "fr".to_string();
+        for _ in level..self.level {
+            path.push_str("->up");
+        }
+        path
+    }
+
+    fn line(&self, out: &mut String, text: &str) {
+        for _ in 0..self.indent {
+            out.push_str("    ");
+        }
+        out.push_str(text);
+        out.push('\n');
+    }
+
+    fn command(&mut self, out: &mut String, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Let(declarations, command) => {
+                self.declaration(out, *declarations)?;
+                self.command(out, *command)?;
+            }
+            ASTNode::Command(commands) => {
+                for &command in commands {
+                    self.command(out, command)?;
+                }
+            }
+            ASTNode::Assign(vname, expr) => {
+                let value = self.expression(*expr)?;
+                let target = self.vname(*vname)?;
+                self.line(out, &format!("{} = {};", target, value));
+            }
+            ASTNode::Call(_, args) => {
+                let call = self.call(id, args)?;
+                self.line(out, &format!("{};", call));
+            }
+            ASTNode::If(cond, _, then_branch, _, else_branch) => {
+                let cond = self.expression(*cond)?;
+                self.line(out, &format!("if ({}) {{", cond));
+                self.indent += 1;
+                self.command(out, *then_branch)?;
+                self.indent -= 1;
+                self.line(out, "} else {");
+                self.indent += 1;
+                self.command(out, *else_branch)?;
+                self.indent -= 1;
+                self.line(out, "}");
+            }
+            ASTNode::While(cond, body) => {
+                let cond = self.expression(*cond)?;
+                self.line(out, &format!("while ({}) {{", cond));
+                self.indent += 1;
+                self.command(out, *body)?;
+                self.indent -= 1;
+                self.line(out, "}");
+            }
+            ASTNode::Empty => {}
+            _ => return self.error(id, "not a command"),
+        }
+        Ok(())
+    }
+
+    fn declaration(&mut self, out: &mut String, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Declaration(declarations) => {
+                for &declaration in declarations {
+                    self.declaration(out, declaration)?;
+                }
+            }
+            ASTNode::Const(_, expr) => {
+                let value = self.expression(*expr)?;
+                let target = format!("fr->{}", self.member_name(id));
+                self.line(out, &format!("{} = {};", target, value));
+            }
+            // Las variables ya están en el marco, inicializadas en cero.
+            ASTNode::Var(..) => {
+                let ty = self.types.type_of(id).clone();
+                self.c_type(&ty);
+            }
+            ASTNode::Func(_, params, _, body) => {
+                let (params, body) = (params.clone(), *body);
+                // El resultado se calcula antes de salir, así las llamadas del
+                // cuerpo cuentan para la profundidad.
+                self.routine(id, &params, |e, out| {
+                    let value = e.expression(body)?;
+                    let ty = e.types.type_of(body).clone();
+                    let ty = e.c_type(&ty);
+                    e.line(out, &format!("{} result = {};", ty, value));
+                    e.line(out, "tri_depth--;");
+                    e.line(out, "return result;");
+                    Ok(())
+                })?;
+            }
+            ASTNode::Proc(_, params, body) => {
+                let (params, body) = (params.clone(), *body);
+                self.routine(id, &params, |e, out| {
+                    e.command(out, body)?;
+                    e.line(out, "tri_depth--;");
+                    Ok(())
+                })?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn routine(
+        &mut self,
+        id: NodeId,
+        params: &[NodeId],
+        body: impl FnOnce(&mut Self, &mut String) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let frame = frame_name(self.ast, Some(id));
+        let result = match self.types.signatures.get(&id).and_then(|s| s.result.clone()) {
+            Some(ty) => self.c_type(&ty),
+            None => "void".to_string(),
+        };
+        let mut args = "void *up, int row, int col".to_string();
+        for (i, &param) in params.iter().enumerate() {
+            write!(args, ", {} a{}", self.member_type(param), i).unwrap();
+        }
+
+        let (saved_level, saved_indent) = (self.level, self.indent);
+        self.level += 1;
+        self.indent = 1;
+        let signature = format!("static {} {}({})", result, self.routine_name(id), args);
+        writeln!(self.prototypes, "{};", signature).unwrap();
+        let mut text = String::new();
+        writeln!(text, "\n{} {{", signature).unwrap();
+        self.line(&mut text, &format!("struct {} frame = {{0}}, *fr = &frame;", frame));
+        self.line(&mut text, "fr->up = up;");
+        for (i, &param) in params.iter().enumerate() {
+            self.line(&mut text, &format!("fr->{} = a{};", self.member_name(param), i));
+        }
+        self.line(&mut text, "tri_enter(row, col);");
+        body(self, &mut text)?;
+        text.push_str("}\n");
+        self.level = saved_level;
+        self.indent = saved_indent;
+        self.functions.push_str(&text);
+        Ok(())
+    }
+
+    fn routine_name(&self, id: NodeId) -> String {
+        format!("r{}_{}", id.index(), routine_symbol(self.ast, id))
+    }
+
+    // Un operador declarado en el programa se llama como una función.
+    fn call(&mut self, id: NodeId, args: &[NodeId]) -> Result<String, String> {
+        match self.types.operator(id).or_else(|| self.identification.decl(id)) {
+            Some(Decl::Standard(name)) => match stdenv::lookup(name) {
+                Some(Standard::Func(_, primitive)) | Some(Standard::Proc(_, primitive)) => {
+                    self.standard_call(id, primitive, args)
+                }
+                _ => self.error(id, "not a routine"),
+            },
+            Some(Decl::Node(decl)) => {
+                let (_, level) = self.owners[&decl];
+                let mut call = match self.ast.kind(decl) {
+                    ASTNode::ProcParam(..) | ASTNode::FuncParam(..) => {
+                        let closure = format!("{}->{}", self.frame_at(level), self.member_name(decl));
+                        let signature = self.types.signatures[&decl].clone();
+                        let function = self.function_type(&signature);
+                        format!("(({}){}.code)({}.up, {}", function, closure, closure, self.position(id))
+                    }
+                    _ => format!("{}({}, {}", self.routine_name(decl), self.frame_at(level), self.position(id)),
+                };
+                for &arg in args {
+                    let arg = match self.ast.kind(arg) {
+                        ASTNode::VarActual(vname) => format!("&{}", self.vname(*vname)?),
+                        ASTNode::ProcActual(_) | ASTNode::FuncActual(_) => self.closure(arg)?,
+                        _ => self.expression(arg)?,
+                    };
+                    write!(call, ", {}", arg).unwrap();
+                }
+                call.push(')');
+                Ok(call)
+            }
+            None => self.error(id, "undeclared routine"),
+        }
+    }
+
+    // Clausura de la rutina que se pasa como argumento `proc P` o `func F`.
+    fn closure(&mut self, arg: NodeId) -> Result<String, String> {
+        let Some(Decl::Node(decl)) = self.identification.decl(arg) else {
+            return self.error(arg, "not a routine");
+        };
+        let (_, level) = self.owners[&decl];
+        match self.ast.kind(decl) {
+            ASTNode::ProcParam(..) | ASTNode::FuncParam(..) => {
+                Ok(format!("{}->{}", self.frame_at(level), self.member_name(decl)))
+            }
+            _ => Ok(format!("(tri_closure){{(void (*)(void)){}, {}}}", self.routine_name(decl), self.frame_at(level))),
+        }
+    }
+
+    fn standard_call(&mut self, id: NodeId, primitive: Primitive, args: &[NodeId]) -> Result<String, String> {
+        let pos = self.position(id);
+        Ok(match primitive {
+            Primitive::Ord => self.expression(args[0])?,
+            Primitive::Chr => format!("tri_chr({}, {})", self.expression(args[0])?, pos),
+            Primitive::Eof => "tri_eof()".to_string(),
+            Primitive::Eol => "tri_eol()".to_string(),
+            Primitive::Get => format!("tri_get(&{})", self.vname(args[0])?),
+            Primitive::Put => format!("tri_put({})", self.expression(args[0])?),
+            Primitive::GetInt => format!("tri_getint(&{}, {})", self.vname(args[0])?, pos),
+            Primitive::PutInt => format!("tri_putint({})", self.expression(args[0])?),
+            Primitive::GetEol => "tri_geteol()".to_string(),
+            Primitive::PutEol => "tri_puteol()".to_string(),
+            _ => return self.error(id, "not a routine"),
+        })
+    }
+
+    fn expression(&mut self, id: NodeId) -> Result<String, String> {
+        Ok(match self.ast.kind(id) {
+            ASTNode::Number(n) => {
+                if *n > MAXINT {
+                    return self.error(id, "integer literal too large");
+                }
+                format!("{}L", n)
+            }
+            ASTNode::Char(c) => format!("{}L", *c as u32),
+            ASTNode::Expression(inner) => self.expression(*inner)?,
+            ASTNode::Identifier(_) | ASTNode::Field(..) | ASTNode::Index(..) => {
+                if let Some(Decl::Standard(name)) = self.identification.decl(id) {
+                    if let Some(Standard::Const(_, value)) = stdenv::lookup(name) {
+                        return Ok(format!("{}L", value));
+                    }
+                }
+                self.vname(id)?
+            }
+            ASTNode::Call(_, args) => self.call(id, args)?,
+            ASTNode::Operator(_, left, right) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*left, *right])?
+            }
+            ASTNode::Unary(_, operand) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*operand])?
+            }
+            ASTNode::Operator(_, left, right) => {
+                let (left_id, right_id) = (*left, *right);
+                let left = self.expression(left_id)?;
+                let right = self.expression(right_id)?;
+                let pos = self.position(id);
+                match self.operator(id) {
+                    Some(primitive @ (Primitive::Eq | Primitive::Ne)) => {
+                        let not = if primitive == Primitive::Ne { "!" } else { "" };
+                        match self.types.type_of(left_id).clone() {
+                            ty @ (Type::Array(..) | Type::Record(..)) => {
+                                format!("{}eq_{}({}, {})", not, self.c_type(&ty), left, right)
+                            }
+                            _ => format!("({} {}= {})", left, if not.is_empty() { "=" } else { "!" }, right),
+                        }
+                    }
+                    Some(Primitive::And) => format!("(long)(({}) & ({}))", left, right),
+                    Some(Primitive::Or) => format!("(long)(({}) | ({}))", left, right),
+                    Some(Primitive::Add) => format!("tri_check({} + {}, {})", left, right, pos),
+                    Some(Primitive::Sub) => format!("tri_check({} - {}, {})", left, right, pos),
+                    Some(Primitive::Mul) => format!("tri_check({} * {}, {})", left, right, pos),
+                    Some(Primitive::Div) => format!("tri_div({}, {}, {})", left, right, pos),
+                    Some(Primitive::Mod) => format!("tri_mod({}, {}, {})", left, right, pos),
+                    Some(Primitive::Lt) => format!("(long)({} < {})", left, right),
+                    Some(Primitive::Le) => format!("(long)({} <= {})", left, right),
+                    Some(Primitive::Gt) => format!("(long)({} > {})", left, right),
+                    Some(Primitive::Ge) => format!("(long)({} >= {})", left, right),
+                    _ => return self.error(id, "not a binary operator"),
+                }
+            }
+            ASTNode::Unary(_, operand) => {
+                let operand = self.expression(*operand)?;
+                match self.operator(id) {
+                    Some(Primitive::Not) => format!("(long)!({})", operand),
+                    _ => return self.error(id, "not a unary operator"),
+                }
+            }
+            ASTNode::ArrayAggregate(elems) => {
+                let ty = self.types.type_of(id).clone();
+                let name = self.c_type(&ty);
+                let values = elems.iter().map(|&e| self.expression(e)).collect::<Result<Vec<_>, _>>()?;
+                format!("(({}){{{{{}}}}})", name, values.join(", "))
+            }
+            ASTNode::RecordAggregate(fields) => {
+                let ty = self.types.type_of(id).clone();
+                let name = self.c_type(&ty);
+                let mut values = Vec::new();
+                for &field in fields {
+                    if let ASTNode::FieldInit(_, expr) = self.ast.kind(field) {
+                        values.push(self.expression(*expr)?);
+                    }
+                }
+                format!("(({}){{{}}})", name, values.join(", "))
+            }
+            // Las constantes se asignan en el marco con el operador coma.
+            ASTNode::LetExpression(declarations, expr) => {
+                let mut parts = Vec::new();
+                self.bindings(*declarations, &mut parts)?;
+                parts.push(self.expression(*expr)?);
+                format!("({})", parts.join(", "))
+            }
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                let cond = self.expression(*cond)?;
+                let then_value = self.expression(*then_expr)?;
+                let else_value = self.expression(*else_expr)?;
+                format!("({} ? {} : {})", cond, then_value, else_value)
+            }
+            _ => return self.error(id, "not an expression"),
+        })
+    }
+
+    // Declaraciones de una expresión `let` como expresiones de C; las rutinas
+    // y las variables no necesitan nada en el lugar.
+    fn bindings(&mut self, id: NodeId, parts: &mut Vec<String>) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Declaration(declarations) => {
+                for &declaration in declarations {
+                    self.bindings(declaration, parts)?;
+                }
+            }
+            ASTNode::Const(_, expr) => {
+                let value = self.expression(*expr)?;
+                parts.push(format!("fr->{} = {}", self.member_name(id), value));
+            }
+            _ => self.declaration(&mut String::new(), id)?,
+        }
+        Ok(())
+    }
+
+    fn operator(&self, id: NodeId) -> Option<Primitive> {
+        let Decl::Standard(name) = self.types.operator(id)? else {
+            return None;
+        };
+        match stdenv::lookup(name)? {
+            Standard::Unary(.., primitive) | Standard::Binary(.., primitive) | Standard::Equality(primitive) => {
+                Some(primitive)
+            }
+            _ => None,
+        }
+    }
+
+    // Lvalue de C para un V-name.
+    fn vname(&mut self, id: NodeId) -> Result<String, String> {
+        match self.ast.kind(id) {
+            ASTNode::Identifier(_) => match self.identification.decl(id) {
+                Some(Decl::Node(decl)) if self.owners.contains_key(&decl) => {
+                    let (_, level) = self.owners[&decl];
+                    let member = format!("{}->{}", self.frame_at(level), self.member_name(decl));
+                    match self.ast.kind(decl) {
+                        ASTNode::VarParam(..) => Ok(format!("(*{})", member)),
+                        _ => Ok(member),
+                    }
+                }
+                _ => self.error(id, "not a variable"),
+            },
+            ASTNode::Field(record, field) => Ok(format!("{}.f_{}", self.vname(*record)?, field)),
+            ASTNode::Index(array, index) => {
+                let Type::Array(len, _) = self.types.type_of(*array) else {
+                    return self.error(id, "not an array");
+                };
+                let len = *len;
+                let array = self.vname(*array)?;
+                let index_pos = self.position(*index);
+                let index = self.expression(*index)?;
+                Ok(format!("{}.e[tri_index({}, {}, {})]", array, index, len, index_pos))
+            }
+            ASTNode::VarActual(vname) => self.vname(*vname),
+            _ => self.error(id, "not a variable"),
+        }
+    }
+}
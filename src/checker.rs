@@ -0,0 +1,580 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::diagnostic::Diagnostic;
+use crate::identification::{Decl, Identification};
+use crate::stdenv::{self, Standard};
+use crate::symbol::Symbol;
+use crate::types::{Param, Signature, Type};
+
+// Resultado del chequeo de tipos. `types` tiene el tipo de cada expresión,
+// V-name y denotador de tipo, y el de cada declaración de const, var, type o
+// parámetro; `signatures` el de cada func y proc; `operators` la declaración
+// que corresponde a cada operador según el tipo de sus operandos.
+#[derive(Debug, Default)]
+pub struct TypeInfo {
+    pub types: HashMap<NodeId, Type>,
+    pub signatures: HashMap<NodeId, Signature>,
+    pub operators: HashMap<NodeId, Decl>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TypeInfo {
+    pub fn type_of(&self, id: NodeId) -> &Type {
+        self.types.get(&id).unwrap_or(&Type::Error)
+    }
+
+    pub fn operator(&self, id: NodeId) -> Option<Decl> {
+        self.operators.get(&id).copied()
+    }
+}
+
+pub fn check(ast: &Ast, identification: &Identification) -> TypeInfo {
+    let mut checker = Checker { ast, identification, info: TypeInfo::default() };
+    if let Some(root) = ast.root() {
+        checker.command(root);
+    }
+    checker.info
+}
+
+// Lo que denota un nombre según su declaración.
+enum Entity {
+    Value(Type, bool),
+    Func(Signature),
+    Proc(Signature),
+    Type(Type),
+    Unary(Type, Type),
+    Binary(Type, Type, Type),
+    Equality,
+    Unknown,
+}
+
+struct Checker<'a> {
+    ast: &'a Ast,
+    identification: &'a Identification,
+    info: TypeInfo,
+}
+
+impl Checker<'_> {
+    fn error(&mut self, id: NodeId, message: String) {
+        self.info.diagnostics.push(Diagnostic::error(self.ast, id, message));
+    }
+
+    fn entity(&self, use_id: NodeId) -> Entity {
+        match self.identification.decl(use_id) {
+            Some(decl) => self.denoted(decl),
+            None => Entity::Unknown,
+        }
+    }
+
+    fn denoted(&self, decl: Decl) -> Entity {
+        match decl {
+            Decl::Node(decl) => match self.ast.kind(decl) {
+                ASTNode::Const(..) => Entity::Value(self.info.type_of(decl).clone(), false),
+                ASTNode::Var(..) | ASTNode::ValueParam(..) | ASTNode::VarParam(..) => {
+                    Entity::Value(self.info.type_of(decl).clone(), true)
+                }
+                ASTNode::Type(..) => Entity::Type(self.info.type_of(decl).clone()),
+                ASTNode::Func(..) | ASTNode::FuncParam(..) => {
+                    self.info.signatures.get(&decl).cloned().map_or(Entity::Unknown, Entity::Func)
+                }
+                ASTNode::Proc(..) | ASTNode::ProcParam(..) => {
+                    self.info.signatures.get(&decl).cloned().map_or(Entity::Unknown, Entity::Proc)
+                }
+                _ => Entity::Unknown,
+            },
+            Decl::Standard(name) => standard_entity(name),
+        }
+    }
+
+    // Elige entre las declaraciones visibles de un operador la primera que
+    // acepta estos operandos. Si ninguna los acepta y hay una sola de la misma
+    // aridad, se queda con ella para informar el error en detalle.
+    fn operator(&mut self, id: NodeId, op: Symbol, operands: &[&Type]) -> Entity {
+        let overloads = self.identification.overloads(id);
+        let same_arity: Vec<Decl> = overloads
+            .iter()
+            .copied()
+            .filter(|&decl| match self.denoted(decl) {
+                Entity::Unary(..) => operands.len() == 1,
+                Entity::Binary(..) | Entity::Equality => operands.len() == 2,
+                Entity::Func(signature) => signature.params.len() == operands.len(),
+                _ => false,
+            })
+            .collect();
+        let chosen = match overloads.iter().find(|&&decl| self.accepts(decl, operands)) {
+            Some(&decl) => decl,
+            None if same_arity.len() > 1 => {
+                let found: Vec<String> = operands.iter().map(|ty| ty.to_string()).collect();
+                self.error(id, format!("no operator \"{}\" accepts {}", op, found.join(" and ")));
+                return Entity::Unknown;
+            }
+            None => match same_arity.first().or(overloads.first()) {
+                Some(&decl) => decl,
+                None => return Entity::Unknown,
+            },
+        };
+        self.info.operators.insert(id, chosen);
+        self.denoted(chosen)
+    }
+
+    fn accepts(&self, decl: Decl, operands: &[&Type]) -> bool {
+        match (self.denoted(decl), operands) {
+            (Entity::Unary(arg, _), [operand]) => operand.equivalent(&arg),
+            (Entity::Binary(l, r, _), [left, right]) => left.equivalent(&l) && right.equivalent(&r),
+            (Entity::Equality, [left, right]) => left.equivalent(right),
+            (Entity::Func(signature), _) => {
+                signature.params.len() == operands.len()
+                    && signature.params.iter().zip(operands).all(|(param, operand)| match param {
+                        Param::Value(ty) => operand.equivalent(ty),
+                        _ => false,
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    fn command(&mut self, id: NodeId) {
+        match self.ast.kind(id) {
+            ASTNode::Let(declarations, command) => {
+                self.declaration(*declarations);
+                self.command(*command);
+            }
+            ASTNode::Command(commands) => {
+                for &command in commands {
+                    self.command(command);
+                }
+            }
+            ASTNode::Assign(vname, expr) => {
+                let (target, assignable) = self.vname(*vname);
+                let value = self.expression(*expr);
+                if !assignable {
+                    self.error(*vname, "cannot assign to a constant".to_string());
+                }
+                if !target.equivalent(&value) {
+                    self.error(id, format!("type mismatch in assignment: expected {}, found {}", target, value));
+                }
+            }
+            ASTNode::Call(name, args) => match self.entity(id) {
+                Entity::Proc(signature) => self.arguments(id, *name, &signature, args),
+                Entity::Func(signature) => {
+                    self.error(id, format!("\"{}\" is a function, not a procedure", name));
+                    self.arguments(id, *name, &signature, args);
+                }
+                entity => {
+                    if !matches!(entity, Entity::Unknown) {
+                        self.error(id, format!("\"{}\" is not a procedure", name));
+                    }
+                    for &arg in args {
+                        self.actual(arg);
+                    }
+                }
+            },
+            ASTNode::If(cond, _, then_branch, _, else_branch) => {
+                self.condition(*cond);
+                self.command(*then_branch);
+                self.command(*else_branch);
+            }
+            ASTNode::While(cond, body) => {
+                self.condition(*cond);
+                self.command(*body);
+            }
+            _ => self.declaration(id),
+        }
+    }
+
+    fn condition(&mut self, cond: NodeId) {
+        let ty = self.expression(cond);
+        if !ty.equivalent(&Type::Bool) {
+            self.error(cond, format!("condition must be Boolean, found {}", ty));
+        }
+    }
+
+    fn declaration(&mut self, id: NodeId) {
+        match self.ast.kind(id) {
+            ASTNode::Declaration(declarations) => {
+                for &declaration in declarations {
+                    self.declaration(declaration);
+                }
+                self.duplicate_operators(declarations);
+            }
+            ASTNode::Const(_, expr) => {
+                let ty = self.expression(*expr);
+                self.info.types.insert(id, ty);
+            }
+            ASTNode::Var(_, type_denoter)
+            | ASTNode::Type(_, type_denoter)
+            | ASTNode::ValueParam(_, type_denoter)
+            | ASTNode::VarParam(_, type_denoter) => {
+                let ty = self.type_denoter(*type_denoter);
+                self.info.types.insert(id, ty);
+            }
+            // La firma se registra antes de revisar el cuerpo para que las
+            // llamadas recursivas ya la encuentren.
+            ASTNode::Func(name, params, return_type, body) => {
+                let value_params = params.iter().all(|&p| matches!(self.ast.kind(p), ASTNode::ValueParam(..)));
+                if name.is_operator() && (!(1..=2).contains(&params.len()) || !value_params) {
+                    self.error(id, format!("operator \"{}\" must have one or two value parameters", name));
+                }
+                let params = self.parameters(params);
+                let result = self.type_denoter(*return_type);
+                self.info.signatures.insert(id, Signature { params, result: Some(result.clone()) });
+                let ty = self.expression(*body);
+                if !ty.equivalent(&result) {
+                    self.error(*body, format!("function \"{}\" must return {}, found {}", name, result, ty));
+                }
+            }
+            ASTNode::Proc(_, params, body) => {
+                let params = self.parameters(params);
+                self.info.signatures.insert(id, Signature { params, result: None });
+                self.command(*body);
+            }
+            ASTNode::ProcParam(_, params) => {
+                let params = self.parameters(params);
+                self.info.signatures.insert(id, Signature { params, result: None });
+            }
+            ASTNode::FuncParam(_, params, return_type) => {
+                let params = self.parameters(params);
+                let result = self.type_denoter(*return_type);
+                self.info.signatures.insert(id, Signature { params, result: Some(result) });
+            }
+            _ => {}
+        }
+    }
+
+    // Un operador puede declararse varias veces en un bloque, pero no dos con
+    // los mismos parámetros: ninguna de las dos se podría elegir.
+    fn duplicate_operators(&mut self, declarations: &[NodeId]) {
+        for (i, &id) in declarations.iter().enumerate() {
+            let ASTNode::Func(name, ..) = self.ast.kind(id) else { continue };
+            if !name.is_operator() {
+                continue;
+            }
+            let params = &self.info.signatures[&id].params;
+            let previous = declarations[..i].iter().find(|&&prev| match self.ast.kind(prev) {
+                ASTNode::Func(other, ..) if other == name => {
+                    let other = &self.info.signatures[&prev].params;
+                    other.len() == params.len() && other.iter().zip(params).all(|(a, b)| a.equivalent(b))
+                }
+                _ => false,
+            });
+            if let Some(&prev) = previous {
+                let message = format!(
+                    "operator \"{}\" already declared with this signature in this block (first declared at row {}, col {})",
+                    name, self.ast[prev].row, self.ast[prev].col
+                );
+                self.error(id, message);
+            }
+        }
+    }
+
+    fn parameters(&mut self, params: &[NodeId]) -> Vec<Param> {
+        params
+            .iter()
+            .map(|&param| {
+                self.declaration(param);
+                match self.ast.kind(param) {
+                    ASTNode::VarParam(..) => Param::Var(self.info.type_of(param).clone()),
+                    ASTNode::ProcParam(..) | ASTNode::FuncParam(..) => Param::Routine(self.info.signatures[&param].clone()),
+                    _ => Param::Value(self.info.type_of(param).clone()),
+                }
+            })
+            .collect()
+    }
+
+    fn arguments(&mut self, call: NodeId, name: Symbol, signature: &Signature, args: &[NodeId]) {
+        if args.len() != signature.params.len() {
+            self.error(
+                call,
+                format!(
+                    "wrong number of arguments to \"{}\": expected {}, found {}",
+                    name,
+                    signature.params.len(),
+                    args.len()
+                ),
+            );
+        }
+        for (i, &arg) in args.iter().enumerate() {
+            let actual = self.actual(arg);
+            let (Some(actual), Some(expected)) = (actual, signature.params.get(i)) else {
+                continue;
+            };
+            if actual.kind() != expected.kind() {
+                self.error(
+                    arg,
+                    format!(
+                        "argument {} of \"{}\" must be a {} actual parameter, found a {} actual parameter",
+                        i + 1,
+                        name,
+                        expected.kind(),
+                        actual.kind()
+                    ),
+                );
+            } else if !actual.equivalent(expected) {
+                self.error(arg, format!("argument {} of \"{}\" must be {}, found {}", i + 1, name, expected, actual));
+            }
+        }
+    }
+
+    // Chequea un argumento y devuelve la clase de parámetro que corresponde,
+    // o `None` si ya se informó un error.
+    fn actual(&mut self, arg: NodeId) -> Option<Param> {
+        match self.ast.kind(arg) {
+            ASTNode::VarActual(vname) => {
+                let (ty, assignable) = self.vname(*vname);
+                if !assignable {
+                    self.error(*vname, "cannot pass a constant as a var parameter".to_string());
+                }
+                self.info.types.insert(arg, ty.clone());
+                Some(Param::Var(ty))
+            }
+            ASTNode::ProcActual(name) | ASTNode::FuncActual(name)
+                if matches!(self.identification.decl(arg), Some(Decl::Standard(_))) =>
+            {
+                self.error(arg, format!("standard routine \"{}\" cannot be passed as a parameter", name));
+                None
+            }
+            ASTNode::ProcActual(name) => match self.entity(arg) {
+                Entity::Proc(signature) => Some(Param::Routine(signature)),
+                Entity::Unknown => None,
+                _ => {
+                    self.error(arg, format!("\"{}\" is not a procedure", name));
+                    None
+                }
+            },
+            ASTNode::FuncActual(name) => match self.entity(arg) {
+                Entity::Func(signature) => Some(Param::Routine(signature)),
+                Entity::Unknown => None,
+                _ => {
+                    self.error(arg, format!("\"{}\" is not a function", name));
+                    None
+                }
+            },
+            _ => Some(Param::Value(self.expression(arg))),
+        }
+    }
+
+    fn expression(&mut self, id: NodeId) -> Type {
+        let ty = match self.ast.kind(id) {
+            ASTNode::Number(_) => Type::Int,
+            ASTNode::Char(_) => Type::Char,
+            ASTNode::Identifier(_) | ASTNode::Field(..) | ASTNode::Index(..) => self.vname(id).0,
+            ASTNode::Expression(inner) => self.expression(*inner),
+            ASTNode::Call(name, args) => match self.entity(id) {
+                Entity::Func(signature) => {
+                    self.arguments(id, *name, &signature, args);
+                    signature.result.unwrap_or(Type::Error)
+                }
+                Entity::Proc(signature) => {
+                    self.error(id, format!("\"{}\" is a procedure, not a function", name));
+                    self.arguments(id, *name, &signature, args);
+                    Type::Error
+                }
+                entity => {
+                    if !matches!(entity, Entity::Unknown) {
+                        self.error(id, format!("\"{}\" is not a function", name));
+                    }
+                    for &arg in args {
+                        self.actual(arg);
+                    }
+                    Type::Error
+                }
+            },
+            ASTNode::Operator(op, left, right) => {
+                let left_ty = self.expression(*left);
+                let right_ty = self.expression(*right);
+                match self.operator(id, *op, &[&left_ty, &right_ty]) {
+                    Entity::Binary(l, r, result) => {
+                        if !left_ty.equivalent(&l) || !right_ty.equivalent(&r) {
+                            self.error(
+                                id,
+                                format!("operator \"{}\" expects {} and {}, found {} and {}", op, l, r, left_ty, right_ty),
+                            );
+                        }
+                        result
+                    }
+                    Entity::Equality => {
+                        if !left_ty.equivalent(&right_ty) {
+                            self.error(id, format!("operator \"{}\" compares {} with {}", op, left_ty, right_ty));
+                        }
+                        Type::Bool
+                    }
+                    Entity::Func(signature) if signature.params.len() == 2 => {
+                        if !self.accepts(self.info.operators[&id], &[&left_ty, &right_ty]) {
+                            let (l, r) = (&signature.params[0], &signature.params[1]);
+                            self.error(
+                                id,
+                                format!("operator \"{}\" expects {} and {}, found {} and {}", op, l, r, left_ty, right_ty),
+                            );
+                        }
+                        signature.result.unwrap_or(Type::Error)
+                    }
+                    Entity::Unknown => Type::Error,
+                    _ => {
+                        self.error(id, format!("\"{}\" is not a binary operator", op));
+                        Type::Error
+                    }
+                }
+            }
+            ASTNode::Unary(op, operand) => {
+                let operand_ty = self.expression(*operand);
+                match self.operator(id, *op, &[&operand_ty]) {
+                    Entity::Unary(arg, result) => {
+                        if !operand_ty.equivalent(&arg) {
+                            self.error(id, format!("operator \"{}\" expects {}, found {}", op, arg, operand_ty));
+                        }
+                        result
+                    }
+                    Entity::Func(signature) if signature.params.len() == 1 => {
+                        if !self.accepts(self.info.operators[&id], &[&operand_ty]) {
+                            let arg = &signature.params[0];
+                            self.error(id, format!("operator \"{}\" expects {}, found {}", op, arg, operand_ty));
+                        }
+                        signature.result.unwrap_or(Type::Error)
+                    }
+                    Entity::Unknown => Type::Error,
+                    _ => {
+                        self.error(id, format!("\"{}\" is not a unary operator", op));
+                        Type::Error
+                    }
+                }
+            }
+            ASTNode::LetExpression(declarations, expr) => {
+                self.declaration(*declarations);
+                self.expression(*expr)
+            }
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                self.condition(*cond);
+                let then_ty = self.expression(*then_expr);
+                let else_ty = self.expression(*else_expr);
+                if !then_ty.equivalent(&else_ty) {
+                    self.error(id, format!("branches of an if expression must agree: found {} and {}", then_ty, else_ty));
+                }
+                if then_ty.is_error() { else_ty } else { then_ty }
+            }
+            ASTNode::ArrayAggregate(elems) => {
+                let first = self.expression(elems[0]);
+                for &elem in &elems[1..] {
+                    let ty = self.expression(elem);
+                    if !ty.equivalent(&first) {
+                        self.error(elem, format!("array elements must all be {}, found {}", first, ty));
+                    }
+                }
+                Type::Array(elems.len(), Box::new(first))
+            }
+            ASTNode::RecordAggregate(fields) => {
+                let mut seen = HashSet::new();
+                let mut record = Vec::new();
+                for &field in fields {
+                    if let ASTNode::FieldInit(name, expr) = self.ast.kind(field) {
+                        if !seen.insert(*name) {
+                            self.error(field, format!("duplicate field \"{}\"", name));
+                        }
+                        let ty = self.expression(*expr);
+                        record.push((*name, ty));
+                    }
+                }
+                Type::Record(record)
+            }
+            _ => Type::Error,
+        };
+        self.info.types.insert(id, ty.clone());
+        ty
+    }
+
+    // Tipo de un V-name y si se le puede asignar (variables sí, constantes no).
+    fn vname(&mut self, id: NodeId) -> (Type, bool) {
+        let (ty, assignable) = match self.ast.kind(id) {
+            ASTNode::Identifier(name) => match self.entity(id) {
+                Entity::Value(ty, assignable) => (ty, assignable),
+                Entity::Unknown => (Type::Error, true),
+                _ => {
+                    self.error(id, format!("\"{}\" is not a variable or constant", name));
+                    (Type::Error, true)
+                }
+            },
+            ASTNode::Field(record, field) => {
+                let (record_ty, assignable) = self.vname(*record);
+                match record_ty.field(*field) {
+                    Some(ty) => (ty.clone(), assignable),
+                    None => {
+                        if !record_ty.is_error() {
+                            self.error(id, format!("{} has no field \"{}\"", record_ty, field));
+                        }
+                        (Type::Error, assignable)
+                    }
+                }
+            }
+            ASTNode::Index(array, index) => {
+                let (array_ty, assignable) = self.vname(*array);
+                let index_ty = self.expression(*index);
+                if !index_ty.equivalent(&Type::Int) {
+                    self.error(*index, format!("array index must be Integer, found {}", index_ty));
+                }
+                match array_ty {
+                    Type::Array(_, elem) => (*elem, assignable),
+                    Type::Error => (Type::Error, assignable),
+                    other => {
+                        self.error(id, format!("{} is not an array", other));
+                        (Type::Error, assignable)
+                    }
+                }
+            }
+            _ => (self.expression(id), false),
+        };
+        self.info.types.insert(id, ty.clone());
+        (ty, assignable)
+    }
+
+    fn type_denoter(&mut self, id: NodeId) -> Type {
+        let ty = match self.ast.kind(id) {
+            ASTNode::TypeName(name) => match self.entity(id) {
+                Entity::Type(ty) => ty,
+                Entity::Unknown => Type::Error,
+                _ => {
+                    self.error(id, format!("\"{}\" is not a type", name));
+                    Type::Error
+                }
+            },
+            ASTNode::ArrayType(size, elem) => {
+                let elem = self.type_denoter(*elem);
+                if *size <= 0 {
+                    self.error(id, format!("array size must be positive, found {}", size));
+                    Type::Error
+                } else {
+                    Type::Array(*size as usize, Box::new(elem))
+                }
+            }
+            ASTNode::RecordType(fields) => {
+                let mut seen = HashSet::new();
+                let mut record = Vec::new();
+                for &field in fields {
+                    if let ASTNode::FieldType(name, type_denoter) = self.ast.kind(field) {
+                        if !seen.insert(*name) {
+                            self.error(field, format!("duplicate field \"{}\"", name));
+                        }
+                        let ty = self.type_denoter(*type_denoter);
+                        record.push((*name, ty));
+                    }
+                }
+                Type::Record(record)
+            }
+            _ => Type::Error,
+        };
+        self.info.types.insert(id, ty.clone());
+        ty
+    }
+}
+
+fn standard_entity(name: Symbol) -> Entity {
+    match stdenv::lookup(name) {
+        Some(Standard::Type(ty)) => Entity::Type(ty),
+        Some(Standard::Const(ty, _)) => Entity::Value(ty, false),
+        Some(Standard::Func(signature, _)) => Entity::Func(signature),
+        Some(Standard::Proc(signature, _)) => Entity::Proc(signature),
+        Some(Standard::Unary(arg, result, _)) => Entity::Unary(arg, result),
+        Some(Standard::Binary(left, right, result, _)) => Entity::Binary(left, right, result),
+        Some(Standard::Equality(_)) => Entity::Equality,
+        None => Entity::Unknown,
+    }
+}
@@ -0,0 +1,602 @@
+use std::collections::HashMap;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::checker::TypeInfo;
+use crate::fold::{self, Constants};
+use crate::identification::{Decl, Identification};
+use crate::stdenv::{self, Primitive, Standard, MAXINT};
+use crate::tam::{Instruction, ObjectFile, Op, PrimitiveRoutine, Reg, LINK_DATA};
+use crate::types::Type;
+
+// Generador de código TAM para un programa ya identificado y chequeado. Las
+// variables del programa principal se direccionan desde SB, las de cada rutina
+// desde LB y las de rutinas que la contienen con los registros de display
+// L1..L6. Los parámetros quedan debajo del marco, con desplazamiento negativo.
+// Las expresiones constantes se cargan ya evaluadas con LOADL. El marco cuenta
+// también los valores que esperan en la pila mientras se evalúa otra parte de
+// la expresión, porque una expresión `let` declara encima de ellos.
+pub fn encode(ast: &Ast, identification: &Identification, types: &TypeInfo) -> Result<ObjectFile, String> {
+    let mut encoder = Encoder {
+        ast,
+        identification,
+        types,
+        constants: fold::fold(ast, identification, types),
+        object: ObjectFile::default(),
+        entities: HashMap::new(),
+        level: 0,
+        frame: 0,
+        position: (0, 0),
+    };
+    if let Some(root) = ast.root() {
+        encoder.command(root)?;
+    }
+    encoder.emit(Op::Halt, 0, Reg::CB, 0);
+    Ok(encoder.object)
+}
+
+// Dirección de ejecución de cada declaración: nivel de anidamiento de la rutina
+// que la contiene y desplazamiento dentro de su marco, o dirección de código.
+// Un parámetro `var` guarda en su lugar la dirección de la variable, y uno
+// `proc` o `func` una clausura de dos palabras: enlace estático y dirección de
+// código, que se llama con CALLI.
+#[derive(Debug, Clone, Copy)]
+enum Entity {
+    Address(usize, i16),
+    Reference(usize, i16),
+    Closure(usize, i16),
+    Routine(usize, i16),
+}
+
+// Dónde está un V-name: en una dirección fija respecto de un registro o en una
+// dirección calculada que quedó en el tope de la pila.
+enum Location {
+    Static(Reg, i16),
+    Dynamic,
+}
+
+struct Encoder<'a> {
+    ast: &'a Ast,
+    identification: &'a Identification,
+    types: &'a TypeInfo,
+    constants: Constants,
+    object: ObjectFile,
+    entities: HashMap<NodeId, Entity>,
+    level: usize,
+    frame: i16,
+    position: (u32, u32),
+}
+
+impl Encoder<'_> {
+    fn emit(&mut self, op: Op, n: u8, r: Reg, d: i16) -> usize {
+        self.object.code.push(Instruction::new(op, n, r, d));
+        self.object.source_map.push(self.position);
+        self.object.code.len() - 1
+    }
+
+    fn patch(&mut self, address: usize, target: usize) -> Result<(), String> {
+        self.object.code[address].d = self.address(target)?;
+        Ok(())
+    }
+
+    // Dirección de código como operando d.
+    fn address(&self, target: usize) -> Result<i16, String> {
+        i16::try_from(target).or_else(|_| self.fail("program too large for TAM"))
+    }
+
+    fn here(&self) -> usize {
+        self.object.code.len()
+    }
+
+    fn error<T>(&self, id: NodeId, message: &str) -> Result<T, String> {
+        let node = &self.ast[id];
+        Err(format!("{} at row {}, col {}", message, node.row, node.col))
+    }
+
+    // Error en el nodo que se está generando.
+    fn fail<T>(&self, message: &str) -> Result<T, String> {
+        let (row, col) = self.position;
+        Err(format!("{} at row {}, col {}", message, row, col))
+    }
+
+    // Operando d de una instrucción, de 16 bits con signo.
+    fn word<T: TryInto<i16>>(&self, value: T) -> Result<i16, String> {
+        value.try_into().or_else(|_| self.fail("value out of range for TAM"))
+    }
+
+    // Operando n de una instrucción, de 8 bits sin signo.
+    fn count<T: TryInto<u8>>(&self, value: T) -> Result<u8, String> {
+        value.try_into().or_else(|_| self.fail("value out of range for TAM"))
+    }
+
+    // Agrega `size` palabras al marco.
+    fn grow(&mut self, size: i16) -> Result<(), String> {
+        match self.frame.checked_add(size) {
+            Some(frame) => {
+                self.frame = frame;
+                Ok(())
+            }
+            None => self.fail("frame too large for TAM"),
+        }
+    }
+
+    fn primitive(&mut self, routine: PrimitiveRoutine) {
+        self.emit(Op::Call, Reg::SB.code(), Reg::PB, routine.displacement());
+    }
+
+    fn size(&self, id: NodeId) -> Result<i16, String> {
+        self.word(self.types.type_of(id).size())
+    }
+
+    // Palabras que ocupa un parámetro en el marco.
+    fn param_size(&self, param: NodeId) -> usize {
+        match self.ast.kind(param) {
+            ASTNode::VarParam(..) => 1,
+            ASTNode::ProcParam(..) | ASTNode::FuncParam(..) => 2,
+            _ => self.types.type_of(param).size(),
+        }
+    }
+
+    // Registro que apunta al marco de las declaraciones de nivel `level`.
+    fn register(&self, id: NodeId, level: usize) -> Result<Reg, String> {
+        if level == 0 {
+            return Ok(Reg::SB);
+        }
+        match Reg::display(self.level - level) {
+            Some(reg) => Ok(reg),
+            None => self.error(id, "routines nested too deeply for TAM"),
+        }
+    }
+
+    // Guarda la posición del nodo para el mapa de fuentes mientras se genera.
+    fn at<T>(&mut self, id: NodeId, f: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        let saved = self.position;
+        let node = &self.ast[id];
+        self.position = (node.row as u32, node.col as u32);
+        let result = f(self);
+        self.position = saved;
+        result
+    }
+
+    fn command(&mut self, id: NodeId) -> Result<(), String> {
+        self.at(id, |e| e.encode_command(id))
+    }
+
+    fn encode_command(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Let(declarations, command) => {
+                let start = self.frame;
+                self.declaration(*declarations)?;
+                self.command(*command)?;
+                self.pop_to(start);
+            }
+            ASTNode::Command(commands) => {
+                let start = self.frame;
+                for &command in commands {
+                    self.command(command)?;
+                }
+                self.pop_to(start);
+            }
+            ASTNode::Assign(vname, expr) => {
+                self.expression(*expr)?;
+                let size = self.size(*vname)?;
+                let location = self.pending(size, |e| e.vname(*vname))?;
+                let n = self.count(size)?;
+                match location {
+                    Location::Static(r, d) => self.emit(Op::Store, n, r, d),
+                    Location::Dynamic => self.emit(Op::StoreI, n, Reg::CB, 0),
+                };
+            }
+            ASTNode::Call(_, args) => self.call(id, args)?,
+            ASTNode::If(cond, _, then_branch, _, else_branch) => {
+                self.expression(*cond)?;
+                let jump_else = self.emit(Op::JumpIf, 0, Reg::CB, 0);
+                self.command(*then_branch)?;
+                let jump_end = self.emit(Op::Jump, 0, Reg::CB, 0);
+                self.patch(jump_else, self.here())?;
+                self.command(*else_branch)?;
+                self.patch(jump_end, self.here())?;
+            }
+            ASTNode::While(cond, body) => {
+                let jump_test = self.emit(Op::Jump, 0, Reg::CB, 0);
+                let loop_start = self.here();
+                self.command(*body)?;
+                self.patch(jump_test, self.here())?;
+                self.expression(*cond)?;
+                let loop_start = self.address(loop_start)?;
+                self.emit(Op::JumpIf, 1, Reg::CB, loop_start);
+            }
+            ASTNode::Empty => {}
+            _ => return self.error(id, "not a command"),
+        }
+        Ok(())
+    }
+
+    // Evalúa `f` con `size` palabras ya apiladas encima del marco.
+    fn pending<T>(&mut self, size: i16, f: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        self.grow(size)?;
+        let result = f(self);
+        self.frame -= size;
+        result
+    }
+
+    // Libera las variables declaradas desde que el marco medía `start`.
+    fn pop_to(&mut self, start: i16) {
+        if self.frame > start {
+            self.emit(Op::Pop, 0, Reg::CB, self.frame - start);
+            self.frame = start;
+        }
+    }
+
+    fn declaration(&mut self, id: NodeId) -> Result<(), String> {
+        self.at(id, |e| e.encode_declaration(id))
+    }
+
+    fn encode_declaration(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Declaration(declarations) => {
+                for &declaration in declarations {
+                    self.declaration(declaration)?;
+                }
+            }
+            ASTNode::Const(_, expr) => {
+                let expr = *expr;
+                self.expression(expr)?;
+                self.entities.insert(id, Entity::Address(self.level, self.frame));
+                self.grow(self.size(id)?)?;
+            }
+            ASTNode::Var(..) => {
+                let size = self.size(id)?;
+                self.emit(Op::Push, 0, Reg::CB, size);
+                self.entities.insert(id, Entity::Address(self.level, self.frame));
+                self.grow(size)?;
+            }
+            ASTNode::Func(_, params, _, body) => {
+                let (params, body) = (params.clone(), *body);
+                let result = self.types.signatures.get(&id).and_then(|s| s.result.as_ref()).map_or(0, Type::size);
+                let result = self.count(result)?;
+                self.routine(id, &params, result, |e| e.expression(body))?;
+            }
+            ASTNode::Proc(_, params, body) => {
+                let (params, body) = (params.clone(), *body);
+                self.routine(id, &params, 0, |e| e.command(body))?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn routine(
+        &mut self,
+        id: NodeId,
+        params: &[NodeId],
+        result: u8,
+        body: impl FnOnce(&mut Self) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let skip = self.emit(Op::Jump, 0, Reg::CB, 0);
+        self.entities.insert(id, Entity::Routine(self.level, self.address(self.here())?));
+        let saved_frame = self.frame;
+        self.level += 1;
+        let args = self.word(params.iter().map(|&p| self.param_size(p)).sum::<usize>())?;
+        let mut offset = -args;
+        for &param in params {
+            let entity = match self.ast.kind(param) {
+                ASTNode::VarParam(..) => Entity::Reference(self.level, offset),
+                ASTNode::ProcParam(..) | ASTNode::FuncParam(..) => Entity::Closure(self.level, offset),
+                _ => Entity::Address(self.level, offset),
+            };
+            self.entities.insert(param, entity);
+            offset += self.word(self.param_size(param))?;
+        }
+        self.frame = LINK_DATA;
+        body(self)?;
+        self.emit(Op::Return, result, Reg::CB, args);
+        self.level -= 1;
+        self.frame = saved_frame;
+        self.patch(skip, self.here())?;
+        Ok(())
+    }
+
+    // Un operador declarado en el programa se llama como una función.
+    fn call(&mut self, id: NodeId, args: &[NodeId]) -> Result<(), String> {
+        match self.types.operator(id).or_else(|| self.identification.decl(id)) {
+            Some(Decl::Standard(name)) => match stdenv::lookup(name) {
+                Some(Standard::Func(_, primitive)) | Some(Standard::Proc(_, primitive)) => {
+                    self.standard_call(primitive, args)
+                }
+                _ => self.error(id, "not a routine"),
+            },
+            Some(Decl::Node(decl)) => {
+                let start = self.frame;
+                for &arg in args {
+                    self.argument(arg)?;
+                    let size = match self.ast.kind(arg) {
+                        ASTNode::VarActual(_) => 1,
+                        ASTNode::ProcActual(_) | ASTNode::FuncActual(_) => 2,
+                        _ => self.size(arg)?,
+                    };
+                    self.grow(size)?;
+                }
+                self.frame = start;
+                match self.entities.get(&decl).copied() {
+                    Some(Entity::Routine(level, address)) => {
+                        let link = self.register(id, level)?;
+                        self.emit(Op::Call, link.code(), Reg::CB, address);
+                        Ok(())
+                    }
+                    Some(Entity::Closure(level, d)) => {
+                        let r = self.register(id, level)?;
+                        self.emit(Op::Load, 2, r, d);
+                        self.emit(Op::CallI, 0, Reg::CB, 0);
+                        Ok(())
+                    }
+                    _ => self.error(id, "not a routine"),
+                }
+            }
+            None => self.error(id, "undeclared routine"),
+        }
+    }
+
+    fn argument(&mut self, arg: NodeId) -> Result<(), String> {
+        match self.ast.kind(arg) {
+            ASTNode::VarActual(vname) => {
+                if let Location::Static(r, d) = self.vname(*vname)? {
+                    self.emit(Op::LoadA, 0, r, d);
+                }
+                Ok(())
+            }
+            ASTNode::ProcActual(_) | ASTNode::FuncActual(_) => {
+                let Some(Decl::Node(decl)) = self.identification.decl(arg) else {
+                    return self.error(arg, "not a routine");
+                };
+                match self.entities.get(&decl).copied() {
+                    Some(Entity::Routine(level, address)) => {
+                        let link = self.register(arg, level)?;
+                        self.emit(Op::LoadA, 0, link, 0);
+                        self.emit(Op::LoadA, 0, Reg::CB, address);
+                    }
+                    Some(Entity::Closure(level, d)) => {
+                        let r = self.register(arg, level)?;
+                        self.emit(Op::Load, 2, r, d);
+                    }
+                    _ => return self.error(arg, "not a routine"),
+                }
+                Ok(())
+            }
+            _ => self.expression(arg),
+        }
+    }
+
+    fn standard_call(&mut self, primitive: Primitive, args: &[NodeId]) -> Result<(), String> {
+        match primitive {
+            // Char e Integer tienen la misma representación; `chr` pasa por la
+            // primitiva Chr, que comprueba el código.
+            Primitive::Ord => self.expression(args[0]),
+            Primitive::Get | Primitive::GetInt => {
+                if let Location::Static(r, d) = self.vname(args[0])? {
+                    self.emit(Op::LoadA, 0, r, d);
+                }
+                let routine = if primitive == Primitive::Get { PrimitiveRoutine::Get } else { PrimitiveRoutine::GetInt };
+                self.primitive(routine);
+                Ok(())
+            }
+            _ => {
+                for &arg in args {
+                    self.expression(arg)?;
+                }
+                self.primitive(routine_for(primitive));
+                Ok(())
+            }
+        }
+    }
+
+    fn expression(&mut self, id: NodeId) -> Result<(), String> {
+        self.at(id, |e| e.encode_expression(id))
+    }
+
+    fn encode_expression(&mut self, id: NodeId) -> Result<(), String> {
+        if let Some(value) = self.constants.value(id) {
+            let value = self.word(value.to_int())?;
+            self.emit(Op::LoadL, 0, Reg::CB, value);
+            return Ok(());
+        }
+        match self.ast.kind(id) {
+            ASTNode::Number(n) => {
+                if *n > MAXINT {
+                    return self.error(id, "integer literal too large");
+                }
+                let n = self.word(*n)?;
+                self.emit(Op::LoadL, 0, Reg::CB, n);
+            }
+            ASTNode::Char(c) => {
+                let c = self.word(*c as u32)?;
+                self.emit(Op::LoadL, 0, Reg::CB, c);
+            }
+            ASTNode::Expression(inner) => self.expression(*inner)?,
+            ASTNode::Identifier(_) | ASTNode::Field(..) | ASTNode::Index(..) => {
+                if let Some(Decl::Standard(name)) = self.identification.decl(id) {
+                    if let Some(Standard::Const(_, value)) = stdenv::lookup(name) {
+                        let value = self.word(value)?;
+                        self.emit(Op::LoadL, 0, Reg::CB, value);
+                        return Ok(());
+                    }
+                }
+                let size = self.count(self.size(id)?)?;
+                match self.vname(id)? {
+                    Location::Static(r, d) => self.emit(Op::Load, size, r, d),
+                    Location::Dynamic => self.emit(Op::LoadI, size, Reg::CB, 0),
+                };
+            }
+            ASTNode::Call(_, args) => self.call(id, args)?,
+            ASTNode::Operator(_, left, right) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*left, *right])?
+            }
+            ASTNode::Unary(_, operand) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*operand])?
+            }
+            ASTNode::Operator(_, left, right) => {
+                let (left, right) = (*left, *right);
+                self.expression(left)?;
+                let size = self.size(left)?;
+                self.pending(size, |e| e.expression(right))?;
+                match self.operator(id) {
+                    Some(primitive @ (Primitive::Eq | Primitive::Ne)) => {
+                        self.emit(Op::LoadL, 0, Reg::CB, size);
+                        self.primitive(routine_for(primitive));
+                    }
+                    Some(primitive) => self.primitive(routine_for(primitive)),
+                    None => return self.error(id, "not an operator"),
+                }
+            }
+            ASTNode::Unary(_, operand) => {
+                self.expression(*operand)?;
+                match self.operator(id) {
+                    Some(primitive) => self.primitive(routine_for(primitive)),
+                    None => return self.error(id, "not an operator"),
+                }
+            }
+            ASTNode::ArrayAggregate(elems) => {
+                let start = self.frame;
+                for &elem in elems {
+                    self.expression(elem)?;
+                    self.grow(self.size(elem)?)?;
+                }
+                self.frame = start;
+            }
+            ASTNode::RecordAggregate(fields) => {
+                let start = self.frame;
+                for &field in fields {
+                    if let ASTNode::FieldInit(_, expr) = self.ast.kind(field) {
+                        self.expression(*expr)?;
+                        self.grow(self.size(*expr)?)?;
+                    }
+                }
+                self.frame = start;
+            }
+            // Las declaraciones quedan debajo del resultado; POP n d las libera.
+            ASTNode::LetExpression(declarations, expr) => {
+                let start = self.frame;
+                self.declaration(*declarations)?;
+                self.expression(*expr)?;
+                if self.frame > start {
+                    let size = self.count(self.size(id)?)?;
+                    self.emit(Op::Pop, size, Reg::CB, self.frame - start);
+                    self.frame = start;
+                }
+            }
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                self.expression(*cond)?;
+                let jump_else = self.emit(Op::JumpIf, 0, Reg::CB, 0);
+                self.expression(*then_expr)?;
+                let jump_end = self.emit(Op::Jump, 0, Reg::CB, 0);
+                self.patch(jump_else, self.here())?;
+                self.expression(*else_expr)?;
+                self.patch(jump_end, self.here())?;
+            }
+            _ => return self.error(id, "not an expression"),
+        }
+        Ok(())
+    }
+
+    fn operator(&self, id: NodeId) -> Option<Primitive> {
+        match stdenv::lookup(match self.types.operator(id)? {
+            Decl::Standard(name) => name,
+            Decl::Node(_) => return None,
+        })? {
+            Standard::Unary(.., primitive) | Standard::Binary(.., primitive) | Standard::Equality(primitive) => {
+                Some(primitive)
+            }
+            _ => None,
+        }
+    }
+
+    fn vname(&mut self, id: NodeId) -> Result<Location, String> {
+        match self.ast.kind(id) {
+            ASTNode::Identifier(_) => match self.identification.decl(id) {
+                Some(Decl::Node(decl)) => match self.entities.get(&decl).copied() {
+                    Some(Entity::Address(level, d)) => Ok(Location::Static(self.register(id, level)?, d)),
+                    Some(Entity::Reference(level, d)) => {
+                        let r = self.register(id, level)?;
+                        self.emit(Op::Load, 1, r, d);
+                        Ok(Location::Dynamic)
+                    }
+                    _ => self.error(id, "not a variable"),
+                },
+                _ => self.error(id, "not a variable"),
+            },
+            ASTNode::Field(record, field) => {
+                let record = *record;
+                let Some((offset, _)) = self.types.type_of(record).field_offset(*field) else {
+                    return self.error(id, "no such field");
+                };
+                match self.vname(record)? {
+                    Location::Static(r, d) => match d.checked_add(self.word(offset)?) {
+                        Some(d) => Ok(Location::Static(r, d)),
+                        None => self.fail("frame too large for TAM"),
+                    },
+                    Location::Dynamic => {
+                        if offset > 0 {
+                            let offset = self.word(offset)?;
+                            self.emit(Op::LoadL, 0, Reg::CB, offset);
+                            self.primitive(PrimitiveRoutine::Add);
+                        }
+                        Ok(Location::Dynamic)
+                    }
+                }
+            }
+            ASTNode::Index(array, index) => {
+                let (array, index) = (*array, *index);
+                let Type::Array(len, elem) = self.types.type_of(array).clone() else {
+                    return self.error(id, "not an array");
+                };
+                if let Location::Static(r, d) = self.vname(array)? {
+                    self.emit(Op::LoadA, 0, r, d);
+                }
+                self.pending(1, |e| e.expression(index))?;
+                self.at(index, |e| {
+                    let len = e.word(len)?;
+                    e.emit(Op::LoadL, 0, Reg::CB, len);
+                    e.primitive(PrimitiveRoutine::Index);
+                    Ok(())
+                })?;
+                if elem.size() != 1 {
+                    let size = self.word(elem.size())?;
+                    self.emit(Op::LoadL, 0, Reg::CB, size);
+                    self.primitive(PrimitiveRoutine::Mult);
+                }
+                self.primitive(PrimitiveRoutine::Add);
+                Ok(Location::Dynamic)
+            }
+            ASTNode::VarActual(vname) => self.vname(*vname),
+            _ => self.error(id, "not a variable"),
+        }
+    }
+}
+
+fn routine_for(primitive: Primitive) -> PrimitiveRoutine {
+    match primitive {
+        Primitive::Not => PrimitiveRoutine::Not,
+        Primitive::And => PrimitiveRoutine::And,
+        Primitive::Or => PrimitiveRoutine::Or,
+        Primitive::Add => PrimitiveRoutine::Add,
+        Primitive::Sub => PrimitiveRoutine::Sub,
+        Primitive::Mul => PrimitiveRoutine::Mult,
+        Primitive::Div => PrimitiveRoutine::Div,
+        Primitive::Mod => PrimitiveRoutine::Mod,
+        Primitive::Lt => PrimitiveRoutine::Lt,
+        Primitive::Le => PrimitiveRoutine::Le,
+        Primitive::Gt => PrimitiveRoutine::Gt,
+        Primitive::Ge => PrimitiveRoutine::Ge,
+        Primitive::Eq => PrimitiveRoutine::Eq,
+        Primitive::Ne => PrimitiveRoutine::Ne,
+        Primitive::Ord => PrimitiveRoutine::Id,
+        Primitive::Chr => PrimitiveRoutine::Chr,
+        Primitive::Eof => PrimitiveRoutine::Eof,
+        Primitive::Eol => PrimitiveRoutine::Eol,
+        Primitive::Get => PrimitiveRoutine::Get,
+        Primitive::Put => PrimitiveRoutine::Put,
+        Primitive::GetInt => PrimitiveRoutine::GetInt,
+        Primitive::PutInt => PrimitiveRoutine::PutInt,
+        Primitive::GetEol => PrimitiveRoutine::GetEol,
+        Primitive::PutEol => PrimitiveRoutine::PutEol,
+    }
+}
@@ -0,0 +1,459 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::symbol::Symbol;
+use crate::token::{Span, TokenType};
+
+// Clase de un nodo del árbol concreto. Casi todas corresponden a una variante
+// de `ASTNode`; `Program`, `Block` (begin … end) y `Paren` conservan lo que el
+// AST descarta.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Program,
+    Let,
+    Const,
+    Var,
+    Func,
+    Proc,
+    Type,
+    Assign,
+    If,
+    While,
+    Empty,
+    Block,
+    Call,
+    Paren,
+    Identifier,
+    Number,
+    Char,
+    Binary,
+    Unary,
+    LetExpression,
+    IfExpression,
+    Declarations,
+    Commands,
+    TypeName,
+    ArrayType,
+    RecordType,
+    FieldType,
+    Field,
+    Index,
+    ArrayAggregate,
+    RecordAggregate,
+    FieldInit,
+    ValueParam,
+    VarParam,
+    ProcParam,
+    FuncParam,
+    VarActual,
+    ProcActual,
+    FuncActual,
+}
+
+// Árbol verde: inmutable y sin posiciones absolutas, así que un subárbol se
+// puede compartir entre versiones del documento.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct GreenToken {
+    kind: TokenType,
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct GreenNode {
+    kind: NodeKind,
+    len: usize,
+    // Cantidad de nodos del subárbol, este incluido.
+    size: usize,
+    children: Vec<GreenElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+
+impl GreenToken {
+    pub fn kind(&self) -> TokenType {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl GreenNode {
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    // Longitud en bytes del texto que cubre.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+}
+
+impl GreenElement {
+    fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+}
+
+// Arma el árbol verde de abajo hacia arriba: los elementos se apilan y
+// `node` agrupa los apilados desde un `checkpoint` bajo un nodo nuevo.
+#[derive(Debug, Default)]
+pub(crate) struct Builder {
+    stack: Vec<GreenElement>,
+}
+
+impl Builder {
+    pub fn checkpoint(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn token(&mut self, kind: TokenType, text: impl Into<String>) {
+        self.stack.push(GreenElement::Token(Rc::new(GreenToken { kind, text: text.into() })));
+    }
+
+    pub fn push(&mut self, element: GreenElement) {
+        self.stack.push(element);
+    }
+
+    pub fn last(&self) -> Option<&GreenElement> {
+        self.stack.last()
+    }
+
+    pub fn node(&mut self, checkpoint: usize, kind: NodeKind) {
+        let children = self.stack.split_off(checkpoint);
+        let len = children.iter().map(GreenElement::len).sum();
+        let size = 1 + children
+            .iter()
+            .map(|child| match child {
+                GreenElement::Node(node) => node.size,
+                GreenElement::Token(_) => 0,
+            })
+            .sum::<usize>();
+        self.stack.push(GreenElement::Node(Rc::new(GreenNode { kind, len, size, children })));
+    }
+
+    // La raíz: el único elemento que queda en la pila.
+    pub fn finish(mut self) -> Rc<GreenNode> {
+        match self.stack.pop() {
+            Some(GreenElement::Node(root)) if self.stack.is_empty() => root,
+            _ => panic!("the syntax tree builder must end with a single root node"),
+        }
+    }
+}
+
+// Árbol rojo: vista de un nodo verde con su posición absoluta y su padre,
+// creada a demanda al recorrer el árbol.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxNode(Rc<RedNode>);
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct RedNode {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    offset: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> SyntaxNode {
+        SyntaxNode(Rc::new(RedNode { green, parent: None, offset: 0 }))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.green.kind
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<&SyntaxNode> {
+        self.0.parent.as_ref()
+    }
+
+    // Rango completo, con los espacios y comentarios que contiene.
+    pub fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.len)
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        let mut offset = self.0.offset;
+        self.0.green.children.iter().map(move |child| {
+            let start = offset;
+            offset += child.len();
+            match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(RedNode {
+                    green: green.clone(),
+                    parent: Some(self.clone()),
+                    offset: start,
+                }))),
+                GreenElement::Token(green) => {
+                    SyntaxElement::Token(SyntaxToken { green: green.clone(), parent: self.clone(), offset: start })
+                }
+            }
+        })
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    // Tokens hijos directos, sin espacios ni comentarios.
+    pub fn child_tokens(&self) -> impl Iterator<Item = SyntaxToken> + '_ {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Token(token) if !token.is_trivia() => Some(token),
+            _ => None,
+        })
+    }
+
+    // Todos los tokens del subárbol en orden, espacios y comentarios incluidos.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    // Rango de los tokens significativos, como el `span` del nodo del AST.
+    pub fn token_span(&self) -> Span {
+        let green = &self.0.green;
+        match (green.first_token(self.0.offset), green.last_token(self.0.offset + green.len)) {
+            (Some(first), Some(last)) => first.to(last),
+            _ => Span::new(self.0.offset, self.0.offset),
+        }
+    }
+}
+
+impl GreenNode {
+    // Rangos del primer y del último token significativo, sin recorrer todo el
+    // subárbol: solo se baja por el borde.
+    fn first_token(&self, mut offset: usize) -> Option<Span> {
+        for child in &self.children {
+            match child {
+                GreenElement::Token(token) if is_significant(token) => {
+                    return Some(Span::new(offset, offset + token.text.len()))
+                }
+                GreenElement::Node(node) => {
+                    if let Some(span) = node.first_token(offset) {
+                        return Some(span);
+                    }
+                }
+                GreenElement::Token(_) => {}
+            }
+            offset += child.len();
+        }
+        None
+    }
+
+    fn last_token(&self, mut end: usize) -> Option<Span> {
+        for child in self.children.iter().rev() {
+            match child {
+                GreenElement::Token(token) if is_significant(token) => {
+                    return Some(Span::new(end - token.text.len(), end))
+                }
+                GreenElement::Node(node) => {
+                    if let Some(span) = node.last_token(end) {
+                        return Some(span);
+                    }
+                }
+                GreenElement::Token(_) => {}
+            }
+            end -= child.len();
+        }
+        None
+    }
+}
+
+fn is_significant(token: &GreenToken) -> bool {
+    !matches!(token.kind, TokenType::Whitespace | TokenType::Comment | TokenType::EOF)
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write(green: &GreenNode, f: &mut fmt::Formatter) -> fmt::Result {
+            for child in &green.children {
+                match child {
+                    GreenElement::Node(node) => write(node, f)?,
+                    GreenElement::Token(token) => f.write_str(&token.text)?,
+                }
+            }
+            Ok(())
+        }
+        write(&self.0.green, f)
+    }
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> TokenType {
+        self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.text.len())
+    }
+
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.green.kind, TokenType::Whitespace | TokenType::Comment)
+    }
+}
+
+// Deriva el AST del árbol concreto. Los nodos se crean en el mismo orden en
+// que los creaba el parser (hijos antes que el padre), así que los `NodeId`
+// coinciden.
+pub fn lower(root: &SyntaxNode) -> Ast {
+    let text = root.to_string();
+    let mut lines = vec![0];
+    lines.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+    let mut lowering = Lowering { text: &text, lines, ast: Ast::new() };
+    if let Some(id) = root.children().next().map(|child| lowering.lower(&child)) {
+        lowering.ast.set_root(id);
+    }
+    lowering.ast
+}
+
+struct Lowering<'a> {
+    text: &'a str,
+    lines: Vec<usize>,
+    ast: Ast,
+}
+
+impl Lowering<'_> {
+    fn lower(&mut self, node: &SyntaxNode) -> NodeId {
+        let children: Vec<SyntaxNode> = node.children().collect();
+        let mut ids = Vec::with_capacity(children.len());
+        for child in &children {
+            ids.push(self.lower(child));
+        }
+        let name = || {
+            node.child_tokens()
+                .find(|t| t.kind() == TokenType::Identifier)
+                .map(|t| Symbol::intern(t.text()))
+                .expect("the node must have a name token")
+        };
+        let operator = || {
+            node.child_tokens()
+                .find(|t| matches!(t.kind(), TokenType::Operator | TokenType::Equals))
+                .map(|t| Symbol::intern(t.text()))
+                .expect("the node must have an operator token")
+        };
+        let literal = || node.child_tokens().next().expect("the node must have a literal token");
+        let kind = match node.kind() {
+            NodeKind::Program | NodeKind::Block => return ids[0],
+            NodeKind::Let => ASTNode::Let(ids[0], ids[1]),
+            NodeKind::Const => ASTNode::Const(name(), ids[0]),
+            NodeKind::Var => ASTNode::Var(name(), ids[0]),
+            // El nombre de una función puede ser un operador.
+            NodeKind::Func => {
+                let body = ids.pop().unwrap();
+                let ret = ids.pop().unwrap();
+                let name = node
+                    .child_tokens()
+                    .find(|t| matches!(t.kind(), TokenType::Identifier | TokenType::Operator | TokenType::Equals))
+                    .map(|t| Symbol::intern(t.text()))
+                    .expect("the node must have a name token");
+                ASTNode::Func(name, ids, ret, body)
+            }
+            NodeKind::Proc => {
+                let body = ids.pop().unwrap();
+                ASTNode::Proc(name(), ids, body)
+            }
+            NodeKind::Type => ASTNode::Type(name(), ids[0]),
+            NodeKind::Assign => ASTNode::Assign(ids[0], ids[1]),
+            NodeKind::If => ASTNode::If(ids[0], TokenType::Then, ids[1], TokenType::Else, ids[2]),
+            NodeKind::While => ASTNode::While(ids[0], ids[1]),
+            NodeKind::Empty => ASTNode::Empty,
+            NodeKind::Call => ASTNode::Call(name(), ids),
+            NodeKind::Paren => ASTNode::Expression(ids[0]),
+            NodeKind::Identifier => ASTNode::Identifier(name()),
+            NodeKind::Number => ASTNode::Number(literal().text().parse().unwrap()),
+            // El texto del literal lleva las comillas: 'c'.
+            NodeKind::Char => ASTNode::Char(literal().text()[1..].chars().next().unwrap()),
+            NodeKind::Binary => ASTNode::Operator(operator(), ids[0], ids[1]),
+            NodeKind::Unary => ASTNode::Unary(operator(), ids[0]),
+            NodeKind::LetExpression => ASTNode::LetExpression(ids[0], ids[1]),
+            NodeKind::IfExpression => ASTNode::IfExpression(ids[0], ids[1], ids[2]),
+            NodeKind::Declarations => ASTNode::Declaration(ids),
+            NodeKind::Commands => ASTNode::Command(ids),
+            NodeKind::TypeName => ASTNode::TypeName(name()),
+            NodeKind::ArrayType => {
+                let size = node.child_tokens().find(|t| t.kind() == TokenType::IntegerLiteral).unwrap();
+                ASTNode::ArrayType(size.text().parse().unwrap(), ids[0])
+            }
+            NodeKind::RecordType => ASTNode::RecordType(ids),
+            NodeKind::FieldType => ASTNode::FieldType(name(), ids[0]),
+            NodeKind::Field => ASTNode::Field(ids[0], name()),
+            NodeKind::Index => ASTNode::Index(ids[0], ids[1]),
+            NodeKind::ArrayAggregate => ASTNode::ArrayAggregate(ids),
+            NodeKind::RecordAggregate => ASTNode::RecordAggregate(ids),
+            NodeKind::FieldInit => ASTNode::FieldInit(name(), ids[0]),
+            NodeKind::ValueParam => ASTNode::ValueParam(name(), ids[0]),
+            NodeKind::VarParam => ASTNode::VarParam(name(), ids[0]),
+            NodeKind::ProcParam => ASTNode::ProcParam(name(), ids),
+            NodeKind::FuncParam => {
+                let ret = ids.pop().unwrap();
+                ASTNode::FuncParam(name(), ids, ret)
+            }
+            NodeKind::VarActual => ASTNode::VarActual(ids[0]),
+            NodeKind::ProcActual => ASTNode::ProcActual(name()),
+            NodeKind::FuncActual => ASTNode::FuncActual(name()),
+        };
+        let span = node.token_span();
+        let (row, col) = self.position(span.start);
+        self.ast.alloc(kind, span, row, col)
+    }
+
+    // Fila y columna (en caracteres) de un offset, como las cuenta el lexer.
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let row = self.lines.partition_point(|&start| start <= offset);
+        let line_start = self.lines[row - 1];
+        (row, self.text[line_start..offset].chars().count() + 1)
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+
+use crate::ast::{Ast, NodeId};
+use crate::token::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+// Mensaje de un paso de análisis, con la posición del nodo que lo originó.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Diagnostic {
+    pub fn error(ast: &Ast, id: NodeId, message: String) -> Self {
+        Diagnostic::new(Severity::Error, ast, id, message)
+    }
+
+    pub fn warning(ast: &Ast, id: NodeId, message: String) -> Self {
+        Diagnostic::new(Severity::Warning, ast, id, message)
+    }
+
+    fn new(severity: Severity, ast: &Ast, id: NodeId, message: String) -> Self {
+        let node = &ast[id];
+        Diagnostic { severity, message, span: node.span, row: node.row, col: node.col }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} at row {}, col {}", self.severity, self.message, self.row, self.col)
+    }
+}
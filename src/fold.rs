@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::checker::TypeInfo;
+use crate::diagnostic::Diagnostic;
+use crate::identification::{Decl, Identification};
+use crate::stdenv::{self, Primitive, Standard, MAXINT};
+use crate::types::Type;
+
+// Valor de una expresión escalar conocido en tiempo de compilación.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Char(char),
+    Bool(bool),
+}
+
+impl Value {
+    // Representación en una palabra de la máquina, como en el intérprete.
+    pub fn to_int(self) -> i64 {
+        match self {
+            Value::Int(n) => n,
+            Value::Char(c) => c as i64,
+            Value::Bool(b) => b as i64,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Char(c) => write!(f, "{:?}", c),
+            Value::Bool(b) => f.write_str(if *b { "true" } else { "false" }),
+        }
+    }
+}
+
+// Valores de las expresiones constantes del programa, incluidas las
+// referencias a `const` cuya expresión se pudo evaluar.
+#[derive(Debug, Default)]
+pub struct Constants {
+    pub values: HashMap<NodeId, Value>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Constants {
+    pub fn value(&self, id: NodeId) -> Option<Value> {
+        self.values.get(&id).copied()
+    }
+}
+
+// Evalúa las expresiones constantes de un programa ya chequeado. Las
+// divisiones por cero, los desbordes y los `chr` inválidos con operandos
+// constantes se reportan como errores en lugar de esperar a la ejecución, igual
+// que los literales y los tamaños de arreglo mayores que maxint.
+pub fn fold(ast: &Ast, identification: &Identification, types: &TypeInfo) -> Constants {
+    let mut folder = Folder { ast, identification, types, constants: Constants::default() };
+    if let Some(root) = ast.root() {
+        folder.visit(root);
+    }
+    folder.constants
+}
+
+struct Folder<'a> {
+    ast: &'a Ast,
+    identification: &'a Identification,
+    types: &'a TypeInfo,
+    constants: Constants,
+}
+
+impl Folder<'_> {
+    fn error(&mut self, id: NodeId, message: &str) {
+        let message = format!("{} in constant expression", message);
+        self.constants.diagnostics.push(Diagnostic::error(self.ast, id, message));
+    }
+
+    fn out_of_range(&mut self, id: NodeId, what: &str) {
+        let message = format!("{} out of range", what);
+        self.constants.diagnostics.push(Diagnostic::error(self.ast, id, message));
+    }
+
+    // Recorre en orden de aparición, así cada `const` se evalúa antes de sus usos.
+    fn visit(&mut self, id: NodeId) {
+        for child in self.ast.children(id) {
+            self.visit(child);
+        }
+        if let Some(value) = self.evaluate(id) {
+            self.constants.values.insert(id, value);
+        }
+    }
+
+    fn evaluate(&mut self, id: NodeId) -> Option<Value> {
+        match self.ast.kind(id) {
+            ASTNode::Number(n) if *n > MAXINT => {
+                self.out_of_range(id, "integer literal");
+                None
+            }
+            ASTNode::Number(n) => Some(Value::Int(*n)),
+            ASTNode::ArrayType(size, _) => {
+                if *size > MAXINT {
+                    self.out_of_range(id, "array size");
+                }
+                None
+            }
+            ASTNode::Char(c) => Some(Value::Char(*c)),
+            ASTNode::Expression(inner) => self.constants.value(*inner),
+            ASTNode::LetExpression(_, expr) => self.constants.value(*expr),
+            ASTNode::IfExpression(cond, then_expr, else_expr) => match self.constants.value(*cond)? {
+                Value::Bool(true) => self.constants.value(*then_expr),
+                _ => self.constants.value(*else_expr),
+            },
+            ASTNode::Identifier(_) => match self.identification.decl(id)? {
+                Decl::Standard(name) => match stdenv::lookup(name)? {
+                    Standard::Const(Type::Bool, value) => Some(Value::Bool(value != 0)),
+                    Standard::Const(Type::Char, value) => char::from_u32(value as u32).map(Value::Char),
+                    Standard::Const(_, value) => Some(Value::Int(value)),
+                    _ => None,
+                },
+                Decl::Node(decl) => match self.ast.kind(decl) {
+                    ASTNode::Const(_, expr) => self.constants.value(*expr),
+                    _ => None,
+                },
+            },
+            ASTNode::Call(_, args) => {
+                let Decl::Standard(name) = self.identification.decl(id)? else {
+                    return None;
+                };
+                let (Standard::Func(_, primitive), [arg]) = (stdenv::lookup(name)?, &args[..]) else {
+                    return None;
+                };
+                let arg = self.constants.value(*arg)?;
+                match primitive {
+                    Primitive::Ord => Some(Value::Int(arg.to_int())),
+                    Primitive::Chr => {
+                        let c = u32::try_from(arg.to_int()).ok().and_then(char::from_u32);
+                        if c.is_none() {
+                            self.error(id, "invalid character code");
+                        }
+                        c.map(Value::Char)
+                    }
+                    _ => None,
+                }
+            }
+            ASTNode::Unary(_, operand) => {
+                let operand = self.constants.value(*operand)?;
+                match (self.primitive(id)?, operand) {
+                    (Primitive::Not, Value::Bool(b)) => Some(Value::Bool(!b)),
+                    _ => None,
+                }
+            }
+            ASTNode::Operator(_, left, right) => {
+                let (left, right) = (self.constants.value(*left)?, self.constants.value(*right)?);
+                let (l, r) = (left.to_int(), right.to_int());
+                let value = match self.primitive(id)? {
+                    Primitive::Eq => Value::Bool(left == right),
+                    Primitive::Ne => Value::Bool(left != right),
+                    Primitive::Lt => Value::Bool(l < r),
+                    Primitive::Le => Value::Bool(l <= r),
+                    Primitive::Gt => Value::Bool(l > r),
+                    Primitive::Ge => Value::Bool(l >= r),
+                    Primitive::And => Value::Bool(l != 0 && r != 0),
+                    Primitive::Or => Value::Bool(l != 0 || r != 0),
+                    Primitive::Add => Value::Int(l + r),
+                    Primitive::Sub => Value::Int(l - r),
+                    Primitive::Mul => Value::Int(l * r),
+                    Primitive::Div | Primitive::Mod if r == 0 => {
+                        self.error(id, "division by zero");
+                        return None;
+                    }
+                    Primitive::Div => Value::Int(l / r),
+                    Primitive::Mod => Value::Int(l.rem_euclid(r)),
+                    _ => return None,
+                };
+                if let Value::Int(n) = value {
+                    if n.abs() > MAXINT {
+                        self.error(id, "integer overflow");
+                        return None;
+                    }
+                }
+                Some(value)
+            }
+            _ => None,
+        }
+        .filter(|_| !self.types.type_of(id).is_error())
+    }
+
+    fn primitive(&self, id: NodeId) -> Option<Primitive> {
+        let Decl::Standard(name) = self.types.operator(id)? else {
+            return None;
+        };
+        match stdenv::lookup(name)? {
+            Standard::Unary(.., primitive) | Standard::Binary(.., primitive) | Standard::Equality(primitive) => {
+                Some(primitive)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::identification::{identify, Decl, Identification};
+use crate::lexer::Lexer;
+use crate::parser::SyntaxParser;
+use crate::stdenv::{self, Standard};
+use crate::token::{Span, Token, TokenType};
+
+// Categoría de resaltado de un rango del texto fuente.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    Keyword,
+    Comment,
+    Number,
+    Char,
+    Operator,
+    Punctuation,
+    // Nombres sin resolver: el programa no compila o el nombre no está declarado.
+    Identifier,
+    Constant,
+    Variable,
+    Parameter,
+    Function,
+    Procedure,
+    Type,
+    Field,
+    Illegal,
+}
+
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Keyword => "keyword",
+            Category::Comment => "comment",
+            Category::Number => "number",
+            Category::Char => "char",
+            Category::Operator => "operator",
+            Category::Punctuation => "punctuation",
+            Category::Identifier => "identifier",
+            Category::Constant => "constant",
+            Category::Variable => "variable",
+            Category::Parameter => "parameter",
+            Category::Function => "function",
+            Category::Procedure => "procedure",
+            Category::Type => "type",
+            Category::Field => "field",
+            Category::Illegal => "illegal",
+        }
+    }
+
+    // Código SGR de la terminal; `None` se muestra sin color.
+    fn ansi(self) -> Option<&'static str> {
+        match self {
+            Category::Keyword => Some("1;35"),
+            Category::Comment => Some("2;3"),
+            Category::Number | Category::Char => Some("33"),
+            Category::Operator => Some("36"),
+            Category::Punctuation | Category::Identifier | Category::Variable => None,
+            Category::Constant => Some("1;33"),
+            Category::Parameter => Some("3"),
+            Category::Function | Category::Procedure => Some("34"),
+            Category::Type => Some("32"),
+            Category::Field => Some("96"),
+            Category::Illegal => Some("1;41"),
+        }
+    }
+
+    fn css(self) -> &'static str {
+        match self {
+            Category::Keyword => "color: #a626a4; font-weight: bold",
+            Category::Comment => "color: #a0a1a7; font-style: italic",
+            Category::Number | Category::Char => "color: #986801",
+            Category::Operator => "color: #0184bc",
+            Category::Punctuation | Category::Identifier | Category::Variable => "color: inherit",
+            Category::Constant => "color: #c18401; font-weight: bold",
+            Category::Parameter => "font-style: italic",
+            Category::Function | Category::Procedure => "color: #4078f2",
+            Category::Type => "color: #50a14f",
+            Category::Field => "color: #e45649",
+            Category::Illegal => "color: #ffffff; background: #e45649",
+        }
+    }
+}
+
+const CATEGORIES: [Category; 15] = [
+    Category::Keyword,
+    Category::Comment,
+    Category::Number,
+    Category::Char,
+    Category::Operator,
+    Category::Punctuation,
+    Category::Identifier,
+    Category::Constant,
+    Category::Variable,
+    Category::Parameter,
+    Category::Function,
+    Category::Procedure,
+    Category::Type,
+    Category::Field,
+    Category::Illegal,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highlight {
+    pub span: Span,
+    pub category: Category,
+}
+
+// Clasifica cada token del texto, comentarios incluidos, en orden. Si el
+// programa se puede analizar, los nombres se clasifican según su declaración;
+// si no, quedan como Identifier.
+pub fn classify(src: &str) -> Vec<Highlight> {
+    let tokens: Vec<Token> = Lexer::new(src).with_comments().collect();
+    let names = SyntaxParser::new(src)
+        .parse_program()
+        .map(|ast| {
+            let identification = identify(&ast);
+            Names::new(&ast, &identification, &tokens)
+        })
+        .unwrap_or_default();
+    tokens
+        .iter()
+        .filter(|t| t.token_type != TokenType::EOF)
+        .map(|t| {
+            let category = match t.token_type {
+                TokenType::Comment => Category::Comment,
+                TokenType::IntegerLiteral => Category::Number,
+                TokenType::CharLiteral => Category::Char,
+                TokenType::Operator => Category::Operator,
+                TokenType::Illegal => Category::Illegal,
+                TokenType::Identifier => names.0.get(&t.span.start).copied().unwrap_or(Category::Identifier),
+                tt if tt.is_keyword() => Category::Keyword,
+                _ => Category::Punctuation,
+            };
+            Highlight { span: t.span, category }
+        })
+        .collect()
+}
+
+// Categoría de cada nombre del programa, indexada por el offset de su token.
+#[derive(Default)]
+struct Names(HashMap<usize, Category>);
+
+impl Names {
+    fn new(ast: &Ast, identification: &Identification, tokens: &[Token]) -> Names {
+        let mut names = Names::default();
+        // Primer identificador dentro del rango del nodo: el nombre declarado
+        // en const, var, type, func, proc, parámetros y campos.
+        let first_name = |id: NodeId| {
+            let span = ast.span(id);
+            tokens.iter().find(|t| t.span.start >= span.start && t.token_type == TokenType::Identifier).map(|t| t.span)
+        };
+        for id in ast.ids() {
+            let (span, category) = match ast.kind(id) {
+                ASTNode::Identifier(_)
+                | ASTNode::TypeName(_)
+                | ASTNode::Call(..)
+                | ASTNode::ProcActual(_)
+                | ASTNode::FuncActual(_) => {
+                    let category = match identification.decl(id) {
+                        Some(Decl::Node(decl)) => declared(ast, decl),
+                        Some(Decl::Standard(name)) => match stdenv::lookup(name) {
+                            Some(Standard::Type(_)) => Category::Type,
+                            Some(Standard::Const(..)) => Category::Constant,
+                            Some(Standard::Func(..)) => Category::Function,
+                            Some(Standard::Proc(..)) => Category::Procedure,
+                            _ => continue,
+                        },
+                        None => continue,
+                    };
+                    (first_name(id), category)
+                }
+                // El nombre de un operador declarado se resalta como operador.
+                ASTNode::Func(name, ..) if name.is_operator() => continue,
+                ASTNode::Const(..)
+                | ASTNode::Var(..)
+                | ASTNode::Type(..)
+                | ASTNode::Func(..)
+                | ASTNode::Proc(..)
+                | ASTNode::ValueParam(..)
+                | ASTNode::VarParam(..)
+                | ASTNode::ProcParam(..)
+                | ASTNode::FuncParam(..) => (first_name(id), declared(ast, id)),
+                ASTNode::FieldType(..) | ASTNode::FieldInit(..) => (first_name(id), Category::Field),
+                // En `r.x` el campo es el último token.
+                ASTNode::Field(..) => {
+                    let end = ast.span(id).end;
+                    (tokens.iter().find(|t| t.span.end == end).map(|t| t.span), Category::Field)
+                }
+                _ => continue,
+            };
+            if let Some(span) = span {
+                names.0.insert(span.start, category);
+            }
+        }
+        names
+    }
+}
+
+fn declared(ast: &Ast, decl: NodeId) -> Category {
+    match ast.kind(decl) {
+        ASTNode::Const(..) => Category::Constant,
+        ASTNode::Type(..) => Category::Type,
+        ASTNode::Func(..) => Category::Function,
+        ASTNode::Proc(..) => Category::Procedure,
+        ASTNode::ValueParam(..) | ASTNode::VarParam(..) | ASTNode::ProcParam(..) | ASTNode::FuncParam(..) => {
+            Category::Parameter
+        }
+        _ => Category::Variable,
+    }
+}
+
+// Recorre el texto en tramos: los resaltados y los espacios entre ellos.
+fn pieces<'a>(src: &'a str, highlights: &[Highlight]) -> Vec<(&'a str, Option<Category>)> {
+    let mut pieces = Vec::new();
+    let mut pos = 0;
+    for h in highlights {
+        if h.span.start > pos {
+            pieces.push((&src[pos..h.span.start], None));
+        }
+        pieces.push((&src[h.span.start..h.span.end], Some(h.category)));
+        pos = h.span.end;
+    }
+    if pos < src.len() {
+        pieces.push((&src[pos..], None));
+    }
+    pieces
+}
+
+pub fn to_ansi(src: &str, highlights: &[Highlight]) -> String {
+    let mut out = String::new();
+    for (text, category) in pieces(src, highlights) {
+        match category.and_then(Category::ansi) {
+            Some(code) => write!(out, "\x1b[{}m{}\x1b[0m", code, text).unwrap(),
+            None => out.push_str(text),
+        }
+    }
+    out
+}
+
+// Página HTML independiente, con la hoja de estilos incluida.
+pub fn to_html(src: &str, highlights: &[Highlight], title: &str) -> String {
+    let mut out = String::new();
+    writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(out, "<title>{}</title>\n<style>", escape(title)).unwrap();
+    writeln!(out, "pre.triangle {{ font-family: monospace; line-height: 1.4; }}").unwrap();
+    for category in CATEGORIES {
+        writeln!(out, ".tri-{} {{ {}; }}", category.name(), category.css()).unwrap();
+    }
+    write!(out, "</style>\n</head>\n<body>\n<pre class=\"triangle\">").unwrap();
+    for (text, category) in pieces(src, highlights) {
+        match category {
+            Some(category) => write!(out, "<span class=\"tri-{}\">{}</span>", category.name(), escape(text)).unwrap(),
+            None => out.push_str(&escape(text)),
+        }
+    }
+    writeln!(out, "</pre>\n</body>\n</html>").unwrap();
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
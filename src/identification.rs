@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::diagnostic::Diagnostic;
+use crate::stdenv;
+use crate::symbol::Symbol;
+
+// A qué declaración se refiere un nombre: un nodo del programa o un
+// identificador predefinido del entorno estándar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Decl {
+    Node(NodeId),
+    Standard(Symbol),
+}
+
+// Tabla de identificación con bloques anidados. El nivel 0 es el entorno
+// estándar, así que un programa puede redeclarar `ord` o `Integer` sin que sea
+// un duplicado. Un operador puede tener varias declaraciones en un mismo
+// bloque, que el chequeo de tipos distingue por el tipo de los operandos.
+pub struct IdentificationTable {
+    scopes: Vec<HashMap<Symbol, Vec<Decl>>>,
+}
+
+impl IdentificationTable {
+    pub fn new() -> Self {
+        let standard = stdenv::names().map(|name| (name, vec![Decl::Standard(name)])).collect();
+        IdentificationTable { scopes: vec![standard] }
+    }
+
+    pub fn level(&self) -> usize {
+        self.scopes.len() - 1
+    }
+
+    pub fn open_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn close_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    // Declara `name` en el bloque actual. Si ya estaba declarado en ese mismo
+    // bloque devuelve la declaración anterior y no la reemplaza.
+    pub fn enter(&mut self, name: Symbol, decl: Decl) -> Option<Decl> {
+        let scope = self.scopes.last_mut().unwrap();
+        match scope.get(&name) {
+            Some(previous) => Some(previous[0]),
+            None => {
+                scope.insert(name, vec![decl]);
+                None
+            }
+        }
+    }
+
+    // Agrega una declaración de operador al bloque actual, sin reemplazar las
+    // que ya tenga. Dos con los mismos parámetros las rechaza el chequeo de
+    // tipos, que es quien los conoce.
+    pub fn overload(&mut self, name: Symbol, decl: Decl) {
+        self.scopes.last_mut().unwrap().entry(name).or_default().push(decl);
+    }
+
+    pub fn retrieve(&self, name: Symbol) -> Option<Decl> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(&name).map(|decls| decls[0]))
+    }
+
+    // Todas las declaraciones visibles de `name`, de la más interna a la del
+    // entorno estándar.
+    pub fn retrieve_all(&self, name: Symbol) -> Vec<Decl> {
+        self.scopes.iter().rev().filter_map(|scope| scope.get(&name)).flatten().copied().collect()
+    }
+}
+
+impl Default for IdentificationTable {
+    fn default() -> Self {
+        IdentificationTable::new()
+    }
+}
+
+// Resultado de la identificación: cada uso (Identifier, Call, Operator, Unary,
+// TypeName, ProcActual, FuncActual) enlazado con su declaración. Un operador
+// se enlaza con la más interna y además guarda en `overloads` todas las
+// visibles, entre las que el chequeo de tipos elige.
+#[derive(Debug, Default)]
+pub struct Identification {
+    pub decls: HashMap<NodeId, Decl>,
+    pub overloads: HashMap<NodeId, Vec<Decl>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Identification {
+    pub fn decl(&self, use_id: NodeId) -> Option<Decl> {
+        self.decls.get(&use_id).copied()
+    }
+
+    pub fn overloads(&self, use_id: NodeId) -> &[Decl] {
+        self.overloads.get(&use_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn uses(&self, decl: Decl) -> Vec<NodeId> {
+        let mut uses: Vec<NodeId> = self.decls.iter().filter(|(_, &d)| d == decl).map(|(&id, _)| id).collect();
+        uses.sort();
+        uses
+    }
+}
+
+pub fn identify(ast: &Ast) -> Identification {
+    let mut identifier = Identifier { ast, table: IdentificationTable::new(), result: Identification::default() };
+    if let Some(root) = ast.root() {
+        identifier.table.open_scope();
+        identifier.visit(root);
+    }
+    identifier.result
+}
+
+struct Identifier<'a> {
+    ast: &'a Ast,
+    table: IdentificationTable,
+    result: Identification,
+}
+
+impl Identifier<'_> {
+    fn visit(&mut self, id: NodeId) {
+        match self.ast.kind(id) {
+            ASTNode::Let(declarations, command) | ASTNode::LetExpression(declarations, command) => {
+                self.table.open_scope();
+                self.visit(*declarations);
+                self.visit(*command);
+                self.table.close_scope();
+            }
+            ASTNode::Const(name, expr) => {
+                self.visit(*expr);
+                self.declare(*name, id);
+            }
+            ASTNode::Var(name, type_denoter)
+            | ASTNode::ValueParam(name, type_denoter)
+            | ASTNode::VarParam(name, type_denoter) => {
+                self.visit(*type_denoter);
+                self.declare(*name, id);
+            }
+            ASTNode::Type(name, type_denoter) => {
+                self.visit(*type_denoter);
+                self.declare(*name, id);
+            }
+            // El nombre de la rutina se declara antes del cuerpo para permitir
+            // la recursión; los parámetros viven en un bloque propio.
+            ASTNode::Func(name, params, return_type, body) => {
+                self.visit(*return_type);
+                if name.is_operator() {
+                    self.table.overload(*name, Decl::Node(id));
+                } else {
+                    self.declare(*name, id);
+                }
+                self.visit_routine(params, *body);
+            }
+            ASTNode::Proc(name, params, body) => {
+                self.declare(*name, id);
+                self.visit_routine(params, *body);
+            }
+            // Los parámetros de una rutina recibida como parámetro solo dan su
+            // firma; sus nombres no son visibles en ningún lado.
+            ASTNode::ProcParam(name, params) => {
+                self.visit_params(params);
+                self.declare(*name, id);
+            }
+            ASTNode::FuncParam(name, params, return_type) => {
+                self.visit(*return_type);
+                self.visit_params(params);
+                self.declare(*name, id);
+            }
+            ASTNode::Identifier(name)
+            | ASTNode::TypeName(name)
+            | ASTNode::ProcActual(name)
+            | ASTNode::FuncActual(name) => self.resolve(*name, id),
+            ASTNode::Call(name, _) => {
+                self.resolve(*name, id);
+                self.visit_children(id);
+            }
+            ASTNode::Operator(name, _, _) | ASTNode::Unary(name, _) => {
+                self.resolve(*name, id);
+                self.result.overloads.insert(id, self.table.retrieve_all(*name));
+                self.visit_children(id);
+            }
+            // Los nombres de campo no pasan por la tabla: se resuelven con el
+            // tipo del registro.
+            _ => self.visit_children(id),
+        }
+    }
+
+    fn visit_children(&mut self, id: NodeId) {
+        for child in self.ast.children(id) {
+            self.visit(child);
+        }
+    }
+
+    fn visit_routine(&mut self, params: &[NodeId], body: NodeId) {
+        self.table.open_scope();
+        for &param in params {
+            self.visit(param);
+        }
+        self.visit(body);
+        self.table.close_scope();
+    }
+
+    fn visit_params(&mut self, params: &[NodeId]) {
+        self.table.open_scope();
+        for &param in params {
+            self.visit(param);
+        }
+        self.table.close_scope();
+    }
+
+    fn declare(&mut self, name: Symbol, id: NodeId) {
+        if let Some(previous) = self.table.enter(name, Decl::Node(id)) {
+            let message = match previous {
+                Decl::Node(prev) => format!(
+                    "duplicate declaration of \"{}\" (first declared at row {}, col {})",
+                    name, self.ast[prev].row, self.ast[prev].col
+                ),
+                Decl::Standard(_) => format!("duplicate declaration of \"{}\"", name),
+            };
+            self.result.diagnostics.push(Diagnostic::error(self.ast, id, message));
+        }
+    }
+
+    fn resolve(&mut self, name: Symbol, id: NodeId) {
+        match self.table.retrieve(name) {
+            Some(decl) => {
+                self.result.decls.insert(id, decl);
+            }
+            None => {
+                let message = format!("undeclared identifier \"{}\"", name);
+                self.result.diagnostics.push(Diagnostic::error(self.ast, id, message));
+            }
+        }
+    }
+}
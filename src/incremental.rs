@@ -0,0 +1,133 @@
+use crate::ast::Ast;
+use crate::cst::{self, SyntaxNode};
+use crate::lexer::Lexer;
+use crate::parser::{Memo, Previous, SyntaxParser};
+use crate::token::{Span, Token, TokenType};
+
+// Edición del texto: reemplaza el rango de bytes `span` del texto actual.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(span: Span, text: impl Into<String>) -> Self {
+        TextEdit { span, text: text.into() }
+    }
+}
+
+// Documento abierto en un editor. Cada edición vuelve a escanear solo las
+// líneas que toca (ningún token cruza un salto de línea) y vuelve a analizar
+// compartiendo con el árbol concreto anterior los comandos y declaraciones
+// que no cambiaron.
+pub struct Document {
+    text: String,
+    tokens: Vec<Token<'static>>,
+    cst: Option<SyntaxNode>,
+    ast: Ast,
+    memo: Vec<Memo>,
+    error: Option<String>,
+    reused: usize,
+}
+
+impl Document {
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let tokens = Lexer::new(&text).map(Token::into_owned).collect();
+        let mut document = Document { text, tokens, cst: None, ast: Ast::new(), memo: Vec::new(), error: None, reused: 0 };
+        document.parse(None);
+        document
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn tokens(&self) -> &[Token<'static>] {
+        &self.tokens
+    }
+
+    // El mismo resultado que `SyntaxParser::parse_cst` sobre el texto actual.
+    pub fn cst(&self) -> Result<&SyntaxNode, &str> {
+        self.cst.as_ref().ok_or_else(|| self.error.as_deref().unwrap_or_default())
+    }
+
+    // El mismo resultado que `SyntaxParser::parse_program` sobre el texto actual.
+    pub fn ast(&self) -> Result<&Ast, &str> {
+        match &self.error {
+            Some(error) => Err(error),
+            None => Ok(&self.ast),
+        }
+    }
+
+    // Cantidad de nodos que el último análisis compartió con el árbol anterior.
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    pub fn edit(&mut self, edit: &TextEdit) -> Result<(), String> {
+        let Span { start, end } = edit.span;
+        if start > end || end > self.text.len() || !self.text.is_char_boundary(start) || !self.text.is_char_boundary(end) {
+            return Err(format!("Invalid edit range {}..{} for a text of {} bytes", start, end, self.text.len()));
+        }
+        // Líneas enteras afectadas, en el texto anterior.
+        let line_start = self.text[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.text[end..].find('\n').map_or(self.text.len(), |i| end + i + 1);
+        let old_rows = self.text[line_start..line_end].matches('\n').count() as isize;
+        let delta = edit.text.len() as isize - (end - start) as isize;
+
+        self.text.replace_range(start..end, &edit.text);
+        let new_end = line_end.wrapping_add_signed(delta);
+        let rows = self.text[line_start..new_end].matches('\n').count() as isize - old_rows;
+
+        let row = self.text[..line_start].matches('\n').count() + 1;
+        let mut relexed: Vec<Token<'static>> =
+            Lexer::at(&self.text[line_start..new_end], row, line_start).map(Token::into_owned).collect();
+        let at_end = new_end == self.text.len();
+        if !at_end {
+            relexed.retain(|t| t.token_type != TokenType::EOF);
+        }
+
+        let first = self.tokens.partition_point(|t| t.span.start < line_start);
+        let last = self.tokens.partition_point(|t| t.span.start < line_end);
+        let mut tail = self.tokens.split_off(last);
+        self.tokens.truncate(first);
+        self.tokens.extend(relexed);
+        // Si la zona llega al final, el EOF escaneado reemplaza al anterior.
+        if !at_end {
+            for token in &mut tail {
+                token.span = Span::new(token.span.start.wrapping_add_signed(delta), token.span.end.wrapping_add_signed(delta));
+                token.row = token.row.wrapping_add_signed(rows);
+            }
+            self.tokens.extend(tail);
+        }
+
+        let previous = Previous { memo: std::mem::take(&mut self.memo), damage: Span::new(line_start, line_end), delta };
+        self.parse(Some(previous));
+        Ok(())
+    }
+
+    fn parse(&mut self, previous: Option<Previous>) {
+        let mut parser = SyntaxParser::incremental(&self.text, std::mem::take(&mut self.tokens), previous);
+        let result = parser.parse_cst();
+        let (tokens, memo, reused) = parser.into_parts();
+        self.tokens = tokens.into_iter().map(Token::into_owned).collect();
+        self.memo = memo;
+        self.reused = reused;
+        // Las reglas de un análisis fallido igual sirven para reutilizar lo
+        // anterior al error en la próxima edición.
+        match result {
+            Ok(cst) => {
+                self.ast = cst::lower(&cst);
+                self.cst = Some(cst);
+                self.error = None;
+            }
+            Err(error) => {
+                self.ast = Ast::new();
+                self.cst = None;
+                self.error = Some(error);
+            }
+        }
+    }
+}
@@ -0,0 +1,442 @@
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+use std::thread;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::checker::TypeInfo;
+use crate::identification::{Decl, Identification};
+use crate::stdenv::{self, Primitive, Standard, MAXINT};
+use crate::types::Type;
+
+// Intérprete que recorre el AST ya identificado y chequeado. La memoria es un
+// vector de palabras como la pila de TAM: cada variable o constante ocupa
+// `Type::size()` palabras consecutivas (Char guarda su código, Boolean 0 o 1).
+pub struct Interpreter<'a, R, W> {
+    ast: &'a Ast,
+    identification: &'a Identification,
+    types: &'a TypeInfo,
+    store: Vec<i64>,
+    env: Option<Rc<Env>>,
+    depth: usize,
+    input: R,
+    output: W,
+}
+
+// Entorno enlazado: cada declaración visible con su dirección o, si es una
+// rutina, con su declaración y el entorno donde fue declarada. Un parámetro
+// `proc` o `func` queda ligado a la rutina que se pasó como argumento.
+struct Env {
+    decl: NodeId,
+    binding: Binding,
+    parent: Option<Rc<Env>>,
+}
+
+#[derive(Clone)]
+enum Binding {
+    Address(usize),
+    Routine(NodeId, Option<Rc<Env>>),
+}
+
+// Argumento ya evaluado: un valor que se copia en el parámetro, o la
+// variable o rutina que el parámetro pasa a denotar.
+enum Argument {
+    Value(Vec<i64>),
+    Bound(Binding),
+}
+
+// Límite de llamadas anidadas, para informar la recursión infinita como error
+// de ejecución en lugar de agotar la pila del proceso.
+const MAX_DEPTH: usize = 2000;
+
+// Cada llamada de Triangle usa varios marcos de Rust, así que MAX_DEPTH
+// llamadas no caben en la pila de un hilo común: `run` interpreta en un hilo
+// con esta pila.
+const STACK_SIZE: usize = 512 << 20;
+
+pub fn run<R: BufRead + Send, W: Write + Send>(
+    ast: &Ast,
+    identification: &Identification,
+    types: &TypeInfo,
+    input: R,
+    output: W,
+) -> Result<(), String> {
+    thread::scope(|scope| {
+        let interpreter = thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, || Interpreter::new(ast, identification, types, input, output).run())
+            .map_err(|e| e.to_string())?;
+        interpreter.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+impl<'a, R: BufRead, W: Write> Interpreter<'a, R, W> {
+    pub fn new(ast: &'a Ast, identification: &'a Identification, types: &'a TypeInfo, input: R, output: W) -> Self {
+        Interpreter { ast, identification, types, store: Vec::new(), env: None, depth: 0, input, output }
+    }
+
+    pub fn run(&mut self) -> Result<(), String> {
+        if let Some(root) = self.ast.root() {
+            self.command(root)?;
+        }
+        self.output.flush().map_err(|e| e.to_string())
+    }
+
+    fn error<T>(&self, id: NodeId, message: &str) -> Result<T, String> {
+        let node = &self.ast[id];
+        Err(format!("{} at row {}, col {}", message, node.row, node.col))
+    }
+
+    fn bind(&mut self, decl: NodeId, binding: Binding) {
+        let parent = self.env.take();
+        self.env = Some(Rc::new(Env { decl, binding, parent }));
+    }
+
+    fn lookup(&self, decl: NodeId) -> Option<Binding> {
+        let mut env = self.env.as_deref();
+        while let Some(e) = env {
+            if e.decl == decl {
+                return Some(e.binding.clone());
+            }
+            env = e.parent.as_deref();
+        }
+        None
+    }
+
+    fn allocate(&mut self, decl: NodeId, value: Vec<i64>) {
+        let address = self.store.len();
+        self.store.extend(value);
+        self.bind(decl, Binding::Address(address));
+    }
+
+    fn command(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Let(declarations, command) => {
+                let (env, top) = (self.env.clone(), self.store.len());
+                self.declaration(*declarations)?;
+                let result = self.command(*command);
+                self.env = env;
+                self.store.truncate(top);
+                result
+            }
+            ASTNode::Command(commands) => {
+                for &command in commands {
+                    self.command(command)?;
+                }
+                Ok(())
+            }
+            ASTNode::Assign(vname, expr) => {
+                let value = self.expression(*expr)?;
+                let address = self.vname(*vname)?;
+                self.store[address..address + value.len()].copy_from_slice(&value);
+                Ok(())
+            }
+            ASTNode::Call(_, args) => self.call(id, args).map(|_| ()),
+            ASTNode::If(cond, _, then_branch, _, else_branch) => {
+                if self.expression(*cond)?[0] != 0 {
+                    self.command(*then_branch)
+                } else {
+                    self.command(*else_branch)
+                }
+            }
+            ASTNode::While(cond, body) => {
+                while self.expression(*cond)?[0] != 0 {
+                    self.command(*body)?;
+                }
+                Ok(())
+            }
+            ASTNode::Empty => Ok(()),
+            _ => self.error(id, "not a command"),
+        }
+    }
+
+    fn declaration(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Declaration(declarations) => {
+                for &declaration in declarations {
+                    self.declaration(declaration)?;
+                }
+            }
+            ASTNode::Const(_, expr) => {
+                let value = self.expression(*expr)?;
+                self.allocate(id, value);
+            }
+            ASTNode::Var(..) => {
+                let size = self.types.type_of(id).size();
+                self.allocate(id, vec![0; size]);
+            }
+            ASTNode::Func(..) | ASTNode::Proc(..) => {
+                let env = self.env.clone();
+                self.bind(id, Binding::Routine(id, env));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Llama a una rutina declarada o predefinida y devuelve su resultado (vacío
+    // para los procedimientos). Un operador declarado en el programa se llama
+    // como una función con sus operandos.
+    fn call(&mut self, id: NodeId, args: &[NodeId]) -> Result<Vec<i64>, String> {
+        match self.types.operator(id).or_else(|| self.identification.decl(id)) {
+            Some(Decl::Standard(name)) => match stdenv::lookup(name) {
+                Some(Standard::Func(_, primitive)) | Some(Standard::Proc(_, primitive)) => {
+                    self.primitive(id, primitive, args)
+                }
+                _ => self.error(id, "not a routine"),
+            },
+            Some(Decl::Node(decl)) => {
+                let Some(Binding::Routine(routine, closure)) = self.lookup(decl) else {
+                    return self.error(id, "routine is not in scope");
+                };
+                let mut values = Vec::with_capacity(args.len());
+                for &arg in args {
+                    values.push(self.argument(arg)?);
+                }
+                if self.depth == MAX_DEPTH {
+                    return self.error(id, "stack overflow");
+                }
+                let (env, top) = (self.env.clone(), self.store.len());
+                self.depth += 1;
+                self.env = closure.clone();
+                self.bind(routine, Binding::Routine(routine, closure));
+                let result = match self.ast.kind(routine) {
+                    ASTNode::Func(_, params, _, body) => {
+                        self.pass(params, values);
+                        self.expression(*body)
+                    }
+                    ASTNode::Proc(_, params, body) => {
+                        self.pass(params, values);
+                        self.command(*body).map(|_| Vec::new())
+                    }
+                    _ => self.error(id, "not a routine"),
+                };
+                self.depth -= 1;
+                self.env = env;
+                self.store.truncate(top);
+                result
+            }
+            None => self.error(id, "undeclared routine"),
+        }
+    }
+
+    fn argument(&mut self, arg: NodeId) -> Result<Argument, String> {
+        match self.ast.kind(arg) {
+            ASTNode::VarActual(vname) => self.vname(*vname).map(|address| Argument::Bound(Binding::Address(address))),
+            ASTNode::ProcActual(_) | ASTNode::FuncActual(_) => match self.identification.decl(arg) {
+                Some(Decl::Node(decl)) => match self.lookup(decl) {
+                    Some(routine @ Binding::Routine(..)) => Ok(Argument::Bound(routine)),
+                    _ => self.error(arg, "routine is not in scope"),
+                },
+                _ => self.error(arg, "not a routine"),
+            },
+            _ => self.expression(arg).map(Argument::Value),
+        }
+    }
+
+    fn pass(&mut self, params: &[NodeId], values: Vec<Argument>) {
+        for (&param, value) in params.iter().zip(values) {
+            match value {
+                Argument::Value(value) => self.allocate(param, value),
+                Argument::Bound(binding) => self.bind(param, binding),
+            }
+        }
+    }
+
+    fn expression(&mut self, id: NodeId) -> Result<Vec<i64>, String> {
+        match self.ast.kind(id) {
+            ASTNode::Number(n) => {
+                if *n > MAXINT {
+                    return self.error(id, "integer literal too large");
+                }
+                Ok(vec![*n])
+            }
+            ASTNode::Char(c) => Ok(vec![*c as i64]),
+            ASTNode::Expression(inner) => self.expression(*inner),
+            ASTNode::Identifier(_) => match self.identification.decl(id) {
+                Some(Decl::Standard(name)) => match stdenv::lookup(name) {
+                    Some(Standard::Const(_, value)) => Ok(vec![value]),
+                    _ => self.error(id, "not a value"),
+                },
+                _ => self.fetch(id),
+            },
+            ASTNode::Field(..) | ASTNode::Index(..) => self.fetch(id),
+            ASTNode::Call(_, args) => self.call(id, args),
+            ASTNode::Operator(_, left, right) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*left, *right])
+            }
+            ASTNode::Unary(_, operand) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*operand])
+            }
+            ASTNode::Operator(_, left, right) => {
+                let left = self.expression(*left)?;
+                let right = self.expression(*right)?;
+                match self.operator(id) {
+                    Some(Primitive::Eq) => Ok(vec![(left == right) as i64]),
+                    Some(Primitive::Ne) => Ok(vec![(left != right) as i64]),
+                    Some(primitive) => self.binary(id, primitive, left[0], right[0]).map(|v| vec![v]),
+                    None => self.error(id, "not an operator"),
+                }
+            }
+            ASTNode::Unary(_, operand) => {
+                let value = self.expression(*operand)?[0];
+                match self.operator(id) {
+                    Some(Primitive::Not) => Ok(vec![(value == 0) as i64]),
+                    _ => self.error(id, "not a unary operator"),
+                }
+            }
+            ASTNode::LetExpression(declarations, expr) => {
+                let (env, top) = (self.env.clone(), self.store.len());
+                self.declaration(*declarations)?;
+                let result = self.expression(*expr);
+                self.env = env;
+                self.store.truncate(top);
+                result
+            }
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                if self.expression(*cond)?[0] != 0 {
+                    self.expression(*then_expr)
+                } else {
+                    self.expression(*else_expr)
+                }
+            }
+            ASTNode::ArrayAggregate(elems) => {
+                let mut value = Vec::new();
+                for &elem in elems {
+                    value.extend(self.expression(elem)?);
+                }
+                Ok(value)
+            }
+            ASTNode::RecordAggregate(fields) => {
+                let mut value = Vec::new();
+                for &field in fields {
+                    if let ASTNode::FieldInit(_, expr) = self.ast.kind(field) {
+                        value.extend(self.expression(*expr)?);
+                    }
+                }
+                Ok(value)
+            }
+            _ => self.error(id, "not an expression"),
+        }
+    }
+
+    fn operator(&self, id: NodeId) -> Option<Primitive> {
+        match self.types.operator(id) {
+            Some(Decl::Standard(name)) => match stdenv::lookup(name)? {
+                Standard::Unary(.., primitive)
+                | Standard::Binary(.., primitive)
+                | Standard::Equality(primitive) => Some(primitive),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn binary(&self, id: NodeId, primitive: Primitive, left: i64, right: i64) -> Result<i64, String> {
+        let value = match primitive {
+            Primitive::And => ((left != 0) && (right != 0)) as i64,
+            Primitive::Or => ((left != 0) || (right != 0)) as i64,
+            Primitive::Lt => (left < right) as i64,
+            Primitive::Le => (left <= right) as i64,
+            Primitive::Gt => (left > right) as i64,
+            Primitive::Ge => (left >= right) as i64,
+            Primitive::Add => left + right,
+            Primitive::Sub => left - right,
+            Primitive::Mul => left * right,
+            Primitive::Div | Primitive::Mod if right == 0 => return self.error(id, "division by zero"),
+            Primitive::Div => left / right,
+            Primitive::Mod => left.rem_euclid(right),
+            _ => return self.error(id, "not a binary operator"),
+        };
+        if value.abs() > MAXINT {
+            return self.error(id, "integer overflow");
+        }
+        Ok(value)
+    }
+
+    fn fetch(&mut self, id: NodeId) -> Result<Vec<i64>, String> {
+        let address = self.vname(id)?;
+        let size = self.types.type_of(id).size();
+        Ok(self.store[address..address + size].to_vec())
+    }
+
+    // Dirección en memoria de un V-name.
+    fn vname(&mut self, id: NodeId) -> Result<usize, String> {
+        match self.ast.kind(id) {
+            ASTNode::Identifier(_) => match self.identification.decl(id) {
+                Some(Decl::Node(decl)) => match self.lookup(decl) {
+                    Some(Binding::Address(address)) => Ok(address),
+                    _ => self.error(id, "not a variable"),
+                },
+                _ => self.error(id, "not a variable"),
+            },
+            ASTNode::Field(record, field) => {
+                let base = self.vname(*record)?;
+                match self.types.type_of(*record).field_offset(*field) {
+                    Some((offset, _)) => Ok(base + offset),
+                    None => self.error(id, "no such field"),
+                }
+            }
+            ASTNode::Index(array, index) => {
+                let base = self.vname(*array)?;
+                let i = self.expression(*index)?[0];
+                match self.types.type_of(*array) {
+                    Type::Array(n, elem) if i >= 0 && (i as usize) < *n => Ok(base + i as usize * elem.size()),
+                    Type::Array(..) => self.error(*index, "array index out of range"),
+                    _ => self.error(id, "not an array"),
+                }
+            }
+            // `var V` como argumento de `get` o `getint`.
+            ASTNode::VarActual(vname) => self.vname(*vname),
+            _ => self.error(id, "not a variable"),
+        }
+    }
+
+    fn primitive(&mut self, id: NodeId, primitive: Primitive, args: &[NodeId]) -> Result<Vec<i64>, String> {
+        let io = |e: std::io::Error| e.to_string();
+        match primitive {
+            Primitive::Ord => self.expression(args[0]),
+            Primitive::Chr => {
+                let code = self.expression(args[0])?[0];
+                if code < 0 || char::from_u32(code as u32).is_none() {
+                    return self.error(id, "invalid character code");
+                }
+                Ok(vec![code])
+            }
+            Primitive::Eof => Ok(vec![stdenv::eof(&mut self.input).map_err(io)? as i64]),
+            Primitive::Eol => Ok(vec![stdenv::eol(&mut self.input).map_err(io)? as i64]),
+            Primitive::Get => {
+                let address = self.vname(args[0])?;
+                self.store[address] = stdenv::read_char(&mut self.input).map_err(io)?.map_or(-1, |c| c as i64);
+                Ok(Vec::new())
+            }
+            Primitive::Put => {
+                let code = self.expression(args[0])?[0];
+                let c = char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+                write!(self.output, "{}", c).map_err(io)?;
+                Ok(Vec::new())
+            }
+            Primitive::GetInt => {
+                let address = self.vname(args[0])?;
+                match stdenv::read_int(&mut self.input).map_err(io)? {
+                    Some(value) => self.store[address] = value,
+                    None => return self.error(id, "invalid integer in input"),
+                }
+                Ok(Vec::new())
+            }
+            Primitive::PutInt => {
+                let value = self.expression(args[0])?[0];
+                write!(self.output, "{}", value).map_err(io)?;
+                Ok(Vec::new())
+            }
+            Primitive::GetEol => {
+                stdenv::skip_line(&mut self.input).map_err(io)?;
+                Ok(Vec::new())
+            }
+            Primitive::PutEol => {
+                writeln!(self.output).map_err(io)?;
+                Ok(Vec::new())
+            }
+            _ => self.error(id, "not a routine"),
+        }
+    }
+}
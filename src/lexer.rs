@@ -0,0 +1,376 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+use crate::symbol::Symbol;
+use crate::token::{Span, Token, TokenType};
+
+// Lexer sobre un `&str` completo: avanza por offsets de bytes y los lexemas de
+// los tokens son porciones prestadas del texto, sin copias.
+pub struct Lexer<'src> {
+    src: &'src str,
+    pos: usize,
+    base: usize,
+    row: usize,
+    col: usize,
+    lookahead: VecDeque<Token<'src>>,
+    done: bool,
+    comments: bool,
+}
+
+impl<'src> Lexer<'src> {
+    pub fn new(src: &'src str) -> Self {
+        Lexer::at(src, 1, 0)
+    }
+
+    // Lexer para un fragmento que empieza en la fila `row` y en el byte `base`
+    // del archivo original; las posiciones de los tokens son absolutas.
+    pub fn at(src: &'src str, row: usize, base: usize) -> Self {
+        Lexer {
+            src,
+            pos: 0,
+            base,
+            row,
+            col: 1,
+            lookahead: VecDeque::new(),
+            done: false,
+            comments: false,
+        }
+    }
+
+    // Devuelve los comentarios `!` como tokens Comment en lugar de saltearlos,
+    // p. ej. para resaltar el texto; el parser no los acepta.
+    pub fn with_comments(mut self) -> Self {
+        self.comments = true;
+        self
+    }
+
+    pub fn next_token(&mut self) -> Token<'src> {
+        match self.lookahead.pop_front() {
+            Some(tok) => tok,
+            None => self.scan_token(),
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<&Token<'src>> {
+        self.peek_nth(0)
+    }
+
+    // Mira `k` tokens hacia adelante sin consumirlos (`peek_nth(0)` es el
+    // siguiente). Devuelve `None` si el flujo termina antes.
+    pub fn peek_nth(&mut self, k: usize) -> Option<&Token<'src>> {
+        if self.done {
+            return None;
+        }
+        while self.lookahead.len() <= k {
+            if self.lookahead.back().is_some_and(|t| t.token_type == TokenType::EOF) {
+                return None;
+            }
+            let tok = self.scan_token();
+            self.lookahead.push_back(tok);
+        }
+        self.lookahead.get(k)
+    }
+
+    fn curr_char(&self) -> char {
+        match self.src.as_bytes().get(self.pos) {
+            None => '\0',
+            Some(&b) if b.is_ascii() => b as char,
+            Some(_) => self.src[self.pos..].chars().next().unwrap_or('\0'),
+        }
+    }
+
+    fn look_ahead(&self) -> char {
+        let mut chars = self.src[self.pos..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.src.len()
+    }
+
+    fn read_char(&mut self) {
+        let ch = self.curr_char();
+        self.pos += ch.len_utf8();
+        if ch == '\n' {
+            self.row += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while !self.at_end() {
+            let ch = self.curr_char();
+            if ch == '!' && self.comments {
+                break;
+            } else if ch == '!' {
+                while !self.at_end() && self.curr_char() != '\n' {
+                    self.read_char();
+                }
+            } else if ch.is_whitespace() {
+                self.read_char();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn scan_token(&mut self) -> Token<'src> {
+        self.skip_whitespace_and_comments();
+
+        let start = self.pos;
+        let (row, col) = (self.row, self.col);
+
+        if self.at_end() {
+            return self.create_token(TokenType::EOF, start, row, col);
+        }
+
+        let token_type = match self.curr_char() {
+            '{' => TokenType::LeftBrace,
+            '}' => TokenType::RightBrace,
+            '(' => TokenType::LeftParen,
+            ')' => TokenType::RightParen,
+            '[' => TokenType::LeftBracket,
+            ']' => TokenType::RightBracket,
+            ':' => {
+                if self.look_ahead() == '=' {
+                    self.read_char();
+                    TokenType::Assign
+                } else {
+                    TokenType::Colon
+                }
+            }
+            ';' => TokenType::Semicolon,
+            ',' => TokenType::Comma,
+            '.' => TokenType::Period,
+            '=' => TokenType::Equals,
+            '~' => TokenType::Tilde,
+            '!' => return self.read_comment(start, row, col),
+            '\'' => return self.read_character(start, row, col),
+            ch if is_letter(ch) => return self.read_identifier(start, row, col),
+            ch if is_digit(ch) => return self.read_number(start, row, col),
+            ch if is_operator_char(ch) => return self.read_operator(start, row, col),
+            _ => TokenType::Illegal,
+        };
+
+        self.read_char();
+        self.create_token(token_type, start, row, col)
+    }
+
+    fn read_identifier(&mut self, start: usize, row: usize, col: usize) -> Token<'src> {
+        while is_letter(self.curr_char()) || is_digit(self.curr_char()) {
+            self.read_char();
+        }
+
+        let symbol = Symbol::intern(&self.src[start..self.pos]);
+        let token_type = TokenType::keyword(symbol).unwrap_or(TokenType::Identifier);
+
+        Token {
+            token_type,
+            lexeme: Cow::Borrowed(&self.src[start..self.pos]),
+            row,
+            col,
+            span: Span::new(self.base + start, self.base + self.pos),
+            symbol: Some(symbol),
+        }
+    }
+
+    fn read_number(&mut self, start: usize, row: usize, col: usize) -> Token<'src> {
+        while is_digit(self.curr_char()) {
+            self.read_char();
+        }
+        self.create_token(TokenType::IntegerLiteral, start, row, col)
+    }
+
+    fn read_operator(&mut self, start: usize, row: usize, col: usize) -> Token<'src> {
+        while is_operator_char(self.curr_char()) {
+            self.read_char();
+        }
+        self.create_token(TokenType::Operator, start, row, col)
+    }
+
+    // Solo con `with_comments`: el comentario llega hasta el fin de línea, sin incluirlo.
+    fn read_comment(&mut self, start: usize, row: usize, col: usize) -> Token<'src> {
+        while !self.at_end() && self.curr_char() != '\n' {
+            self.read_char();
+        }
+        self.create_token(TokenType::Comment, start, row, col)
+    }
+
+    // Un literal de carácter ocupa exactamente tres posiciones: 'c'. El lexema
+    // es solo el carácter, sin las comillas.
+    fn read_character(&mut self, start: usize, row: usize, col: usize) -> Token<'src> {
+        self.read_char();
+        let char_start = self.pos;
+        if self.at_end() || self.curr_char() == '\n' {
+            return self.create_token(TokenType::Illegal, start, row, col);
+        }
+        self.read_char();
+        let char_end = self.pos;
+        if self.curr_char() != '\'' {
+            return self.create_token(TokenType::Illegal, start, row, col);
+        }
+        self.read_char();
+
+        Token::new(TokenType::CharLiteral, &self.src[char_start..char_end], row, col)
+            .with_span(Span::new(self.base + start, self.base + self.pos))
+    }
+
+    fn create_token(&self, token_type: TokenType, start: usize, row: usize, col: usize) -> Token<'src> {
+        Token::new(token_type, &self.src[start..self.pos], row, col)
+            .with_span(Span::new(self.base + start, self.base + self.pos))
+    }
+}
+
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Token<'src>;
+
+    // Produce los tokens hasta el EOF inclusive y luego termina.
+    fn next(&mut self) -> Option<Token<'src>> {
+        if self.done {
+            return None;
+        }
+        let tok = self.next_token();
+        if tok.token_type == TokenType::EOF {
+            self.done = true;
+        }
+        Some(tok)
+    }
+}
+
+// Lexer incremental sobre cualquier `BufRead`: lee una línea a la vez y la pasa
+// por `Lexer`, así que solo mantiene en memoria la línea actual y los tokens
+// pedidos por adelantado. Ningún token de Triangle cruza un salto de línea (los
+// comentarios `!` terminan en él), así que no hace falta más contexto.
+pub struct StreamLexer<R> {
+    reader: R,
+    buffer: String,
+    row: usize,
+    offset: usize,
+    // Fila y columna del final de lo leído, donde va el EOF.
+    end: (usize, usize),
+    pending: VecDeque<Token<'static>>,
+    exhausted: bool,
+    done: bool,
+    error: Option<io::Error>,
+}
+
+impl<R: BufRead> StreamLexer<R> {
+    pub fn new(reader: R) -> Self {
+        StreamLexer {
+            reader,
+            buffer: String::new(),
+            row: 0,
+            offset: 0,
+            end: (1, 1),
+            pending: VecDeque::new(),
+            exhausted: false,
+            done: false,
+            error: None,
+        }
+    }
+
+    // Error de lectura que detuvo el análisis, si lo hubo. El lexer lo trata como
+    // fin de archivo para que el iterador termine con EOF.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    pub fn next_token(&mut self) -> Token<'static> {
+        self.fill(0);
+        self.pending.pop_front().unwrap_or_else(|| self.eof_token())
+    }
+
+    pub fn peek(&mut self) -> Option<&Token<'static>> {
+        self.peek_nth(0)
+    }
+
+    pub fn peek_nth(&mut self, k: usize) -> Option<&Token<'static>> {
+        if self.done {
+            return None;
+        }
+        self.fill(k);
+        self.pending.get(k)
+    }
+
+    fn fill(&mut self, k: usize) {
+        while self.pending.len() <= k && !self.exhausted {
+            if !self.read_line() {
+                let eof = self.eof_token();
+                self.pending.push_back(eof);
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> bool {
+        self.buffer.clear();
+        let read = match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => {
+                self.exhausted = true;
+                return false;
+            }
+            Ok(read) => read,
+            Err(e) => {
+                self.error = Some(e);
+                self.exhausted = true;
+                return false;
+            }
+        };
+
+        self.row += 1;
+        self.end = match self.buffer.strip_suffix('\n') {
+            Some(_) => (self.row + 1, 1),
+            None => (self.row, self.buffer.chars().count() + 1),
+        };
+        let text = self.buffer.strip_suffix('\n').unwrap_or(&self.buffer);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+
+        let lexer = Lexer::at(text, self.row, self.offset);
+        for tok in lexer.take_while(|t| t.token_type != TokenType::EOF) {
+            self.pending.push_back(tok.into_owned());
+        }
+        self.offset += read;
+        true
+    }
+
+    // El EOF queda justo después del último carácter, como en `Lexer`: tras un
+    // salto de línea final está al comienzo de la fila siguiente.
+    fn eof_token(&self) -> Token<'static> {
+        Token::new(TokenType::EOF, "", self.end.0, self.end.1)
+            .with_span(Span::new(self.offset, self.offset))
+    }
+}
+
+impl<R: BufRead> Iterator for StreamLexer<R> {
+    type Item = Token<'static>;
+
+    fn next(&mut self) -> Option<Token<'static>> {
+        if self.done {
+            return None;
+        }
+        let tok = self.next_token();
+        if tok.token_type == TokenType::EOF {
+            self.done = true;
+        }
+        Some(tok)
+    }
+}
+
+pub(crate) fn is_letter(ch: char) -> bool {
+    ch.is_alphabetic()
+}
+
+fn is_digit(ch: char) -> bool {
+    ch.is_ascii_digit()
+}
+
+fn is_operator_char(ch: char) -> bool {
+    matches!(ch, '+' | '-' | '*' | '/' | '=' | '<' | '>' | '&' | '@' | '%' | '^' | '?' | '\\')
+}
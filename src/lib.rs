@@ -0,0 +1,25 @@
+pub mod ast;
+pub mod cgen;
+pub mod checker;
+pub mod codegen;
+pub mod cst;
+pub mod diagnostic;
+pub mod fold;
+pub mod highlight;
+pub mod identification;
+pub mod incremental;
+pub mod interp;
+pub mod lexer;
+pub mod lint;
+pub mod lsp;
+pub mod machine;
+pub mod parser;
+pub mod stdenv;
+pub mod symbol;
+pub mod tam;
+pub mod tamasm;
+pub mod token;
+pub mod types;
+pub mod wasm;
+pub mod wasmgen;
+pub mod x86gen;
@@ -0,0 +1,350 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::fold::Constants;
+use crate::identification::{Decl, Identification};
+use crate::lexer::Lexer;
+use crate::symbol::Symbol;
+use crate::token::TokenType;
+
+// Reglas del lint. Cada una tiene un identificador estable que se usa en la
+// configuración y en los comentarios `! lint:allow(regla)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    UnusedDeclaration,
+    UnassignedVarParameter,
+    ShadowedName,
+    AssignToConstant,
+    SelfAssignment,
+    ConstantCondition,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 6] = [
+        Rule::UnusedDeclaration,
+        Rule::UnassignedVarParameter,
+        Rule::ShadowedName,
+        Rule::AssignToConstant,
+        Rule::SelfAssignment,
+        Rule::ConstantCondition,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::UnusedDeclaration => "unused-declaration",
+            Rule::UnassignedVarParameter => "unassigned-var-parameter",
+            Rule::ShadowedName => "shadowed-name",
+            Rule::AssignToConstant => "assign-to-constant",
+            Rule::SelfAssignment => "self-assignment",
+            Rule::ConstantCondition => "constant-condition",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.id() == id)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+// Qué hacer con los hallazgos de una regla: ignorarlos, avisar o tratarlos
+// como errores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name {
+            "allow" => Some(Level::Allow),
+            "warn" => Some(Level::Warn),
+            "deny" => Some(Level::Deny),
+            _ => None,
+        }
+    }
+}
+
+// Nivel de cada regla; por defecto todas avisan.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    levels: HashMap<Rule, Level>,
+}
+
+impl Config {
+    pub fn level(&self, rule: Rule) -> Level {
+        self.levels.get(&rule).copied().unwrap_or(Level::Warn)
+    }
+
+    pub fn set(&mut self, rule: Rule, level: Level) {
+        self.levels.insert(rule, level);
+    }
+
+    // Archivo de configuración con una línea `regla = nivel` por regla; las
+    // líneas vacías y las que empiezan con `#` se ignoran.
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((rule, level)) = line.split_once('=') else {
+                return Err(format!("Expected `rule = level` at line {}", i + 1));
+            };
+            let rule = Rule::from_id(rule.trim())
+                .ok_or_else(|| format!("Unknown lint rule \"{}\" at line {}", rule.trim(), i + 1))?;
+            let level = Level::from_name(level.trim()).ok_or_else(|| {
+                format!("Unknown lint level \"{}\" at line {} (expected allow, warn or deny)", level.trim(), i + 1)
+            })?;
+            config.set(rule, level);
+        }
+        Ok(config)
+    }
+}
+
+// Avisos sobre un programa que ya pasó el análisis sintáctico. Tolera errores
+// de identificación y de tipos: los nombres sin resolver se ignoran. Los
+// hallazgos en una línea con `! lint:allow(regla, …)`, o en la línea siguiente
+// a un comentario así que ocupa su propia línea, se descartan.
+pub fn lint(src: &str, ast: &Ast, identification: &Identification, constants: &Constants, config: &Config) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        ast,
+        identification,
+        constants,
+        config,
+        allowed: allowed_lines(src),
+        scopes: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    if let Some(root) = ast.root() {
+        linter.unused();
+        linter.unassigned_var_parameters();
+        linter.scopes.push(HashMap::new());
+        linter.visit(root);
+    }
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|d| (d.row, d.col));
+    diagnostics
+}
+
+// Reglas permitidas en cada fila por los comentarios `! lint:allow(…)`.
+fn allowed_lines(src: &str) -> HashMap<usize, HashSet<Rule>> {
+    let mut allowed: HashMap<usize, HashSet<Rule>> = HashMap::new();
+    let mut last_row = 0;
+    for token in Lexer::new(src).with_comments() {
+        let alone = token.row != last_row;
+        last_row = token.row;
+        if token.token_type != TokenType::Comment {
+            continue;
+        }
+        let Some(rules) = token.lexeme.split_once("lint:allow(").and_then(|(_, rest)| rest.split_once(')')) else {
+            continue;
+        };
+        let rules: Vec<Rule> = rules.0.split(',').filter_map(|id| Rule::from_id(id.trim())).collect();
+        allowed.entry(token.row).or_default().extend(&rules);
+        if alone {
+            allowed.entry(token.row + 1).or_default().extend(&rules);
+        }
+    }
+    allowed
+}
+
+struct Linter<'a> {
+    ast: &'a Ast,
+    identification: &'a Identification,
+    constants: &'a Constants,
+    config: &'a Config,
+    allowed: HashMap<usize, HashSet<Rule>>,
+    // Declaraciones visibles en cada bloque abierto, para detectar nombres ocultos.
+    scopes: Vec<HashMap<Symbol, NodeId>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, rule: Rule, id: NodeId, message: String) {
+        let severity = match self.config.level(rule) {
+            Level::Allow => return,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        if self.allowed.get(&self.ast[id].row).is_some_and(|rules| rules.contains(&rule)) {
+            return;
+        }
+        let mut diagnostic = Diagnostic::warning(self.ast, id, format!("{} [{}]", message, rule));
+        diagnostic.severity = severity;
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn unused(&mut self) {
+        // Un operador cuenta como usado si es candidato de algún uso, sea cual
+        // sea el que elija el chequeo de tipos.
+        let overloads =
+            self.identification.overloads.iter().flat_map(|(&use_id, decls)| decls.iter().map(move |&d| (use_id, d)));
+        let used: HashSet<NodeId> = self
+            .identification
+            .decls
+            .iter()
+            .map(|(&use_id, &decl)| (use_id, decl))
+            .chain(overloads)
+            .filter_map(|(use_id, decl)| match decl {
+                // Una rutina que solo se llama a sí misma no cuenta como usada.
+                Decl::Node(decl) if !self.ast.ancestors(use_id).any(|a| a == decl) => Some(decl),
+                _ => None,
+            })
+            .collect();
+        for id in self.ast.ids() {
+            let (what, name) = match self.ast.kind(id) {
+                ASTNode::Const(name, _) => ("constant", name),
+                ASTNode::Var(name, _) => ("variable", name),
+                ASTNode::Func(name, ..) => ("function", name),
+                ASTNode::Proc(name, ..) => ("procedure", name),
+                _ => continue,
+            };
+            if !used.contains(&id) {
+                self.report(Rule::UnusedDeclaration, id, format!("unused {} \"{}\"", what, name));
+            }
+        }
+    }
+
+    // Parámetros `var` que la rutina nunca asigna ni pasa como `var` a otra
+    // rutina (como `get` o `getint`).
+    fn unassigned_var_parameters(&mut self) {
+        let mut assigned = HashSet::new();
+        for id in self.ast.ids() {
+            let target = match self.ast.kind(id) {
+                ASTNode::Assign(vname, _) | ASTNode::VarActual(vname) => *vname,
+                _ => continue,
+            };
+            if let Some(Decl::Node(decl)) = self.root_decl(target) {
+                assigned.insert(decl);
+            }
+        }
+        for param in self.ast.ids() {
+            if let (ASTNode::VarParam(name, _), false) = (self.ast.kind(param), assigned.contains(&param)) {
+                let message = format!("var parameter \"{}\" is never assigned", name);
+                self.report(Rule::UnassignedVarParameter, param, message);
+            }
+        }
+    }
+
+    // Declaración del identificador en la raíz de un V-name: `a` en `a.x[i]`.
+    fn root_decl(&self, vname: NodeId) -> Option<Decl> {
+        match self.ast.kind(vname) {
+            ASTNode::Identifier(_) => self.identification.decl(vname),
+            ASTNode::Field(record, _) => self.root_decl(*record),
+            ASTNode::Index(array, _) => self.root_decl(*array),
+            _ => None,
+        }
+    }
+
+    fn declare(&mut self, name: Symbol, id: NodeId) {
+        let outer = self.scopes[..self.scopes.len() - 1].iter().rev().find_map(|scope| scope.get(&name).copied());
+        if let Some(outer) = outer {
+            let message = format!(
+                "\"{}\" shadows the declaration at row {}, col {}",
+                name, self.ast[outer].row, self.ast[outer].col
+            );
+            self.report(Rule::ShadowedName, id, message);
+        }
+        self.scopes.last_mut().unwrap().insert(name, id);
+    }
+
+    // Recorre los bloques como la identificación, y además revisa los comandos.
+    fn visit(&mut self, id: NodeId) {
+        match self.ast.kind(id) {
+            ASTNode::Let(declarations, command) | ASTNode::LetExpression(declarations, command) => {
+                self.scopes.push(HashMap::new());
+                self.visit(*declarations);
+                self.visit(*command);
+                self.scopes.pop();
+            }
+            ASTNode::Const(name, child)
+            | ASTNode::Var(name, child)
+            | ASTNode::Type(name, child)
+            | ASTNode::ValueParam(name, child)
+            | ASTNode::VarParam(name, child) => {
+                self.visit(*child);
+                self.declare(*name, id);
+            }
+            // Los parámetros de su firma no son visibles en ningún bloque.
+            ASTNode::ProcParam(name, _) | ASTNode::FuncParam(name, ..) => self.declare(*name, id),
+            ASTNode::Func(name, params, _, body) | ASTNode::Proc(name, params, body) => {
+                self.declare(*name, id);
+                self.scopes.push(HashMap::new());
+                for &param in params {
+                    self.visit(param);
+                }
+                self.visit(*body);
+                self.scopes.pop();
+            }
+            ASTNode::Assign(vname, expr) => {
+                if let Some(Decl::Node(decl)) = self.root_decl(*vname) {
+                    if let ASTNode::Const(name, _) = self.ast.kind(decl) {
+                        self.report(Rule::AssignToConstant, id, format!("assignment to constant \"{}\"", name));
+                    }
+                }
+                if self.same_vname(*vname, self.unparenthesized(*expr)) {
+                    self.report(Rule::SelfAssignment, id, "assignment of a variable to itself".to_string());
+                }
+                self.visit(*expr);
+            }
+            ASTNode::If(cond, _, then_branch, _, else_branch) | ASTNode::IfExpression(cond, then_branch, else_branch) => {
+                self.condition("if", *cond);
+                self.visit(*then_branch);
+                self.visit(*else_branch);
+            }
+            ASTNode::While(cond, body) => {
+                self.condition("while", *cond);
+                self.visit(*body);
+            }
+            _ => {
+                for child in self.ast.children(id) {
+                    self.visit(child);
+                }
+            }
+        }
+    }
+
+    fn condition(&mut self, command: &str, cond: NodeId) {
+        if let Some(value) = self.constants.value(cond) {
+            self.report(Rule::ConstantCondition, cond, format!("{} condition is always {}", command, value));
+        }
+    }
+
+    fn unparenthesized(&self, mut id: NodeId) -> NodeId {
+        while let ASTNode::Expression(inner) = self.ast.kind(id) {
+            id = *inner;
+        }
+        id
+    }
+
+    // Dos V-names que denotan siempre la misma variable: mismo identificador,
+    // mismos campos e índices iguales (constantes o el mismo V-name).
+    fn same_vname(&self, a: NodeId, b: NodeId) -> bool {
+        match (self.ast.kind(a), self.ast.kind(b)) {
+            (ASTNode::Identifier(x), ASTNode::Identifier(y)) => match (self.identification.decl(a), self.identification.decl(b)) {
+                (Some(x), Some(y)) => x == y,
+                _ => x == y,
+            },
+            (ASTNode::Field(x, f), ASTNode::Field(y, g)) => f == g && self.same_vname(*x, *y),
+            (ASTNode::Index(x, i), ASTNode::Index(y, j)) => {
+                let (i, j) = (self.unparenthesized(*i), self.unparenthesized(*j));
+                let same_index = match (self.constants.value(i), self.constants.value(j)) {
+                    (Some(i), Some(j)) => i == j,
+                    _ => self.same_vname(i, j),
+                };
+                same_index && self.same_vname(*x, *y)
+            }
+            _ => false,
+        }
+    }
+}
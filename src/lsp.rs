@@ -0,0 +1,627 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::checker::{self, TypeInfo};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::fold::{self, Constants};
+use crate::identification::{identify, Decl, Identification};
+use crate::lexer::Lexer;
+use crate::parser::SyntaxParser;
+use crate::stdenv::{self, Standard};
+use crate::symbol::{sym, Symbol};
+use crate::token::{Span, Token, TokenType};
+use crate::types::{Param, Signature, Type};
+
+// Servidor LSP sobre stdio para el binario triangle-lsp. Sincroniza documentos
+// completos y vuelve a analizar en cada cambio; las consultas usan el último
+// análisis del texto actual, y la completación también uno anterior si el
+// texto actual no compila.
+pub fn serve(input: impl BufRead, output: impl Write) -> io::Result<i32> {
+    let (mut input, mut output) = (input, output);
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut input)? {
+        for reply in server.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+        if let Some(code) = server.exit_code() {
+            return Ok(code);
+        }
+    }
+    Ok(1)
+}
+
+// Lee un mensaje con su encabezado `Content-Length`; `None` al final de la entrada.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header"));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+    exit: Option<i32>,
+}
+
+struct Document {
+    text: String,
+    // Último análisis sin errores de sintaxis; puede ser de un texto anterior.
+    analysis: Option<Analysis>,
+}
+
+struct Analysis {
+    lines: LineIndex,
+    tokens: Vec<Token<'static>>,
+    ast: Ast,
+    identification: Identification,
+    types: TypeInfo,
+    constants: Constants,
+}
+
+impl Server {
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit
+    }
+
+    // Atiende un mensaje y devuelve las respuestas y notificaciones a enviar.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            if message.is_null() {
+                return vec![error_response(Value::Null, PARSE_ERROR, "invalid JSON")];
+            }
+            return match message.get("id") {
+                Some(id) => vec![error_response(id.clone(), INVALID_REQUEST, "missing method")],
+                None => Vec::new(),
+            };
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let Some(id) = message.get("id").cloned() else {
+            return self.notification(method, &params);
+        };
+        let result = match method {
+            "initialize" => Some(capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Some(Value::Null)
+            }
+            "textDocument/hover" => self.at_position(&params, Analysis::hover),
+            "textDocument/definition" => self.at_position(&params, Analysis::definition),
+            "textDocument/references" => {
+                let include = params["context"]["includeDeclaration"].as_bool().unwrap_or(true);
+                self.at_position(&params, |analysis, uri, offset| analysis.references(uri, offset, include))
+            }
+            "textDocument/documentSymbol" => Some(self.symbols(&params)),
+            "textDocument/completion" => Some(self.completion(&params)),
+            _ => None,
+        };
+        match result {
+            Some(result) => vec![json!({"jsonrpc": "2.0", "id": id, "result": result})],
+            None => vec![error_response(id, METHOD_NOT_FOUND, &format!("unknown method {}", method))],
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+        match method {
+            "exit" => {
+                self.exit = Some(if self.shutdown { 0 } else { 1 });
+                Vec::new()
+            }
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.clone(), Document { text: String::new(), analysis: None });
+                vec![self.update(&uri, text.to_string())]
+            }
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array();
+                let Some(text) = changes.and_then(|c| c.last()).and_then(|c| c["text"].as_str()) else {
+                    return Vec::new();
+                };
+                if !self.documents.contains_key(&uri) {
+                    return Vec::new();
+                }
+                vec![self.update(&uri, text.to_string())]
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                vec![publish(&uri, Vec::new())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // Analiza el nuevo texto y devuelve la notificación con sus diagnósticos.
+    fn update(&mut self, uri: &str, text: String) -> Value {
+        let lines = LineIndex::new(&text);
+        let document = self.documents.get_mut(uri).unwrap();
+        let diagnostics = match SyntaxParser::new(&text).parse_program() {
+            Ok(ast) => {
+                let analysis = Analysis::new(&text, ast);
+                let diagnostics = analysis.diagnostics();
+                document.analysis = Some(analysis);
+                diagnostics
+            }
+            Err(message) => {
+                let (message, start) = split_position(&message, &lines);
+                vec![json!({
+                    "range": {"start": lines.position(start), "end": lines.position(start)},
+                    "severity": 1,
+                    "source": "triangle",
+                    "message": message,
+                })]
+            }
+        };
+        document.text = text;
+        publish(uri, diagnostics)
+    }
+
+    fn current<'a>(&'a self, params: &'a Value) -> Option<(&'a str, &'a Analysis)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let document = self.documents.get(uri)?;
+        let analysis = document.analysis.as_ref().filter(|a| a.lines.text == document.text)?;
+        Some((uri, analysis))
+    }
+
+    fn at_position(&self, params: &Value, query: impl Fn(&Analysis, &str, usize) -> Value) -> Option<Value> {
+        let Some((uri, analysis)) = self.current(params) else {
+            return Some(Value::Null);
+        };
+        let position = &params["position"];
+        let offset = analysis.lines.offset(
+            position["line"].as_u64().unwrap_or(0) as usize,
+            position["character"].as_u64().unwrap_or(0) as usize,
+        );
+        Some(query(analysis, uri, offset))
+    }
+
+    fn symbols(&self, params: &Value) -> Value {
+        match self.current(params) {
+            Some((_, analysis)) => analysis.document_symbols(),
+            None => json!([]),
+        }
+    }
+
+    fn completion(&self, params: &Value) -> Value {
+        let mut items = Vec::new();
+        let mut seen = HashSet::new();
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        if let Some(analysis) = self.documents.get(uri).and_then(|d| d.analysis.as_ref()) {
+            let position = &params["position"];
+            let offset = analysis.lines.offset(
+                position["line"].as_u64().unwrap_or(0) as usize,
+                position["character"].as_u64().unwrap_or(0) as usize,
+            );
+            for decl in analysis.visible(offset) {
+                let name = analysis.decl_name(decl);
+                // Los operadores no se escriben como nombres.
+                if !name.is_operator() && seen.insert(name) {
+                    let kind = match analysis.ast.kind(decl) {
+                        ASTNode::Const(..) => COMPLETION_CONSTANT,
+                        ASTNode::Type(..) => COMPLETION_STRUCT,
+                        ASTNode::Func(..) | ASTNode::Proc(..) => COMPLETION_FUNCTION,
+                        _ => COMPLETION_VARIABLE,
+                    };
+                    items.push(json!({"label": name.as_str(), "kind": kind, "detail": analysis.signature(Decl::Node(decl))}));
+                }
+            }
+        }
+        for name in stdenv::names() {
+            if seen.insert(name) {
+                let kind = match stdenv::lookup(name) {
+                    Some(Standard::Type(_)) => COMPLETION_STRUCT,
+                    Some(Standard::Const(..)) => COMPLETION_CONSTANT,
+                    Some(Standard::Func(..) | Standard::Proc(..)) => COMPLETION_FUNCTION,
+                    _ => continue,
+                };
+                items.push(json!({"label": name.as_str(), "kind": kind, "detail": standard_signature(name)}));
+            }
+        }
+        for keyword in &sym::PREDEFINED[..sym::KEYWORD_COUNT as usize] {
+            items.push(json!({"label": keyword, "kind": COMPLETION_KEYWORD}));
+        }
+        json!({"isIncomplete": false, "items": items})
+    }
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            "textDocumentSync": 1,
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "referencesProvider": true,
+            "documentSymbolProvider": true,
+            "completionProvider": {},
+        },
+        "serverInfo": {"name": "triangle-lsp"},
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "diagnostics": diagnostics},
+    })
+}
+
+// Separa "mensaje at row r, col c" de los errores del parser en el mensaje y
+// el offset de esa posición.
+fn split_position(message: &str, lines: &LineIndex) -> (String, usize) {
+    let parsed = message.rsplit_once(" at row ").and_then(|(text, position)| {
+        let (row, col) = position.split_once(", col ")?;
+        Some((text, row.trim().parse::<usize>().ok()?, col.trim().parse::<usize>().ok()?))
+    });
+    match parsed {
+        Some((text, row, col)) => (text.to_string(), lines.row_col(row, col)),
+        None => (message.to_string(), 0),
+    }
+}
+
+const COMPLETION_FUNCTION: u32 = 3;
+const COMPLETION_VARIABLE: u32 = 6;
+const COMPLETION_KEYWORD: u32 = 14;
+const COMPLETION_CONSTANT: u32 = 21;
+const COMPLETION_STRUCT: u32 = 22;
+
+const SYMBOL_FUNCTION: u32 = 12;
+const SYMBOL_VARIABLE: u32 = 13;
+const SYMBOL_CONSTANT: u32 = 14;
+const SYMBOL_STRUCT: u32 = 23;
+
+impl Analysis {
+    fn new(text: &str, ast: Ast) -> Self {
+        let identification = identify(&ast);
+        let types = checker::check(&ast, &identification);
+        let constants = fold::fold(&ast, &identification, &types);
+        let tokens = Lexer::new(text).map(Token::into_owned).collect();
+        Analysis { lines: LineIndex::new(text), tokens, ast, identification, types, constants }
+    }
+
+    fn diagnostics(&self) -> Vec<Value> {
+        let all = self.identification.diagnostics.iter().chain(&self.types.diagnostics).chain(&self.constants.diagnostics);
+        all.map(|d| self.diagnostic(d)).collect()
+    }
+
+    fn diagnostic(&self, diagnostic: &Diagnostic) -> Value {
+        let severity = match diagnostic.severity {
+            Severity::Error => 1,
+            Severity::Warning => 2,
+        };
+        json!({
+            "range": self.range(diagnostic.span),
+            "severity": severity,
+            "source": "triangle",
+            "message": diagnostic.message,
+        })
+    }
+
+    fn range(&self, span: Span) -> Value {
+        json!({"start": self.lines.position(span.start), "end": self.lines.position(span.end)})
+    }
+
+    fn location(&self, uri: &str, span: Span) -> Value {
+        json!({"uri": uri, "range": self.range(span)})
+    }
+
+    fn decl_name(&self, id: NodeId) -> Symbol {
+        match self.ast.kind(id) {
+            ASTNode::Const(name, _)
+            | ASTNode::Var(name, _)
+            | ASTNode::Type(name, _)
+            | ASTNode::Func(name, ..)
+            | ASTNode::Proc(name, ..)
+            | ASTNode::ValueParam(name, _)
+            | ASTNode::VarParam(name, _)
+            | ASTNode::ProcParam(name, _)
+            | ASTNode::FuncParam(name, ..) => *name,
+            _ => unreachable!(),
+        }
+    }
+
+    fn is_decl(&self, id: NodeId) -> bool {
+        matches!(
+            self.ast.kind(id),
+            ASTNode::Const(..)
+                | ASTNode::Var(..)
+                | ASTNode::Type(..)
+                | ASTNode::Func(..)
+                | ASTNode::Proc(..)
+                | ASTNode::ValueParam(..)
+                | ASTNode::VarParam(..)
+                | ASTNode::ProcParam(..)
+                | ASTNode::FuncParam(..)
+        )
+    }
+
+    // Rango del nombre en una declaración o en un uso.
+    fn name_span(&self, id: NodeId) -> Option<Span> {
+        let span = self.ast.span(id);
+        let name = match self.ast.kind(id) {
+            ASTNode::Identifier(_) | ASTNode::TypeName(_) => return Some(span),
+            ASTNode::Call(name, _) | ASTNode::Unary(name, _) | ASTNode::Operator(name, ..) => *name,
+            _ if self.is_decl(id) => self.decl_name(id),
+            _ => return None,
+        };
+        // En un operador binario el nombre va después del operando izquierdo.
+        let start = match self.ast.kind(id) {
+            ASTNode::Operator(_, left, _) => self.ast.span(*left).end,
+            _ => span.start,
+        };
+        self.tokens
+            .iter()
+            .find(|t| t.span.start >= start && t.span.end <= span.end && t.lexeme == name.as_str())
+            .map(|t| t.span)
+    }
+
+    // Nodo (declaración o uso) cuyo nombre está bajo el cursor.
+    fn named_at(&self, offset: usize) -> Option<NodeId> {
+        // Con el cursor justo después de un nombre también vale ese nombre.
+        let tokens = || self.tokens.iter().filter(|t| t.token_type != TokenType::EOF);
+        let token = tokens().find(|t| t.span.contains(offset)).or_else(|| tokens().find(|t| t.span.end == offset))?;
+        let node = self.ast.node_at(token.span.start)?;
+        self.ast.ancestors(node).find(|&id| self.name_span(id) == Some(token.span))
+    }
+
+    // Declaración de un uso; la de un operador la elige el chequeo de tipos.
+    fn decl_of(&self, id: NodeId) -> Option<Decl> {
+        self.types.operator(id).or_else(|| self.identification.decl(id))
+    }
+
+    fn decl_at(&self, offset: usize) -> Option<Decl> {
+        let id = self.named_at(offset)?;
+        if self.is_decl(id) {
+            Some(Decl::Node(id))
+        } else {
+            self.decl_of(id)
+        }
+    }
+
+    fn hover(&self, _uri: &str, offset: usize) -> Value {
+        let Some(id) = self.named_at(offset) else {
+            return Value::Null;
+        };
+        let decl = if self.is_decl(id) { Some(Decl::Node(id)) } else { self.decl_of(id) };
+        let Some(decl) = decl else {
+            return Value::Null;
+        };
+        json!({
+            "contents": {"kind": "markdown", "value": format!("```triangle\n{}\n```", self.signature(decl))},
+            "range": self.range(self.name_span(id).unwrap()),
+        })
+    }
+
+    fn definition(&self, uri: &str, offset: usize) -> Value {
+        match self.decl_at(offset) {
+            Some(Decl::Node(decl)) => match self.name_span(decl) {
+                Some(span) => self.location(uri, span),
+                None => Value::Null,
+            },
+            _ => Value::Null,
+        }
+    }
+
+    fn references(&self, uri: &str, offset: usize, include_declaration: bool) -> Value {
+        let Some(decl) = self.decl_at(offset) else {
+            return json!([]);
+        };
+        let mut spans = Vec::new();
+        if let (Decl::Node(id), true) = (decl, include_declaration) {
+            spans.extend(self.name_span(id));
+        }
+        let uses = self.ast.ids().filter(|&id| self.decl_of(id) == Some(decl));
+        spans.extend(uses.filter_map(|id| self.name_span(id)));
+        spans.sort_by_key(|s| s.start);
+        Value::Array(spans.into_iter().map(|s| self.location(uri, s)).collect())
+    }
+
+    // Texto que describe una declaración, como se escribiría en Triangle.
+    fn signature(&self, decl: Decl) -> String {
+        let id = match decl {
+            Decl::Standard(name) => return standard_signature(name),
+            Decl::Node(id) => id,
+        };
+        let name = self.decl_name(id);
+        match self.ast.kind(id) {
+            ASTNode::Const(_, expr) => match self.constants.value(*expr) {
+                Some(value) => format!("const {} : {} ~ {}", name, self.types.type_of(id), value),
+                None => format!("const {} : {}", name, self.types.type_of(id)),
+            },
+            ASTNode::Var(..) | ASTNode::VarParam(..) => format!("var {} : {}", name, self.types.type_of(id)),
+            ASTNode::ValueParam(..) => format!("{} : {}", name, self.types.type_of(id)),
+            ASTNode::ProcParam(..) | ASTNode::FuncParam(..) => {
+                let keyword = if matches!(self.ast.kind(id), ASTNode::FuncParam(..)) { "func" } else { "proc" };
+                match self.types.signatures.get(&id) {
+                    Some(signature) => format!("{} {} {}", keyword, name, signature),
+                    None => format!("{} {}", keyword, name),
+                }
+            }
+            ASTNode::Type(_, denoter) => format!("type {} ~ {}", name, self.types.type_of(*denoter)),
+            ASTNode::Func(_, params, ..) | ASTNode::Proc(_, params, ..) => {
+                let params: Vec<String> = params
+                    .iter()
+                    .map(|&p| self.signature(Decl::Node(p)))
+                    .collect();
+                let keyword = if matches!(self.ast.kind(id), ASTNode::Func(..)) { "func" } else { "proc" };
+                let result = self.types.signatures.get(&id).and_then(|s| s.result.as_ref());
+                match result {
+                    Some(result) => format!("{} {} ({}) : {}", keyword, name, params.join(", "), result),
+                    None => format!("{} {} ({})", keyword, name, params.join(", ")),
+                }
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    fn document_symbols(&self) -> Value {
+        match self.ast.root() {
+            Some(root) => Value::Array(self.symbols_in(root)),
+            None => json!([]),
+        }
+    }
+
+    // Símbolos de las declaraciones bajo `id`, con las rutinas como padres de
+    // sus parámetros y declaraciones locales.
+    fn symbols_in(&self, id: NodeId) -> Vec<Value> {
+        let mut symbols = Vec::new();
+        for child in self.ast.children(id) {
+            if !self.is_decl(child) {
+                symbols.extend(self.symbols_in(child));
+                continue;
+            }
+            let kind = match self.ast.kind(child) {
+                ASTNode::Const(..) => SYMBOL_CONSTANT,
+                ASTNode::Type(..) => SYMBOL_STRUCT,
+                ASTNode::Func(..) | ASTNode::Proc(..) => SYMBOL_FUNCTION,
+                _ => SYMBOL_VARIABLE,
+            };
+            let span = self.ast.span(child);
+            let mut symbol = json!({
+                "name": self.decl_name(child).as_str(),
+                "detail": self.signature(Decl::Node(child)),
+                "kind": kind,
+                "range": self.range(span),
+                "selectionRange": self.range(self.name_span(child).unwrap_or(span)),
+            });
+            if kind == SYMBOL_FUNCTION {
+                symbol["children"] = Value::Array(self.symbols_in(child));
+            }
+            symbols.push(symbol);
+        }
+        symbols
+    }
+
+    // Declaraciones visibles en `offset`, de la más interna a la más externa.
+    // Las de un `let` valen desde donde empiezan, así que las que siguen al
+    // cursor todavía no se ven.
+    fn visible(&self, offset: usize) -> Vec<NodeId> {
+        let Some(node) = self.ast.node_at(offset).or(self.ast.root()) else {
+            return Vec::new();
+        };
+        let mut decls = Vec::new();
+        for id in self.ast.ancestors(node) {
+            match self.ast.kind(id) {
+                ASTNode::Let(declarations, _) | ASTNode::LetExpression(declarations, _) => {
+                    let declarations = match self.ast.kind(*declarations) {
+                        ASTNode::Declaration(list) => list.clone(),
+                        _ => vec![*declarations],
+                    };
+                    decls.extend(
+                        declarations
+                            .into_iter()
+                            .rev()
+                            .filter(|&d| self.is_decl(d) && self.ast.span(d).start <= offset),
+                    );
+                }
+                ASTNode::Func(_, params, ..) | ASTNode::Proc(_, params, ..) => {
+                    decls.extend(params.iter().rev());
+                }
+                _ => {}
+            }
+        }
+        decls
+    }
+}
+
+fn standard_signature(name: Symbol) -> String {
+    let params = |signature: &Signature| {
+        signature.params.iter().map(Param::to_string).collect::<Vec<_>>().join(", ")
+    };
+    match stdenv::lookup(name) {
+        Some(Standard::Type(ty)) => format!("type {} ~ {}", name, ty),
+        Some(Standard::Const(ty, value)) if ty == Type::Bool => format!("const {} : {} ~ {}", name, ty, value != 0),
+        Some(Standard::Const(ty, value)) => format!("const {} : {} ~ {}", name, ty, value),
+        Some(Standard::Func(signature, _)) => {
+            format!("func {} ({}) : {}", name, params(&signature), signature.result.as_ref().unwrap())
+        }
+        Some(Standard::Proc(signature, _)) => format!("proc {} ({})", name, params(&signature)),
+        Some(Standard::Unary(operand, result, _)) => format!("operator {} ({}) : {}", name, operand, result),
+        Some(Standard::Binary(left, right, result, _)) => {
+            format!("operator {} ({}, {}) : {}", name, left, right, result)
+        }
+        Some(Standard::Equality(_)) => format!("operator {} (T, T) : Boolean", name),
+        None => name.to_string(),
+    }
+}
+
+// Conversión entre offsets de bytes del texto y posiciones LSP (línea desde
+// 0 y columna en unidades UTF-16).
+struct LineIndex {
+    text: String,
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { text: text.to_string(), starts }
+    }
+
+    fn position(&self, offset: usize) -> Value {
+        let offset = offset.min(self.text.len());
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let character = self.text[self.starts[line]..offset].encode_utf16().count();
+        json!({"line": line, "character": character})
+    }
+
+    fn offset(&self, line: usize, character: usize) -> usize {
+        let Some(&start) = self.starts.get(line) else {
+            return self.text.len();
+        };
+        let mut units = 0;
+        for (i, c) in self.text[start..].char_indices() {
+            if units >= character || c == '\n' {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    // Offset de una fila y columna (desde 1, en caracteres) como las de los
+    // mensajes del parser.
+    fn row_col(&self, row: usize, col: usize) -> usize {
+        let Some(&start) = self.starts.get(row.saturating_sub(1)) else {
+            return self.text.len();
+        };
+        self.text[start..].char_indices().nth(col.saturating_sub(1)).map_or(self.text.len(), |(i, _)| start + i)
+    }
+}
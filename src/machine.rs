@@ -0,0 +1,365 @@
+use std::io::{self, BufRead, Write};
+
+use crate::stdenv::{self, MAXINT};
+use crate::tam::{ObjectFile, Op, PrimitiveRoutine, Reg, LINK_DATA, PRIMITIVES};
+
+// Palabras de la memoria de datos: la pila crece desde SB = 0 hacia arriba y
+// el heap desde HB = MEMORY hacia abajo.
+pub const MEMORY: usize = 1 << 16;
+
+// Máximo de llamadas anidadas, el mismo límite que el intérprete y los demás
+// backends.
+const MAX_DEPTH: usize = 2000;
+
+// Intérprete de código objeto TAM. El segmento de código empieza en CB = 0 y
+// las primitivas ocupan PB..PT, justo después del código.
+pub struct Machine<'a, R, W> {
+    object: &'a ObjectFile,
+    data: Vec<i64>,
+    st: usize,
+    ht: usize,
+    lb: usize,
+    cp: usize,
+    depth: usize,
+    input: R,
+    output: W,
+}
+
+pub fn run<R: BufRead, W: Write>(object: &ObjectFile, input: R, output: W) -> Result<(), String> {
+    Machine::new(object, input, output).run()
+}
+
+impl<'a, R: BufRead, W: Write> Machine<'a, R, W> {
+    pub fn new(object: &'a ObjectFile, input: R, output: W) -> Self {
+        Machine { object, data: vec![0; MEMORY], st: 0, ht: MEMORY, lb: 0, cp: 0, depth: 0, input, output }
+    }
+
+    fn pb(&self) -> usize {
+        self.object.code.len()
+    }
+
+    pub fn run(&mut self) -> Result<(), String> {
+        let result = self.execute();
+        self.output.flush().map_err(|e| e.to_string())?;
+        result.map_err(|message| match self.object.position(self.cp) {
+            Some((row, col)) => format!("{} at row {}, col {}", message, row, col),
+            None => format!("{} at code address {}", message, self.cp),
+        })
+    }
+
+    fn execute(&mut self) -> Result<(), String> {
+        loop {
+            let Some(&instr) = self.object.code.get(self.cp) else {
+                return Err("code address out of range".to_string());
+            };
+            let n = instr.n as usize;
+            match instr.op {
+                Op::Load => {
+                    let address = self.address(instr.r, instr.d)?;
+                    for i in 0..n {
+                        let value = self.read(address + i)?;
+                        self.push(value)?;
+                    }
+                }
+                Op::LoadA => {
+                    let address = self.address(instr.r, instr.d)?;
+                    self.push(address as i64)?;
+                }
+                Op::LoadI => {
+                    let address = self.pop_address()?;
+                    for i in 0..n {
+                        let value = self.read(address + i)?;
+                        self.push(value)?;
+                    }
+                }
+                Op::LoadL => self.push(instr.d as i64)?,
+                Op::Store => {
+                    let address = self.address(instr.r, instr.d)?;
+                    self.store(address, n)?;
+                }
+                Op::StoreI => {
+                    let address = self.pop_address()?;
+                    self.store(address, n)?;
+                }
+                Op::Call => {
+                    let static_link = Reg::from_code(instr.n).ok_or("invalid static link register")?;
+                    let static_link = self.register(static_link)?;
+                    let target = self.address(instr.r, instr.d)?;
+                    self.call(static_link, target)?;
+                    continue;
+                }
+                Op::CallI => {
+                    let target = self.pop_address()?;
+                    let static_link = self.pop_address()?;
+                    self.call(static_link, target)?;
+                    continue;
+                }
+                // El resultado tiene que estar encima del marco que se libera.
+                Op::Return => {
+                    let result = self.st.checked_sub(n).ok_or("stack underflow")?;
+                    let frame = self.lb;
+                    let args = words(instr.d)?;
+                    if self.depth == 0 || frame < args || frame > result {
+                        return Err("invalid return".to_string());
+                    }
+                    let cp = usize::try_from(self.read(frame + 2)?).map_err(|_| "invalid return")?;
+                    let lb = usize::try_from(self.read(frame + 1)?).map_err(|_| "invalid return")?;
+                    (self.cp, self.lb) = (cp, lb);
+                    self.data.copy_within(result..self.st, frame - args);
+                    self.st = frame - args + n;
+                    self.depth -= 1;
+                    continue;
+                }
+                Op::Push => {
+                    let words = words(instr.d)?;
+                    if self.st + words > self.ht {
+                        return Err("stack overflow".to_string());
+                    }
+                    self.data[self.st..self.st + words].fill(0);
+                    self.st += words;
+                }
+                Op::Pop => {
+                    let words = words(instr.d)?;
+                    let result = self.st.checked_sub(n).ok_or("stack underflow")?;
+                    let base = result.checked_sub(words).ok_or("stack underflow")?;
+                    self.data.copy_within(result..self.st, base);
+                    self.st -= words;
+                }
+                Op::Jump => {
+                    self.cp = self.address(instr.r, instr.d)?;
+                    continue;
+                }
+                Op::JumpI => {
+                    self.cp = self.pop_address()?;
+                    continue;
+                }
+                Op::JumpIf => {
+                    if self.pop()? == instr.n as i64 {
+                        self.cp = self.address(instr.r, instr.d)?;
+                        continue;
+                    }
+                }
+                Op::Halt => return Ok(()),
+            }
+            self.cp += 1;
+        }
+    }
+
+    // Valor de un registro. L1..L6 se obtienen siguiendo los enlaces
+    // estáticos desde LB.
+    fn register(&self, r: Reg) -> Result<usize, String> {
+        Ok(match r {
+            Reg::CB => 0,
+            Reg::CT | Reg::PB => self.pb(),
+            Reg::PT => self.pb() + PRIMITIVES as usize,
+            Reg::SB => 0,
+            Reg::ST => self.st,
+            Reg::HB => MEMORY,
+            Reg::HT => self.ht,
+            Reg::LB => self.lb,
+            Reg::CP => self.cp,
+            _ => {
+                let mut frame = self.lb;
+                for _ in 0..(r.code() - Reg::LB.code()) {
+                    frame = usize::try_from(self.read(frame)?).map_err(|_| "invalid static link")?;
+                }
+                frame
+            }
+        })
+    }
+
+    fn address(&self, r: Reg, d: i16) -> Result<usize, String> {
+        let address = self.register(r)? as i64 + d as i64;
+        if address < 0 {
+            return Err("negative address".to_string());
+        }
+        Ok(address as usize)
+    }
+
+    fn read(&self, address: usize) -> Result<i64, String> {
+        self.data.get(address).copied().ok_or_else(|| "data address out of range".to_string())
+    }
+
+    fn push(&mut self, value: i64) -> Result<(), String> {
+        if self.st >= self.ht {
+            return Err("stack overflow".to_string());
+        }
+        self.data[self.st] = value;
+        self.st += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<i64, String> {
+        if self.st == 0 {
+            return Err("stack underflow".to_string());
+        }
+        self.st -= 1;
+        Ok(self.data[self.st])
+    }
+
+    // Cantidad de palabras tomada de la pila.
+    fn pop_size(&mut self) -> Result<usize, String> {
+        usize::try_from(self.pop()?).map_err(|_| "negative size".to_string())
+    }
+
+    fn pop_address(&mut self) -> Result<usize, String> {
+        let address = self.pop()?;
+        if address < 0 || address as usize >= MEMORY {
+            return Err("data address out of range".to_string());
+        }
+        Ok(address as usize)
+    }
+
+    // Guarda en `address` las `n` palabras del tope de la pila.
+    fn store(&mut self, address: usize, n: usize) -> Result<(), String> {
+        if self.st < n || address + n > MEMORY {
+            return Err("data address out of range".to_string());
+        }
+        self.st -= n;
+        self.data.copy_within(self.st..self.st + n, address);
+        Ok(())
+    }
+
+    fn call(&mut self, static_link: usize, target: usize) -> Result<(), String> {
+        let pb = self.pb();
+        if target >= pb {
+            let routine = i16::try_from(target - pb)
+                .ok()
+                .and_then(PrimitiveRoutine::from_displacement)
+                .ok_or("invalid primitive routine")?;
+            self.primitive(routine)?;
+            self.cp += 1;
+            return Ok(());
+        }
+        if self.depth == MAX_DEPTH {
+            return Err("stack overflow".to_string());
+        }
+        self.depth += 1;
+        let frame = self.st;
+        self.push(static_link as i64)?;
+        self.push(self.lb as i64)?;
+        self.push(self.cp as i64 + 1)?;
+        debug_assert_eq!(self.st - frame, LINK_DATA as usize);
+        self.lb = frame;
+        self.cp = target;
+        Ok(())
+    }
+
+    fn arithmetic(&mut self, f: impl FnOnce(i64, i64) -> Result<i64, String>) -> Result<(), String> {
+        let right = self.pop()?;
+        let left = self.pop()?;
+        let value = f(left, right)?;
+        if value.abs() > MAXINT {
+            return Err("integer overflow".to_string());
+        }
+        self.push(value)
+    }
+
+    fn primitive(&mut self, routine: PrimitiveRoutine) -> Result<(), String> {
+        let io = |e: io::Error| e.to_string();
+        match routine {
+            PrimitiveRoutine::Id => {}
+            PrimitiveRoutine::Not => {
+                let value = self.pop()?;
+                self.push((value == 0) as i64)?;
+            }
+            PrimitiveRoutine::And => self.arithmetic(|l, r| Ok((l != 0 && r != 0) as i64))?,
+            PrimitiveRoutine::Or => self.arithmetic(|l, r| Ok((l != 0 || r != 0) as i64))?,
+            PrimitiveRoutine::Succ => {
+                self.push(1)?;
+                self.arithmetic(|l, r| Ok(l + r))?;
+            }
+            PrimitiveRoutine::Pred => {
+                self.push(1)?;
+                self.arithmetic(|l, r| Ok(l - r))?;
+            }
+            PrimitiveRoutine::Neg => {
+                let value = self.pop()?;
+                self.push(-value)?;
+            }
+            PrimitiveRoutine::Add => self.arithmetic(|l, r| Ok(l + r))?,
+            PrimitiveRoutine::Sub => self.arithmetic(|l, r| Ok(l - r))?,
+            PrimitiveRoutine::Mult => self.arithmetic(|l, r| Ok(l * r))?,
+            PrimitiveRoutine::Div | PrimitiveRoutine::Mod => self.arithmetic(|l, r| match (r, routine) {
+                (0, _) => Err("division by zero".to_string()),
+                (_, PrimitiveRoutine::Div) => Ok(l / r),
+                _ => Ok(l.rem_euclid(r)),
+            })?,
+            PrimitiveRoutine::Lt => self.arithmetic(|l, r| Ok((l < r) as i64))?,
+            PrimitiveRoutine::Le => self.arithmetic(|l, r| Ok((l <= r) as i64))?,
+            PrimitiveRoutine::Ge => self.arithmetic(|l, r| Ok((l >= r) as i64))?,
+            PrimitiveRoutine::Gt => self.arithmetic(|l, r| Ok((l > r) as i64))?,
+            PrimitiveRoutine::Eq | PrimitiveRoutine::Ne => {
+                let size = self.pop_size()?;
+                let right = self.st.checked_sub(size).ok_or("stack underflow")?;
+                let left = right.checked_sub(size).ok_or("stack underflow")?;
+                let equal = self.data[left..right] == self.data[right..self.st];
+                self.st = left;
+                self.push((equal == (routine == PrimitiveRoutine::Eq)) as i64)?;
+            }
+            PrimitiveRoutine::Eol => {
+                let eol = stdenv::eol(&mut self.input).map_err(io)?;
+                self.push(eol as i64)?;
+            }
+            PrimitiveRoutine::Eof => {
+                let eof = stdenv::eof(&mut self.input).map_err(io)?;
+                self.push(eof as i64)?;
+            }
+            PrimitiveRoutine::Get => {
+                let address = self.pop_address()?;
+                self.data[address] = stdenv::read_char(&mut self.input).map_err(io)?.map_or(-1, |c| c as i64);
+            }
+            PrimitiveRoutine::Put => {
+                let code = self.pop()?;
+                let c = char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+                write!(self.output, "{}", c).map_err(io)?;
+            }
+            PrimitiveRoutine::GetEol => stdenv::skip_line(&mut self.input).map_err(io)?,
+            PrimitiveRoutine::PutEol => writeln!(self.output).map_err(io)?,
+            PrimitiveRoutine::GetInt => {
+                let address = self.pop_address()?;
+                match stdenv::read_int(&mut self.input).map_err(io)? {
+                    Some(value) => self.data[address] = value,
+                    None => return Err("invalid integer in input".to_string()),
+                }
+            }
+            PrimitiveRoutine::PutInt => {
+                let value = self.pop()?;
+                write!(self.output, "{}", value).map_err(io)?;
+            }
+            PrimitiveRoutine::New => {
+                let size = self.pop_size()?;
+                if self.ht - self.st < size {
+                    return Err("heap overflow".to_string());
+                }
+                self.ht -= size;
+                self.data[self.ht..self.ht + size].fill(0);
+                self.push(self.ht as i64)?;
+            }
+            // Sin recolección: el espacio liberado no se reutiliza.
+            PrimitiveRoutine::Dispose => {
+                self.pop_address()?;
+                self.pop()?;
+            }
+            PrimitiveRoutine::Index => {
+                let bound = self.pop()?;
+                let index = *self.data[..self.st].last().ok_or("stack underflow")?;
+                if index < 0 || index >= bound {
+                    return Err("array index out of range".to_string());
+                }
+            }
+            PrimitiveRoutine::Chr => {
+                let code = *self.data[..self.st].last().ok_or("stack underflow")?;
+                if u32::try_from(code).ok().and_then(char::from_u32).is_none() {
+                    return Err("invalid character code".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Cantidad de palabras del operando d de PUSH, POP y RETURN.
+fn words(d: i16) -> Result<usize, String> {
+    usize::try_from(d).map_err(|_| "negative word count".to_string())
+}
@@ -145,15 +145,9 @@ fn main() {
 
     //let salida: Box<dyn Write> = Box::new(stdout());
 
-    if args.len() > 2 && args[2] == "-o" {
-        if args.len() < 4 {
-            eprintln!("Error: falta el nombre del archivo de salida");
-            std::process::exit(1);
-        }
-
-        //let archivo_salida = &args[3];
-        //let file = File::create(archivo_salida).unwrap();
-        //salida = Box::new(file);
+    if args.len() > 2 && args[2] == "-o" && args.len() < 4 {
+        eprintln!("Error: falta el nombre del archivo de salida");
+        std::process::exit(1);
     }
 
     // Construir el árbol de parsing
@@ -0,0 +1,700 @@
+use std::collections::HashMap;
+
+use crate::ast::Ast;
+use crate::cst::{self, Builder, GreenElement, NodeKind, SyntaxNode};
+use crate::lexer::Lexer;
+use crate::token::{Span, Token, TokenType};
+
+// El parser recorre los tokens por índice y arma el árbol concreto, que
+// conserva todo el texto; el AST se deriva de él.
+pub struct SyntaxParser<'src> {
+    tokens: Vec<Token<'src>>,
+    index: usize,
+    // Texto fuente, del que se copian los espacios y comentarios entre tokens.
+    // Sin él (tokens leídos de un .tok) se reconstruyen a partir de fila y columna.
+    src: Option<&'src str>,
+    builder: Builder,
+    // Solo en el análisis incremental: reglas registradas y análisis anterior.
+    memo: Option<Vec<Memo>>,
+    previous: Option<Previous>,
+    by_start: HashMap<(Rule, usize), usize>,
+    reused: usize,
+}
+
+// Reglas cuyo resultado depende solo de los tokens que consumen y del token
+// siguiente, así que se pueden copiar del árbol anterior si esos no cambiaron.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Rule {
+    Command,
+    Declaration,
+}
+
+// Aplicación exitosa de una regla en el análisis anterior.
+#[derive(Debug, Clone)]
+pub(crate) struct Memo {
+    rule: Rule,
+    // Offsets del primer token y del token que cerró la regla.
+    start: usize,
+    lookahead: usize,
+    tokens: usize,
+    // Incluye los espacios y comentarios que siguen al último token.
+    green: GreenElement,
+    // Memos de las subreglas, justo antes de este en la lista.
+    nested: usize,
+}
+
+// Reglas del análisis anterior, posiblemente incompleto si tenía errores, y
+// la zona del texto anterior que se volvió a escanear, que abarca líneas enteras.
+pub(crate) struct Previous {
+    pub memo: Vec<Memo>,
+    pub damage: Span,
+    pub delta: isize,
+}
+
+impl<'src> SyntaxParser<'src> {
+    pub fn new(src: &'src str) -> Self {
+        let mut parser = SyntaxParser::from_tokens(Lexer::new(src).collect());
+        parser.src = Some(src);
+        parser
+    }
+
+    pub fn from_tokens(mut tokens: Vec<Token<'src>>) -> Self {
+        if tokens.last().is_none_or(|t| t.token_type != TokenType::EOF) {
+            let (row, col, end) = tokens.last().map_or((1, 1, 0), |t| (t.row, t.col + t.lexeme.chars().count(), t.span.end));
+            tokens.push(Token::new(TokenType::EOF, "", row, col).with_span(Span::new(end, end)));
+        }
+        SyntaxParser {
+            tokens,
+            index: 0,
+            src: None,
+            builder: Builder::default(),
+            memo: None,
+            previous: None,
+            by_start: HashMap::new(),
+            reused: 0,
+        }
+    }
+
+    // Parser que registra las reglas aplicadas y reutiliza los subárboles de
+    // `previous` fuera de la zona editada.
+    pub(crate) fn incremental(src: &'src str, tokens: Vec<Token<'src>>, previous: Option<Previous>) -> Self {
+        let mut parser = SyntaxParser::from_tokens(tokens);
+        parser.src = Some(src);
+        parser.memo = Some(Vec::new());
+        if let Some(previous) = &previous {
+            parser.by_start = previous.memo.iter().enumerate().map(|(i, m)| ((m.rule, m.start), i)).collect();
+        }
+        parser.previous = previous;
+        parser
+    }
+
+    // Tokens, reglas registradas (también si el análisis falló) y cantidad de
+    // nodos copiados del árbol anterior.
+    pub(crate) fn into_parts(self) -> (Vec<Token<'src>>, Vec<Memo>, usize) {
+        (self.tokens, self.memo.unwrap_or_default(), self.reused)
+    }
+
+    pub fn current(&self) -> &Token<'src> {
+        &self.tokens[self.index]
+    }
+
+    pub fn tokens(&self) -> &[Token<'src>] {
+        &self.tokens
+    }
+
+    // Texto del token tal como aparece en el fuente.
+    fn text(&self, tok: &Token) -> String {
+        match self.src {
+            Some(src) => src[tok.span.start..tok.span.end].to_string(),
+            None if tok.token_type == TokenType::CharLiteral => format!("'{}'", tok.lexeme),
+            None => tok.lexeme.to_string(),
+        }
+    }
+
+    // Agrega al árbol los espacios y comentarios que preceden al token actual.
+    fn trivia(&mut self) {
+        let next = &self.tokens[self.index];
+        let Some(src) = self.src else {
+            let (row, col) = match self.index.checked_sub(1) {
+                None => (1, 1),
+                Some(i) => {
+                    let prev = &self.tokens[i];
+                    (prev.row, prev.col + self.text(prev).chars().count())
+                }
+            };
+            let gap = if next.row > row {
+                "\n".repeat(next.row - row) + &" ".repeat(next.col.saturating_sub(1))
+            } else {
+                " ".repeat(next.col.saturating_sub(col))
+            };
+            if !gap.is_empty() {
+                self.builder.token(TokenType::Whitespace, gap);
+            }
+            return;
+        };
+        let from = self.index.checked_sub(1).map_or(0, |i| self.tokens[i].span.end);
+        let mut gap = &src[from..next.span.start];
+        while let Some(c) = gap.chars().next() {
+            let (kind, len) = if c == '!' {
+                (TokenType::Comment, gap.find('\n').unwrap_or(gap.len()))
+            } else if c.is_whitespace() {
+                (TokenType::Whitespace, gap.find(|c: char| !c.is_whitespace()).unwrap_or(gap.len()))
+            } else {
+                (TokenType::Illegal, c.len_utf8())
+            };
+            self.builder.token(kind, &gap[..len]);
+            gap = &gap[len..];
+        }
+    }
+
+    fn mark(&self) -> usize {
+        self.builder.checkpoint()
+    }
+
+    // Agrupa bajo un nodo todo lo agregado al árbol desde `mark`.
+    fn finish(&mut self, mark: usize, kind: NodeKind) {
+        self.builder.node(mark, kind);
+    }
+
+    fn advance(&mut self) {
+        if self.index < self.tokens.len() - 1 {
+            let text = self.text(&self.tokens[self.index]);
+            self.builder.token(self.tokens[self.index].token_type, text);
+            self.index += 1;
+            self.trivia();
+        }
+    }
+
+    fn expect_token(&mut self, expected: TokenType) -> Result<(), String> {
+        if self.current().token_type == expected {
+            self.advance();
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected {:?}, but found {:?} at row {}, col {}",
+                expected, self.current().token_type, self.current().row, self.current().col
+            ))
+        }
+    }
+
+    pub fn parse_program(&mut self) -> Result<Ast, String> {
+        self.parse_cst().map(|cst| cst::lower(&cst))
+    }
+
+    // Árbol concreto del programa: imprimirlo reproduce el texto fuente.
+    pub fn parse_cst(&mut self) -> Result<SyntaxNode, String> {
+        self.trivia();
+        self.process_commands()?;
+        if self.current().token_type != TokenType::EOF {
+            return Err(format!(
+                "Unexpected token after the program: {:?} at row {}, col {}",
+                self.current().token_type, self.current().row, self.current().col
+            ));
+        }
+        self.builder.token(TokenType::EOF, "");
+        self.finish(0, NodeKind::Program);
+        Ok(SyntaxNode::new_root(std::mem::take(&mut self.builder).finish()))
+    }
+
+    fn process_commands(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        self.parse_single_command()?;
+        let mut count = 1;
+
+        while self.current().token_type == TokenType::Semicolon {
+            self.advance();
+            self.parse_single_command()?;
+            count += 1;
+        }
+
+        if count > 1 {
+            self.finish(start, NodeKind::Commands);
+        }
+        Ok(())
+    }
+
+    fn memoized(&mut self, rule: Rule, parse: fn(&mut Self) -> Result<(), String>) -> Result<(), String> {
+        if self.reuse(rule) {
+            return Ok(());
+        }
+        let Some(memo) = &self.memo else {
+            return parse(self);
+        };
+        let (index, nested) = (self.index, memo.len());
+        let start = self.current().span.start;
+        parse(self)?;
+        let lookahead = self.current().span.start;
+        let green = self.builder.last().cloned().expect("a rule always builds a node");
+        if let Some(memo) = &mut self.memo {
+            let nested = memo.len() - nested;
+            memo.push(Memo { rule, start, lookahead, tokens: self.index - index, green, nested });
+        }
+        Ok(())
+    }
+
+    // Copia el subárbol que la misma regla produjo en el análisis anterior a
+    // partir del token actual, si ninguno de los tokens que miró cambió.
+    fn reuse(&mut self, rule: Rule) -> bool {
+        let Some(previous) = &self.previous else {
+            return false;
+        };
+        let start = self.tokens[self.index].span.start;
+        let (old_start, delta) = if start < previous.damage.start {
+            (start, 0)
+        } else if start >= previous.damage.end.wrapping_add_signed(previous.delta) {
+            (start.wrapping_add_signed(-previous.delta), previous.delta)
+        } else {
+            return false;
+        };
+        let Some(&i) = self.by_start.get(&(rule, old_start)) else {
+            return false;
+        };
+        let memo = &previous.memo[i];
+        if memo.lookahead >= previous.damage.start && memo.start < previous.damage.end {
+            return false;
+        }
+        self.builder.push(memo.green.clone());
+        if let Some(memos) = &mut self.memo {
+            for m in &previous.memo[i - memo.nested..=i] {
+                memos.push(Memo {
+                    start: m.start.wrapping_add_signed(delta),
+                    lookahead: m.lookahead.wrapping_add_signed(delta),
+                    ..m.clone()
+                });
+            }
+        }
+        self.index += memo.tokens;
+        if let GreenElement::Node(node) = &memo.green {
+            self.reused += node.size();
+        }
+        true
+    }
+
+    fn parse_single_command(&mut self) -> Result<(), String> {
+        self.memoized(Rule::Command, Self::parse_command_rule)
+    }
+
+    fn parse_command_rule(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        let kind = match self.current().token_type {
+            TokenType::Let => {
+                self.advance();
+                self.process_declarations()?;
+                self.expect_token(TokenType::In)?;
+                self.parse_single_command()?;
+                NodeKind::Let
+            }
+            TokenType::If => {
+                self.advance();
+                self.parse_expression()?;
+                self.expect_token(TokenType::Then)?;
+                self.parse_single_command()?;
+                self.expect_token(TokenType::Else)?;
+                self.parse_single_command()?;
+                NodeKind::If
+            }
+            TokenType::While => {
+                self.advance();
+                self.parse_expression()?;
+                self.expect_token(TokenType::Do)?;
+                self.parse_single_command()?;
+                NodeKind::While
+            }
+            TokenType::Begin => {
+                self.advance();
+                self.process_commands()?;
+                self.expect_token(TokenType::End)?;
+                NodeKind::Block
+            }
+            TokenType::Identifier => {
+                self.capture_identifier()?;
+                if self.current().token_type == TokenType::LeftParen {
+                    self.advance();
+                    self.get_actual_parameters()?;
+                    self.expect_token(TokenType::RightParen)?;
+                    self.finish(start, NodeKind::Call);
+                    return Ok(());
+                }
+                self.parse_vname_rest(start)?;
+                if self.current().token_type == TokenType::Assign {
+                    self.advance();
+                    self.parse_expression()?;
+                    NodeKind::Assign
+                } else {
+                    return Err(format!(
+                        "Unexpected token: {:?} at row {}, col {}",
+                        self.current().token_type, self.current().row, self.current().col
+                    ));
+                }
+            }
+            // Comando vacío: no consume tokens, solo mira el que sigue.
+            TokenType::Semicolon | TokenType::End | TokenType::Else | TokenType::In | TokenType::EOF => NodeKind::Empty,
+            _ => {
+                return Err(format!(
+                    "Unexpected command token: {:?} at row {}, col {}",
+                    self.current().token_type, self.current().row, self.current().col
+                ))
+            }
+        };
+        self.finish(start, kind);
+        Ok(())
+    }
+
+    fn get_formal_parameters(&mut self) -> Result<(), String> {
+        if self.current().token_type != TokenType::RightParen {
+            self.parse_formal_parameter()?;
+            while self.current().token_type == TokenType::Comma {
+                self.advance();
+                self.parse_formal_parameter()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_formal_parameter(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        let kind = match self.current().token_type {
+            TokenType::Var => {
+                self.advance();
+                self.capture_identifier()?;
+                self.expect_token(TokenType::Colon)?;
+                self.parse_type_denoter()?;
+                NodeKind::VarParam
+            }
+            TokenType::Proc => {
+                self.advance();
+                self.capture_identifier()?;
+                self.expect_token(TokenType::LeftParen)?;
+                self.get_formal_parameters()?;
+                self.expect_token(TokenType::RightParen)?;
+                NodeKind::ProcParam
+            }
+            TokenType::Func => {
+                self.advance();
+                self.capture_identifier()?;
+                self.expect_token(TokenType::LeftParen)?;
+                self.get_formal_parameters()?;
+                self.expect_token(TokenType::RightParen)?;
+                self.expect_token(TokenType::Colon)?;
+                self.parse_type_denoter()?;
+                NodeKind::FuncParam
+            }
+            _ => {
+                self.capture_identifier()?;
+                self.expect_token(TokenType::Colon)?;
+                self.parse_type_denoter()?;
+                NodeKind::ValueParam
+            }
+        };
+        self.finish(start, kind);
+        Ok(())
+    }
+
+    fn get_actual_parameters(&mut self) -> Result<(), String> {
+        if self.current().token_type != TokenType::RightParen {
+            self.parse_actual_parameter()?;
+            while self.current().token_type == TokenType::Comma {
+                self.advance();
+                self.parse_actual_parameter()?;
+            }
+        }
+        Ok(())
+    }
+
+    // Un argumento es una expresión o bien `var V`, `proc P` o `func F`.
+    fn parse_actual_parameter(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        let kind = match self.current().token_type {
+            TokenType::Var => {
+                self.advance();
+                let vname = self.mark();
+                self.capture_identifier()?;
+                self.parse_vname_rest(vname)?;
+                NodeKind::VarActual
+            }
+            TokenType::Proc => {
+                self.advance();
+                self.capture_identifier()?;
+                NodeKind::ProcActual
+            }
+            TokenType::Func => {
+                self.advance();
+                self.capture_identifier()?;
+                NodeKind::FuncActual
+            }
+            _ => return self.parse_expression(),
+        };
+        self.finish(start, kind);
+        Ok(())
+    }
+
+    fn capture_identifier(&mut self) -> Result<(), String> {
+        if self.current().token_type == TokenType::Identifier {
+            self.advance();
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected identifier, found {:?} at row {}, col {}",
+                self.current().token_type, self.current().row, self.current().col
+            ))
+        }
+    }
+
+    // Una función puede declarar un operador: `func + (a : T, b : T) : T ~ E`.
+    fn capture_routine_name(&mut self) -> Result<(), String> {
+        if matches!(self.current().token_type, TokenType::Operator | TokenType::Equals) {
+            self.advance();
+            Ok(())
+        } else {
+            self.capture_identifier()
+        }
+    }
+
+    fn parse_type_denoter(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        let kind = match self.current().token_type {
+            TokenType::Array => {
+                let (row, col) = (self.current().row, self.current().col);
+                self.advance();
+                let size = self.current().lexeme.parse::<i64>();
+                self.expect_token(TokenType::IntegerLiteral)?;
+                size.map_err(|_| format!("Invalid array size at row {}, col {}", row, col))?;
+                self.expect_token(TokenType::Of)?;
+                self.parse_type_denoter()?;
+                NodeKind::ArrayType
+            }
+            TokenType::Record => {
+                self.advance();
+                self.parse_field_type()?;
+                while self.current().token_type == TokenType::Comma {
+                    self.advance();
+                    self.parse_field_type()?;
+                }
+                self.expect_token(TokenType::End)?;
+                NodeKind::RecordType
+            }
+            _ => {
+                self.capture_identifier()?;
+                NodeKind::TypeName
+            }
+        };
+        self.finish(start, kind);
+        Ok(())
+    }
+
+    fn parse_field_type(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        self.capture_identifier()?;
+        self.expect_token(TokenType::Colon)?;
+        self.parse_type_denoter()?;
+        self.finish(start, NodeKind::FieldType);
+        Ok(())
+    }
+
+    fn parse_field_init(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        self.capture_identifier()?;
+        self.expect_token(TokenType::Tilde)?;
+        self.parse_expression()?;
+        self.finish(start, NodeKind::FieldInit);
+        Ok(())
+    }
+
+    // Resto de un V-name cuyo identificador inicial ya se consumió:
+    // selecciones de campo `.f` e índices `[e]`.
+    fn parse_vname_rest(&mut self, start: usize) -> Result<(), String> {
+        self.finish(start, NodeKind::Identifier);
+        loop {
+            match self.current().token_type {
+                TokenType::Period => {
+                    self.advance();
+                    self.capture_identifier()?;
+                    self.finish(start, NodeKind::Field);
+                }
+                TokenType::LeftBracket => {
+                    self.advance();
+                    self.parse_expression()?;
+                    self.expect_token(TokenType::RightBracket)?;
+                    self.finish(start, NodeKind::Index);
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn process_declarations(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        self.parse_single_declaration()?;
+        let mut count = 1;
+        while self.current().token_type == TokenType::Semicolon {
+            self.advance();
+            self.parse_single_declaration()?;
+            count += 1;
+        }
+        if count > 1 {
+            self.finish(start, NodeKind::Declarations);
+        }
+        Ok(())
+    }
+
+    fn parse_single_declaration(&mut self) -> Result<(), String> {
+        self.memoized(Rule::Declaration, Self::parse_declaration_rule)
+    }
+
+    fn parse_declaration_rule(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        let kind = match self.current().token_type {
+            TokenType::Const => {
+                self.advance();
+                self.capture_identifier()?;
+                self.expect_token(TokenType::Tilde)?;
+                self.parse_expression()?;
+                NodeKind::Const
+            }
+            TokenType::Var => {
+                self.advance();
+                self.capture_identifier()?;
+                self.expect_token(TokenType::Colon)?;
+                self.parse_type_denoter()?;
+                NodeKind::Var
+            }
+            TokenType::Func => {
+                self.advance();
+                self.capture_routine_name()?;
+                self.expect_token(TokenType::LeftParen)?;
+                self.get_formal_parameters()?;
+                self.expect_token(TokenType::RightParen)?;
+                self.expect_token(TokenType::Colon)?;
+                self.parse_type_denoter()?;
+                self.expect_token(TokenType::Tilde)?;
+                self.parse_expression()?;
+                NodeKind::Func
+            }
+            TokenType::Type => {
+                self.advance();
+                self.capture_identifier()?;
+                self.expect_token(TokenType::Tilde)?;
+                self.parse_type_denoter()?;
+                NodeKind::Type
+            }
+            TokenType::Proc => {
+                self.advance();
+                self.capture_identifier()?;
+                self.expect_token(TokenType::LeftParen)?;
+                self.get_formal_parameters()?;
+                self.expect_token(TokenType::RightParen)?;
+                self.expect_token(TokenType::Tilde)?;
+                self.parse_single_command()?;
+                NodeKind::Proc
+            }
+            _ => {
+                return Err(format!(
+                    "Unexpected declaration token: {:?} at row {}, col {}",
+                    self.current().token_type, self.current().row, self.current().col
+                ))
+            }
+        };
+        self.finish(start, kind);
+        Ok(())
+    }
+
+    fn parse_expression(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        self.parse_primary_expression()?;
+        // Los operadores binarios asocian a izquierda y tienen todos la misma precedencia.
+        while matches!(self.current().token_type, TokenType::Operator | TokenType::Equals) {
+            self.advance();
+            self.parse_primary_expression()?;
+            self.finish(start, NodeKind::Binary);
+        }
+        Ok(())
+    }
+
+    fn parse_primary_expression(&mut self) -> Result<(), String> {
+        let start = self.mark();
+        let kind = match self.current().token_type {
+            TokenType::IntegerLiteral => {
+                if self.current().lexeme.parse::<i64>().is_err() {
+                    return Err(format!(
+                        "Integer literal out of range at row {}, col {}",
+                        self.current().row, self.current().col
+                    ));
+                }
+                self.advance();
+                NodeKind::Number
+            }
+            TokenType::CharLiteral => {
+                self.advance();
+                NodeKind::Char
+            }
+            TokenType::Identifier => {
+                self.capture_identifier()?;
+                if self.current().token_type == TokenType::LeftParen {
+                    self.advance();
+                    self.get_actual_parameters()?;
+                    self.expect_token(TokenType::RightParen)?;
+                    NodeKind::Call
+                } else {
+                    return self.parse_vname_rest(start);
+                }
+            }
+            TokenType::Operator => {
+                self.advance();
+                self.parse_primary_expression()?;
+                NodeKind::Unary
+            }
+            TokenType::LeftBracket => {
+                self.advance();
+                self.parse_expression()?;
+                while self.current().token_type == TokenType::Comma {
+                    self.advance();
+                    self.parse_expression()?;
+                }
+                self.expect_token(TokenType::RightBracket)?;
+                NodeKind::ArrayAggregate
+            }
+            TokenType::LeftBrace => {
+                self.advance();
+                self.parse_field_init()?;
+                while self.current().token_type == TokenType::Comma {
+                    self.advance();
+                    self.parse_field_init()?;
+                }
+                self.expect_token(TokenType::RightBrace)?;
+                NodeKind::RecordAggregate
+            }
+            TokenType::LeftParen => {
+                self.advance();
+                self.parse_expression()?;
+                self.expect_token(TokenType::RightParen)?;
+                NodeKind::Paren
+            }
+            // `let D in E` e `if E1 then E2 else E3`: la expresión final se
+            // extiende lo más posible, como en Triangle.
+            TokenType::Let => {
+                self.advance();
+                self.process_declarations()?;
+                self.expect_token(TokenType::In)?;
+                self.parse_expression()?;
+                NodeKind::LetExpression
+            }
+            TokenType::If => {
+                self.advance();
+                self.parse_expression()?;
+                self.expect_token(TokenType::Then)?;
+                self.parse_expression()?;
+                self.expect_token(TokenType::Else)?;
+                self.parse_expression()?;
+                NodeKind::IfExpression
+            }
+            _ => {
+                return Err(format!(
+                    "Unexpected primary expression token: {:?} at row {}, col {}",
+                    self.current().token_type, self.current().row, self.current().col
+                ))
+            }
+        };
+        self.finish(start, kind);
+        Ok(())
+    }
+}
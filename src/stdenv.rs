@@ -0,0 +1,162 @@
+use std::io::{self, BufRead};
+
+use crate::symbol::{sym, Symbol};
+use crate::types::{Param, Signature, Type};
+
+// Entorno estándar de Triangle: tipos, constantes, operadores y rutinas
+// predefinidos. Identificación, chequeo de tipos y ejecución lo consultan por
+// nombre.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Standard {
+    Type(Type),
+    Const(Type, i64),
+    Func(Signature, Primitive),
+    Proc(Signature, Primitive),
+    Unary(Type, Type, Primitive),
+    Binary(Type, Type, Type, Primitive),
+    // `=` y `\=` aceptan dos operandos de cualquier tipo, siempre que sea el mismo.
+    Equality(Primitive),
+}
+
+// Operación que implementa cada rutina u operador predefinido.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Primitive {
+    Not,
+    And,
+    Or,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    Ord,
+    Chr,
+    Eof,
+    Eol,
+    Get,
+    Put,
+    GetInt,
+    PutInt,
+    GetEol,
+    PutEol,
+}
+
+pub const MAXINT: i64 = 32767;
+
+// Nombres declarados en el entorno estándar, en el orden en que se internaron.
+pub fn names() -> impl Iterator<Item = Symbol> {
+    sym::PREDEFINED[sym::KEYWORD_COUNT as usize..].iter().map(|name| Symbol::intern(name))
+}
+
+pub fn lookup(name: Symbol) -> Option<Standard> {
+    let routine = |params: Vec<Param>, result: Option<Type>| Signature { params, result };
+    let standard = match name {
+        sym::INTEGER => Standard::Type(Type::Int),
+        sym::CHAR => Standard::Type(Type::Char),
+        sym::BOOLEAN => Standard::Type(Type::Bool),
+        sym::FALSE => Standard::Const(Type::Bool, 0),
+        sym::TRUE => Standard::Const(Type::Bool, 1),
+        sym::MAXINT => Standard::Const(Type::Int, MAXINT),
+        sym::ORD => Standard::Func(routine(vec![Param::Value(Type::Char)], Some(Type::Int)), Primitive::Ord),
+        sym::CHR => Standard::Func(routine(vec![Param::Value(Type::Int)], Some(Type::Char)), Primitive::Chr),
+        sym::EOF => Standard::Func(routine(vec![], Some(Type::Bool)), Primitive::Eof),
+        sym::EOL => Standard::Func(routine(vec![], Some(Type::Bool)), Primitive::Eol),
+        sym::GET => Standard::Proc(routine(vec![Param::Var(Type::Char)], None), Primitive::Get),
+        sym::PUT => Standard::Proc(routine(vec![Param::Value(Type::Char)], None), Primitive::Put),
+        sym::GETINT => Standard::Proc(routine(vec![Param::Var(Type::Int)], None), Primitive::GetInt),
+        sym::PUTINT => Standard::Proc(routine(vec![Param::Value(Type::Int)], None), Primitive::PutInt),
+        sym::GETEOL => Standard::Proc(routine(vec![], None), Primitive::GetEol),
+        sym::PUTEOL => Standard::Proc(routine(vec![], None), Primitive::PutEol),
+        sym::NOT => Standard::Unary(Type::Bool, Type::Bool, Primitive::Not),
+        sym::AND => Standard::Binary(Type::Bool, Type::Bool, Type::Bool, Primitive::And),
+        sym::OR => Standard::Binary(Type::Bool, Type::Bool, Type::Bool, Primitive::Or),
+        sym::ADD => Standard::Binary(Type::Int, Type::Int, Type::Int, Primitive::Add),
+        sym::SUB => Standard::Binary(Type::Int, Type::Int, Type::Int, Primitive::Sub),
+        sym::MUL => Standard::Binary(Type::Int, Type::Int, Type::Int, Primitive::Mul),
+        sym::DIV => Standard::Binary(Type::Int, Type::Int, Type::Int, Primitive::Div),
+        sym::MOD => Standard::Binary(Type::Int, Type::Int, Type::Int, Primitive::Mod),
+        sym::LT => Standard::Binary(Type::Int, Type::Int, Type::Bool, Primitive::Lt),
+        sym::LE => Standard::Binary(Type::Int, Type::Int, Type::Bool, Primitive::Le),
+        sym::GT => Standard::Binary(Type::Int, Type::Int, Type::Bool, Primitive::Gt),
+        sym::GE => Standard::Binary(Type::Int, Type::Int, Type::Bool, Primitive::Ge),
+        sym::EQ => Standard::Equality(Primitive::Eq),
+        sym::NE => Standard::Equality(Primitive::Ne),
+        _ => return None,
+    };
+    Some(standard)
+}
+
+// Entrada de las rutinas predefinidas, compartida por el intérprete y la
+// máquina TAM. `get` decodifica UTF-8, como escribe `put`; una secuencia
+// inválida da U+FFFD.
+pub fn eof(input: &mut impl BufRead) -> io::Result<bool> {
+    Ok(input.fill_buf()?.is_empty())
+}
+
+pub fn eol(input: &mut impl BufRead) -> io::Result<bool> {
+    Ok(input.fill_buf()?.first() == Some(&b'\n'))
+}
+
+fn peek(input: &mut impl BufRead) -> io::Result<Option<u8>> {
+    Ok(input.fill_buf()?.first().copied())
+}
+
+pub fn read_char(input: &mut impl BufRead) -> io::Result<Option<char>> {
+    let Some(lead) = peek(input)? else {
+        return Ok(None);
+    };
+    input.consume(1);
+    // Bytes de continuación que siguen al primero y menor código sin forma más corta.
+    let (len, min) = match lead {
+        0x00..=0x7F => return Ok(Some(lead as char)),
+        0xC0..=0xDF => (1, 0x80),
+        0xE0..=0xEF => (2, 0x800),
+        0xF0..=0xF7 => (3, 0x10000),
+        _ => return Ok(Some(char::REPLACEMENT_CHARACTER)),
+    };
+    let mut code = u32::from(lead & (0x3F >> len));
+    for _ in 0..len {
+        match peek(input)? {
+            Some(byte) if byte & 0xC0 == 0x80 => {
+                input.consume(1);
+                code = code << 6 | u32::from(byte & 0x3F);
+            }
+            _ => return Ok(Some(char::REPLACEMENT_CHARACTER)),
+        }
+    }
+    Ok(Some(char::from_u32(code).filter(|_| code >= min).unwrap_or(char::REPLACEMENT_CHARACTER)))
+}
+
+// Consume hasta el siguiente fin de línea inclusive.
+pub fn skip_line(input: &mut impl BufRead) -> io::Result<()> {
+    while let Some(c) = read_char(input)? {
+        if c == '\n' {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Lee un entero como `getint` de TAM: salta espacios y acepta un signo.
+// Devuelve `None` si no hay un entero válido dentro del rango de Integer.
+pub fn read_int(input: &mut impl BufRead) -> io::Result<Option<i64>> {
+    while matches!(peek(input)?, Some(b) if b.is_ascii_whitespace()) {
+        input.consume(1);
+    }
+    let mut text = String::new();
+    if let Some(sign @ (b'-' | b'+')) = peek(input)? {
+        text.push(sign as char);
+        input.consume(1);
+    }
+    while let Some(b) = peek(input)?.filter(u8::is_ascii_digit) {
+        text.push(b as char);
+        input.consume(1);
+    }
+    Ok(text.parse::<i64>().ok().filter(|value| value.abs() <= MAXINT))
+}
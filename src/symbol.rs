@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+use crate::lexer;
+
+// Nombre internado: comparar, copiar o usar como clave de un `HashMap` cuesta
+// lo mismo que un u32.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn intern(name: &str) -> Symbol {
+        INTERNER.intern(name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        INTERNER.resolve(self)
+    }
+
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_keyword(self) -> bool {
+        self.0 < sym::KEYWORD_COUNT
+    }
+
+    // Los operadores, a diferencia de los identificadores, no empiezan con
+    // letra, con la misma regla del lexer.
+    pub fn is_operator(self) -> bool {
+        !self.as_str().starts_with(lexer::is_letter)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+// El intérprete de nombres es un `static`: los textos viven en su tabla, que
+// solo crece, así que `as_str` puede devolver `&'static str` y leerlos sin
+// tomar el candado, que solo protege el alta de nombres nuevos.
+static INTERNER: Interner = Interner::new();
+
+struct Interner {
+    names: Names,
+    map: OnceLock<Mutex<HashMap<&'static str, Symbol>>>,
+}
+
+impl Interner {
+    const fn new() -> Self {
+        Interner { names: Names::new(), map: OnceLock::new() }
+    }
+
+    // Al crear la tabla se internan los símbolos de `sym`, así sus números
+    // son los de las constantes.
+    fn map(&'static self) -> &'static Mutex<HashMap<&'static str, Symbol>> {
+        self.map.get_or_init(|| {
+            let mut map = HashMap::new();
+            for (index, name) in sym::PREDEFINED.iter().enumerate() {
+                map.insert(self.names.push(index, name), Symbol(index as u32));
+            }
+            Mutex::new(map)
+        })
+    }
+
+    fn intern(&'static self, name: &str) -> Symbol {
+        let mut map = self.map().lock().unwrap();
+        if let Some(&sym) = map.get(name) {
+            return sym;
+        }
+        let index = map.len();
+        let sym = Symbol(index as u32);
+        map.insert(self.names.push(index, name), sym);
+        sym
+    }
+
+    fn resolve(&'static self, sym: Symbol) -> &'static str {
+        self.map();
+        self.names.get(sym.0 as usize).expect("symbol not interned")
+    }
+}
+
+// Tabla de textos por número de símbolo, en tramos que duplican su tamaño y
+// nunca se mueven. Cada casilla se escribe una sola vez, con el candado del
+// intérprete de nombres tomado.
+const FIRST_BUCKET: usize = 64;
+const BUCKETS: usize = 27;
+
+type Bucket = Box<[OnceLock<Box<str>>]>;
+
+struct Names {
+    buckets: [OnceLock<Bucket>; BUCKETS],
+}
+
+impl Names {
+    const fn new() -> Self {
+        Names { buckets: [const { OnceLock::new() }; BUCKETS] }
+    }
+
+    // Tramo y posición dentro del tramo del símbolo `index`.
+    fn slot(index: usize) -> (usize, usize) {
+        let bucket = (index / FIRST_BUCKET + 1).ilog2() as usize;
+        (bucket, index - FIRST_BUCKET * ((1 << bucket) - 1))
+    }
+
+    fn push(&'static self, index: usize, name: &str) -> &'static str {
+        let (bucket, offset) = Names::slot(index);
+        let slots = self.buckets[bucket].get_or_init(|| (0..FIRST_BUCKET << bucket).map(|_| OnceLock::new()).collect());
+        slots[offset].get_or_init(|| name.into())
+    }
+
+    fn get(&'static self, index: usize) -> Option<&'static str> {
+        let (bucket, offset) = Names::slot(index);
+        self.buckets.get(bucket)?.get()?.get(offset)?.get().map(|name| &**name)
+    }
+}
+
+macro_rules! predefined {
+    ($($konst:ident = $text:expr),* $(,)?) => {
+        pub(crate) const PREDEFINED: &[&str] = &[$($text),*];
+        predefined!(@consts 0u32; $($konst,)*);
+    };
+    (@consts $n:expr; $konst:ident, $($rest:ident,)*) => {
+        pub const $konst: Symbol = Symbol($n);
+        predefined!(@consts $n + 1; $($rest,)*);
+    };
+    (@consts $n:expr;) => {};
+}
+
+// Símbolos internados de antemano, en este orden, al crear el intérprete de
+// nombres. Las palabras reservadas van primero (ver `Symbol::is_keyword`).
+pub mod sym {
+    use super::Symbol;
+
+    pub(crate) const KEYWORD_COUNT: u32 = 17;
+
+    predefined! {
+        ARRAY = "array",
+        BEGIN = "begin",
+        CONST = "const",
+        DO = "do",
+        ELSE = "else",
+        END = "end",
+        FUNC = "func",
+        IF = "if",
+        IN = "in",
+        LET = "let",
+        OF = "of",
+        PROC = "proc",
+        RECORD = "record",
+        THEN = "then",
+        TYPE = "type",
+        VAR = "var",
+        WHILE = "while",
+
+        INTEGER = "Integer",
+        CHAR = "Char",
+        BOOLEAN = "Boolean",
+        TRUE = "true",
+        FALSE = "false",
+        MAXINT = "maxint",
+        ORD = "ord",
+        CHR = "chr",
+        EOF = "eof",
+        EOL = "eol",
+        GET = "get",
+        PUT = "put",
+        GETINT = "getint",
+        PUTINT = "putint",
+        GETEOL = "geteol",
+        PUTEOL = "puteol",
+
+        NOT = "\\",
+        AND = "/\\",
+        OR = "\\/",
+        ADD = "+",
+        SUB = "-",
+        MUL = "*",
+        DIV = "/",
+        MOD = "//",
+        LT = "<",
+        LE = "<=",
+        GT = ">",
+        GE = ">=",
+        EQ = "=",
+        NE = "\\=",
+    }
+}
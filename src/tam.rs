@@ -0,0 +1,377 @@
+use std::fmt;
+
+// Conjunto de instrucciones de la máquina abstracta de Triangle (TAM), como
+// en Watt y Brown. Cada instrucción ocupa una palabra de 32 bits:
+// op (4 bits), r (4 bits), n (8 bits) y d (16 bits con signo).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    Load,
+    LoadA,
+    LoadI,
+    LoadL,
+    Store,
+    StoreI,
+    Call,
+    CallI,
+    Return,
+    Push,
+    Pop,
+    Jump,
+    JumpI,
+    JumpIf,
+    Halt,
+}
+
+impl Op {
+    const ALL: [Op; 15] = [
+        Op::Load,
+        Op::LoadA,
+        Op::LoadI,
+        Op::LoadL,
+        Op::Store,
+        Op::StoreI,
+        Op::Call,
+        Op::CallI,
+        Op::Return,
+        Op::Push,
+        Op::Pop,
+        Op::Jump,
+        Op::JumpI,
+        Op::JumpIf,
+        Op::Halt,
+    ];
+
+    // Código de operación; el 9 está libre en TAM.
+    pub fn code(self) -> u8 {
+        match self {
+            Op::Load => 0,
+            Op::LoadA => 1,
+            Op::LoadI => 2,
+            Op::LoadL => 3,
+            Op::Store => 4,
+            Op::StoreI => 5,
+            Op::Call => 6,
+            Op::CallI => 7,
+            Op::Return => 8,
+            Op::Push => 10,
+            Op::Pop => 11,
+            Op::Jump => 12,
+            Op::JumpI => 13,
+            Op::JumpIf => 14,
+            Op::Halt => 15,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Op> {
+        Op::ALL.into_iter().find(|op| op.code() == code)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Op::Load => "LOAD",
+            Op::LoadA => "LOADA",
+            Op::LoadI => "LOADI",
+            Op::LoadL => "LOADL",
+            Op::Store => "STORE",
+            Op::StoreI => "STOREI",
+            Op::Call => "CALL",
+            Op::CallI => "CALLI",
+            Op::Return => "RETURN",
+            Op::Push => "PUSH",
+            Op::Pop => "POP",
+            Op::Jump => "JUMP",
+            Op::JumpI => "JUMPI",
+            Op::JumpIf => "JUMPIF",
+            Op::Halt => "HALT",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Op> {
+        Op::ALL.into_iter().find(|op| op.name().eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Reg {
+    CB,
+    CT,
+    PB,
+    PT,
+    SB,
+    ST,
+    HB,
+    HT,
+    LB,
+    L1,
+    L2,
+    L3,
+    L4,
+    L5,
+    L6,
+    CP,
+}
+
+impl Reg {
+    const ALL: [Reg; 16] = [
+        Reg::CB,
+        Reg::CT,
+        Reg::PB,
+        Reg::PT,
+        Reg::SB,
+        Reg::ST,
+        Reg::HB,
+        Reg::HT,
+        Reg::LB,
+        Reg::L1,
+        Reg::L2,
+        Reg::L3,
+        Reg::L4,
+        Reg::L5,
+        Reg::L6,
+        Reg::CP,
+    ];
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_code(code: u8) -> Option<Reg> {
+        Reg::ALL.get(code as usize).copied()
+    }
+
+    pub fn from_name(name: &str) -> Option<Reg> {
+        Reg::ALL.into_iter().find(|r| format!("{:?}", r).eq_ignore_ascii_case(name))
+    }
+
+    // Registro de display para un marco `depth` niveles por fuera del actual.
+    pub fn display(depth: usize) -> Option<Reg> {
+        match depth {
+            0 => Some(Reg::LB),
+            1..=6 => Reg::from_code(Reg::LB.code() + depth as u8),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Rutinas primitivas, llamadas con `CALL d[PB]`. `Index` y `Chr` no están en
+// el TAM original: comprueban que un índice esté dentro de los límites del
+// arreglo y que un entero sea un código de carácter válido.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrimitiveRoutine {
+    Id = 1,
+    Not,
+    And,
+    Or,
+    Succ,
+    Pred,
+    Neg,
+    Add,
+    Sub,
+    Mult,
+    Div,
+    Mod,
+    Lt,
+    Le,
+    Ge,
+    Gt,
+    Eq,
+    Ne,
+    Eol,
+    Eof,
+    Get,
+    Put,
+    GetEol,
+    PutEol,
+    GetInt,
+    PutInt,
+    New,
+    Dispose,
+    Index,
+    Chr,
+}
+
+impl PrimitiveRoutine {
+    const ALL: [PrimitiveRoutine; 30] = [
+        PrimitiveRoutine::Id,
+        PrimitiveRoutine::Not,
+        PrimitiveRoutine::And,
+        PrimitiveRoutine::Or,
+        PrimitiveRoutine::Succ,
+        PrimitiveRoutine::Pred,
+        PrimitiveRoutine::Neg,
+        PrimitiveRoutine::Add,
+        PrimitiveRoutine::Sub,
+        PrimitiveRoutine::Mult,
+        PrimitiveRoutine::Div,
+        PrimitiveRoutine::Mod,
+        PrimitiveRoutine::Lt,
+        PrimitiveRoutine::Le,
+        PrimitiveRoutine::Ge,
+        PrimitiveRoutine::Gt,
+        PrimitiveRoutine::Eq,
+        PrimitiveRoutine::Ne,
+        PrimitiveRoutine::Eol,
+        PrimitiveRoutine::Eof,
+        PrimitiveRoutine::Get,
+        PrimitiveRoutine::Put,
+        PrimitiveRoutine::GetEol,
+        PrimitiveRoutine::PutEol,
+        PrimitiveRoutine::GetInt,
+        PrimitiveRoutine::PutInt,
+        PrimitiveRoutine::New,
+        PrimitiveRoutine::Dispose,
+        PrimitiveRoutine::Index,
+        PrimitiveRoutine::Chr,
+    ];
+
+    pub fn displacement(self) -> i16 {
+        self as i16
+    }
+
+    pub fn from_displacement(d: i16) -> Option<PrimitiveRoutine> {
+        PrimitiveRoutine::ALL.into_iter().find(|p| p.displacement() == d)
+    }
+
+    pub fn name(self) -> String {
+        format!("{:?}", self).to_lowercase()
+    }
+
+    pub fn from_name(name: &str) -> Option<PrimitiveRoutine> {
+        PrimitiveRoutine::ALL.into_iter().find(|p| p.name() == name)
+    }
+}
+
+// Tamaño del segmento de primitivas: PT = PB + PRIMITIVES.
+pub const PRIMITIVES: i16 = 30;
+
+// Palabras de enlace al comienzo de cada marco: enlace estático, enlace
+// dinámico y dirección de retorno.
+pub const LINK_DATA: i16 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub op: Op,
+    pub r: Reg,
+    pub n: u8,
+    pub d: i16,
+}
+
+impl Instruction {
+    pub fn new(op: Op, n: u8, r: Reg, d: i16) -> Self {
+        Instruction { op, r, n, d }
+    }
+
+    pub fn encode(self) -> u32 {
+        ((self.op.code() as u32) << 28) | ((self.r.code() as u32) << 24) | ((self.n as u32) << 16) | (self.d as u16 as u32)
+    }
+
+    pub fn decode(word: u32) -> Result<Instruction, String> {
+        let op = Op::from_code((word >> 28) as u8).ok_or_else(|| format!("invalid opcode {}", word >> 28))?;
+        let r = Reg::from_code(((word >> 24) & 0xf) as u8).unwrap();
+        Ok(Instruction { op, r, n: (word >> 16) as u8, d: word as u16 as i16 })
+    }
+}
+
+// Formato de ensamblador: `LOAD(1) 3[LB]`, `CALL(SB) add`, `LOADL 5`, `HALT`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.op.name();
+        match self.op {
+            Op::Load | Op::LoadI | Op::Store | Op::StoreI | Op::Pop => {
+                write!(f, "{}({})", name, self.n)?;
+                match self.op {
+                    Op::LoadI | Op::StoreI => Ok(()),
+                    Op::Pop => write!(f, " {}", self.d),
+                    _ => write!(f, " {}[{}]", self.d, self.r),
+                }
+            }
+            Op::LoadA | Op::Jump => write!(f, "{} {}[{}]", name, self.d, self.r),
+            Op::JumpIf => write!(f, "{}({}) {}[{}]", name, self.n, self.d, self.r),
+            Op::Call => {
+                let n = Reg::from_code(self.n).map_or(self.n.to_string(), |r| r.to_string());
+                match PrimitiveRoutine::from_displacement(self.d).filter(|_| self.r == Reg::PB) {
+                    Some(p) => write!(f, "{}({}) {}", name, n, p.name()),
+                    None => write!(f, "{}({}) {}[{}]", name, n, self.d, self.r),
+                }
+            }
+            Op::Return => write!(f, "{}({}) {}", name, self.n, self.d),
+            Op::LoadL | Op::Push => write!(f, "{} {}", name, self.d),
+            Op::CallI | Op::JumpI | Op::Halt => f.write_str(name),
+        }
+    }
+}
+
+// Programa objeto: el código más, opcionalmente, la posición en el `.tri` de
+// cada instrucción (fila 0 si no tiene).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectFile {
+    pub code: Vec<Instruction>,
+    pub source_map: Vec<(u32, u32)>,
+}
+
+const MAGIC: &[u8; 4] = b"TAM\x01";
+
+impl ObjectFile {
+    pub fn position(&self, address: usize) -> Option<(u32, u32)> {
+        self.source_map.get(address).copied().filter(|&(row, _)| row > 0)
+    }
+
+    // Formato binario: "TAM\x01", cantidad de instrucciones (u32), las
+    // instrucciones y, si hay mapa de fuentes, una entrada (fila, columna) por
+    // instrucción. Todo en big-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend((self.code.len() as u32).to_be_bytes());
+        for instr in &self.code {
+            bytes.extend(instr.encode().to_be_bytes());
+        }
+        for &(row, col) in &self.source_map {
+            bytes.extend(row.to_be_bytes());
+            bytes.extend(col.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ObjectFile, String> {
+        if bytes.len() < 8 || &bytes[..4] != MAGIC {
+            return Err("not a TAM object file".to_string());
+        }
+        let word = |i: usize| -> Result<u32, String> {
+            bytes
+                .get(i..i + 4)
+                .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+                .ok_or_else(|| "truncated object file".to_string())
+        };
+        let len = word(4)? as usize;
+        let mut object = ObjectFile::default();
+        for i in 0..len {
+            object.code.push(Instruction::decode(word(8 + 4 * i)?)?);
+        }
+        let map = 8 + 4 * len;
+        if bytes.len() > map {
+            for i in 0..len {
+                object.source_map.push((word(map + 8 * i)?, word(map + 8 * i + 4)?));
+            }
+        }
+        Ok(object)
+    }
+
+    // Listado legible: dirección, instrucción y posición en el fuente.
+    pub fn listing(&self) -> String {
+        let mut out = String::new();
+        for (address, instr) in self.code.iter().enumerate() {
+            let line = format!("{:5}:  {}", address, instr);
+            match self.position(address) {
+                Some((row, col)) => out.push_str(&format!("{:<32}; row {}, col {}\n", line, row, col)),
+                None => out.push_str(&format!("{}\n", line)),
+            }
+        }
+        out
+    }
+}
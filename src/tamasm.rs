@@ -0,0 +1,173 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::tam::{Instruction, ObjectFile, Op, PrimitiveRoutine, Reg};
+
+// Ensamblador de TAM. Una instrucción por línea, con etiquetas opcionales y
+// comentarios desde `;`:
+//
+//     L3:  LOAD(1) -1[LB]
+//          CALL(SB) add        ; primitiva, equivale a 8[PB]
+//          JUMPIF(0) L3        ; etiqueta, equivale a 3[CB]
+//          HALT
+//
+// Un comentario `(row R, col C)`, como los que escribe el desensamblador, da la
+// posición de la instrucción en el `.tri` para el mapa de fuentes.
+pub fn assemble(src: &str) -> Result<ObjectFile, String> {
+    let mut lines = Vec::new();
+    let mut labels = HashMap::new();
+    for (i, line) in src.lines().enumerate() {
+        let row = i + 1;
+        let (mut text, comment) = line.split_once(';').unwrap_or((line, ""));
+        while let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            let col = line.find(label).unwrap_or(0) + 1;
+            if !is_label(label) {
+                return Err(format!("invalid label \"{}\" at row {}, col {}", label, row, col));
+            }
+            if labels.insert(label.to_string(), lines.len()).is_some() {
+                return Err(format!("duplicate label \"{}\" at row {}, col {}", label, row, col));
+            }
+            text = rest;
+        }
+        if !text.trim().is_empty() {
+            let col = line.len() - line.trim_start().len() + 1;
+            lines.push((text.trim(), row, col, source_position(comment)));
+        }
+    }
+    let mut object = ObjectFile::default();
+    for (text, row, col, position) in lines {
+        let instr = parse_instruction(text, &labels).map_err(|e| format!("{} at row {}, col {}", e, row, col))?;
+        object.code.push(instr);
+        object.source_map.push(position.unwrap_or((0, 0)));
+    }
+    // Sin ninguna posición el objeto no lleva mapa de fuentes.
+    if object.source_map.iter().all(|&(row, _)| row == 0) {
+        object.source_map.clear();
+    }
+    Ok(object)
+}
+
+// Posición `(row R, col C)` al final de un comentario.
+fn source_position(comment: &str) -> Option<(u32, u32)> {
+    let (_, position) = comment.rsplit_once("(row ")?;
+    let (row, col) = position.trim_end().strip_suffix(')')?.split_once(", col ")?;
+    Some((row.parse().ok()?, col.parse().ok()?))
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_instruction(text: &str, labels: &HashMap<String, usize>) -> Result<Instruction, String> {
+    let (head, operand) = match text.split_once(char::is_whitespace) {
+        Some((head, operand)) => (head, operand.trim()),
+        None => (text, ""),
+    };
+    let (mnemonic, n) = match head.split_once('(') {
+        Some((mnemonic, n)) => (mnemonic, Some(n.strip_suffix(')').ok_or("missing \")\"")?)),
+        None => (head, None),
+    };
+    let op = Op::from_name(mnemonic).ok_or_else(|| format!("unknown instruction \"{}\"", mnemonic))?;
+
+    let count = |n: Option<&str>| -> Result<u8, String> {
+        let n = n.ok_or_else(|| format!("{} needs a count", op.name()))?;
+        n.parse::<u8>().map_err(|_| format!("invalid count \"{}\"", n))
+    };
+    let number = |text: &str| -> Result<i16, String> {
+        text.parse::<i16>().map_err(|_| format!("invalid number \"{}\"", text))
+    };
+    let no_operand = |instr: Instruction| -> Result<Instruction, String> {
+        if operand.is_empty() {
+            Ok(instr)
+        } else {
+            Err(format!("unexpected operand \"{}\"", operand))
+        }
+    };
+
+    match op {
+        Op::Load | Op::Store => {
+            let (r, d) = address(operand, labels)?;
+            Ok(Instruction::new(op, count(n)?, r, d))
+        }
+        Op::LoadA | Op::Jump => {
+            let (r, d) = address(operand, labels)?;
+            Ok(Instruction::new(op, 0, r, d))
+        }
+        Op::JumpIf => {
+            let (r, d) = address(operand, labels)?;
+            Ok(Instruction::new(op, count(n)?, r, d))
+        }
+        Op::Call => {
+            let link = n.ok_or("CALL needs a static link register")?;
+            let link = Reg::from_name(link).ok_or_else(|| format!("unknown register \"{}\"", link))?;
+            let (r, d) = address(operand, labels)?;
+            Ok(Instruction::new(op, link.code(), r, d))
+        }
+        Op::LoadI | Op::StoreI => no_operand(Instruction::new(op, count(n)?, Reg::CB, 0)),
+        Op::Return | Op::Pop => Ok(Instruction::new(op, count(n)?, Reg::CB, number(operand)?)),
+        Op::LoadL | Op::Push => Ok(Instruction::new(op, 0, Reg::CB, number(operand)?)),
+        Op::CallI | Op::JumpI | Op::Halt => no_operand(Instruction::new(op, 0, Reg::CB, 0)),
+    }
+}
+
+// Operando de dirección: `d[R]`, una etiqueta o el nombre de una primitiva.
+fn address(operand: &str, labels: &HashMap<String, usize>) -> Result<(Reg, i16), String> {
+    if operand.is_empty() {
+        return Err("missing operand".to_string());
+    }
+    if let Some((d, r)) = operand.strip_suffix(']').and_then(|s| s.split_once('[')) {
+        let r = Reg::from_name(r.trim()).ok_or_else(|| format!("unknown register \"{}\"", r))?;
+        let d = d.trim().parse::<i16>().map_err(|_| format!("invalid displacement \"{}\"", d))?;
+        return Ok((r, d));
+    }
+    if let Some(&address) = labels.get(operand) {
+        return Ok((Reg::CB, address as i16));
+    }
+    if let Some(routine) = PrimitiveRoutine::from_name(operand) {
+        return Ok((Reg::PB, routine.displacement()));
+    }
+    Err(format!("undefined label \"{}\"", operand))
+}
+
+// Desensamblador: el texto que produce se puede volver a ensamblar. Los
+// destinos de saltos y llamadas en el código llevan etiquetas `L<dirección>`;
+// cada instrucción lleva como comentario su dirección y, si el objeto tiene
+// mapa de fuentes, su posición; con el `.tri` a mano se intercala además la
+// línea del fuente que originó cada grupo de instrucciones.
+pub fn disassemble(object: &ObjectFile, source: Option<&str>) -> String {
+    let targets: BTreeSet<usize> = object
+        .code
+        .iter()
+        .filter(|i| matches!(i.op, Op::Jump | Op::JumpIf | Op::Call) && i.r == Reg::CB)
+        .map(|i| i.d as usize)
+        .filter(|&d| d < object.code.len())
+        .collect();
+    let source_lines: Vec<&str> = source.map(|s| s.lines().collect()).unwrap_or_default();
+
+    let mut out = String::new();
+    let mut last_row = 0;
+    for (address, instr) in object.code.iter().enumerate() {
+        let position = object.position(address);
+        if let Some((row, _)) = position.filter(|&(row, _)| row != last_row) {
+            if let Some(src) = source_lines.get(row as usize - 1) {
+                out.push_str(&format!("; {}: {}\n", row, src.trim()));
+            }
+            last_row = row;
+        }
+        let label = if targets.contains(&address) { format!("L{}:", address) } else { String::new() };
+        let mut text = instr.to_string();
+        if matches!(instr.op, Op::Jump | Op::JumpIf | Op::Call) && instr.r == Reg::CB && targets.contains(&(instr.d as usize)) {
+            let operand = format!("{}[CB]", instr.d);
+            text = format!("{}L{}", text.strip_suffix(&operand).unwrap_or(&text), instr.d);
+        }
+        let mut line = format!("{:<8}{:<28}; {}", label, text, address);
+        if let Some((row, col)) = position {
+            line.push_str(&format!(" (row {}, col {})", row, col));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,105 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use clap::{Arg, ArgMatches, Command};
+use tokenize::machine;
+use tokenize::tam::ObjectFile;
+use tokenize::tamasm;
+
+fn cli() -> Command {
+    Command::new("tam")
+        .about("Máquina abstracta de Triangle")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("run")
+                .about("Execute a TAM object file, reading stdin and writing stdout")
+                .arg(Arg::new("object").required(true)),
+        )
+        .subcommand(
+            Command::new("asm")
+                .about("Assemble TAM assembly text into an object file")
+                .arg(Arg::new("input").required(true))
+                .arg(Arg::new("output").short('o').long("output").help("Object file (default: input with .tam extension)")),
+        )
+        .subcommand(
+            Command::new("disasm")
+                .about("Print a TAM object file as assembly text")
+                .arg(Arg::new("object").required(true))
+                .arg(Arg::new("source").short('s').long("source").help("The .tri file the object was compiled from")),
+        )
+}
+
+fn main() {
+    let matches = cli().get_matches();
+    match matches.subcommand() {
+        Some(("run", sub)) => run(sub),
+        Some(("asm", sub)) => asm(sub),
+        Some(("disasm", sub)) => disasm(sub),
+        _ => unreachable!(),
+    }
+}
+
+fn read_text(path: &str) -> String {
+    match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error al leer {}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn load(path: &str) -> ObjectFile {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Error al leer {}: {}", path, e);
+            process::exit(1);
+        }
+    };
+    match ObjectFile::from_bytes(&bytes) {
+        Ok(object) => object,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(matches: &ArgMatches) {
+    let path = matches.get_one::<String>("object").unwrap();
+    let object = load(path);
+    if let Err(err) = machine::run(&object, io::stdin().lock(), io::stdout().lock()) {
+        eprintln!("{}: Runtime error: {}", path, err);
+        process::exit(1);
+    }
+}
+
+fn asm(matches: &ArgMatches) {
+    let path = matches.get_one::<String>("input").unwrap();
+    let object = match tamasm::assemble(&read_text(path)) {
+        Ok(object) => object,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    };
+    let output = match matches.get_one::<String>("output") {
+        Some(output) => output.clone(),
+        None => Path::new(path).with_extension("tam").to_string_lossy().into_owned(),
+    };
+    if let Err(e) = fs::write(&output, object.to_bytes()) {
+        eprintln!("Error al escribir {}: {}", output, e);
+        process::exit(1);
+    }
+}
+
+fn disasm(matches: &ArgMatches) {
+    let path = matches.get_one::<String>("object").unwrap();
+    let object = load(path);
+    let source = matches.get_one::<String>("source").map(|source| read_text(source));
+    print!("{}", tamasm::disassemble(&object, source.as_deref()));
+}
@@ -0,0 +1,211 @@
+use std::borrow::Cow;
+
+use crate::symbol::{sym, Symbol};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenType {
+    EOF,
+    Illegal,
+    Identifier,
+    IntegerLiteral,
+    CharLiteral,
+    Operator,
+    Array,
+    Begin,
+    Const,
+    Do,
+    Else,
+    End,
+    Func,
+    If,
+    In,
+    Let,
+    Of,
+    Proc,
+    Record,
+    Then,
+    Type,
+    Var,
+    While,
+    Period,
+    Colon,
+    Semicolon,
+    Comma,
+    Equals,
+    Tilde,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    LeftBrace,
+    RightBrace,
+    Assign,
+    Comment,
+    // Solo en el árbol concreto: espacios entre tokens.
+    Whitespace,
+}
+
+impl TokenType {
+    pub fn keyword(symbol: Symbol) -> Option<TokenType> {
+        let token_type = match symbol {
+            sym::ARRAY => TokenType::Array,
+            sym::BEGIN => TokenType::Begin,
+            sym::CONST => TokenType::Const,
+            sym::DO => TokenType::Do,
+            sym::ELSE => TokenType::Else,
+            sym::END => TokenType::End,
+            sym::FUNC => TokenType::Func,
+            sym::IF => TokenType::If,
+            sym::IN => TokenType::In,
+            sym::LET => TokenType::Let,
+            sym::OF => TokenType::Of,
+            sym::PROC => TokenType::Proc,
+            sym::RECORD => TokenType::Record,
+            sym::THEN => TokenType::Then,
+            sym::TYPE => TokenType::Type,
+            sym::VAR => TokenType::Var,
+            sym::WHILE => TokenType::While,
+            _ => return None,
+        };
+        Some(token_type)
+    }
+
+    // Tokens cuyo lexema es un nombre y por eso llevan un `Symbol`.
+    pub fn is_name(&self) -> bool {
+        matches!(self, TokenType::Identifier | TokenType::Operator) || self.is_keyword()
+    }
+
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            TokenType::Array
+                | TokenType::Begin
+                | TokenType::Const
+                | TokenType::Do
+                | TokenType::Else
+                | TokenType::End
+                | TokenType::Func
+                | TokenType::If
+                | TokenType::In
+                | TokenType::Let
+                | TokenType::Of
+                | TokenType::Proc
+                | TokenType::Record
+                | TokenType::Then
+                | TokenType::Type
+                | TokenType::Var
+                | TokenType::While
+        )
+    }
+
+    // Inverso del nombre que usa `{:?}`, para leer archivos .tok
+    pub fn from_name(name: &str) -> Option<TokenType> {
+        let token_type = match name {
+            "EOF" => TokenType::EOF,
+            "Illegal" => TokenType::Illegal,
+            "Identifier" => TokenType::Identifier,
+            "IntegerLiteral" => TokenType::IntegerLiteral,
+            "CharLiteral" => TokenType::CharLiteral,
+            "Operator" => TokenType::Operator,
+            "Array" => TokenType::Array,
+            "Begin" => TokenType::Begin,
+            "Const" => TokenType::Const,
+            "Do" => TokenType::Do,
+            "Else" => TokenType::Else,
+            "End" => TokenType::End,
+            "Func" => TokenType::Func,
+            "If" => TokenType::If,
+            "In" => TokenType::In,
+            "Let" => TokenType::Let,
+            "Of" => TokenType::Of,
+            "Proc" => TokenType::Proc,
+            "Record" => TokenType::Record,
+            "Then" => TokenType::Then,
+            "Type" => TokenType::Type,
+            "Var" => TokenType::Var,
+            "While" => TokenType::While,
+            "Period" => TokenType::Period,
+            "Colon" => TokenType::Colon,
+            "Semicolon" => TokenType::Semicolon,
+            "Comma" => TokenType::Comma,
+            "Equals" => TokenType::Equals,
+            "Tilde" => TokenType::Tilde,
+            "LeftParen" => TokenType::LeftParen,
+            "RightParen" => TokenType::RightParen,
+            "LeftBracket" => TokenType::LeftBracket,
+            "RightBracket" => TokenType::RightBracket,
+            "LeftBrace" => TokenType::LeftBrace,
+            "RightBrace" => TokenType::RightBrace,
+            "Assign" => TokenType::Assign,
+            "Comment" => TokenType::Comment,
+            "Whitespace" => TokenType::Whitespace,
+            _ => return None,
+        };
+        Some(token_type)
+    }
+}
+
+// Rango de bytes [start, end) dentro del texto fuente.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    pub fn to(&self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+// El lexema se toma prestado del texto fuente cuando es posible (`Lexer`) y
+// solo se copia cuando el texto no sobrevive al token (`StreamLexer`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'src> {
+    pub token_type: TokenType,
+    pub lexeme: Cow<'src, str>,
+    pub row: usize,
+    pub col: usize,
+    pub span: Span,
+    pub symbol: Option<Symbol>,
+}
+
+impl<'src> Token<'src> {
+    pub fn new(token_type: TokenType, lexeme: impl Into<Cow<'src, str>>, row: usize, col: usize) -> Self {
+        let lexeme = lexeme.into();
+        let symbol = token_type.is_name().then(|| Symbol::intern(&lexeme));
+        Token { token_type, lexeme, row, col, span: Span::default(), symbol }
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            token_type: self.token_type,
+            lexeme: Cow::Owned(self.lexeme.into_owned()),
+            row: self.row,
+            col: self.col,
+            span: self.span,
+            symbol: self.symbol,
+        }
+    }
+}
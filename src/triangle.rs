@@ -0,0 +1,264 @@
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::process;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use tokenize::ast::Ast;
+use tokenize::cgen;
+use tokenize::checker::{self, TypeInfo};
+use tokenize::codegen;
+use tokenize::diagnostic::Diagnostic;
+use tokenize::fold;
+use tokenize::highlight;
+use tokenize::identification::{identify, Identification};
+use tokenize::interp;
+use tokenize::lint::{self, Level, Rule};
+use tokenize::parser::SyntaxParser;
+use tokenize::{wasmgen, x86gen};
+
+fn cli() -> Command {
+    Command::new("triangle")
+        .about("Compilador de Triangle")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("check")
+                .about("Parse a .tri file and report scope and type errors")
+                .arg(Arg::new("input").required(true)),
+        )
+        .subcommand(
+            Command::new("compile")
+                .about("Compile a .tri file to a TAM object file, C source, WebAssembly or x86-64 assembly")
+                .arg(Arg::new("input").required(true))
+                .arg(
+                    Arg::new("target")
+                        .short('t')
+                        .long("target")
+                        .value_parser(["tam", "c", "wat", "wasm", "x86-64"])
+                        .default_value("tam")
+                        .help("Output format"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Output file (default: input with the target's extension)"),
+                )
+                .arg(
+                    Arg::new("listing")
+                        .short('l')
+                        .long("listing")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the generated code to stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("run")
+                .about("Check a .tri file and interpret it, reading stdin and writing stdout")
+                .arg(Arg::new("input").required(true)),
+        )
+        .subcommand(
+            Command::new("highlight")
+                .about("Print a .tri file with syntax highlighting, as ANSI colours or HTML")
+                .arg(Arg::new("input").required(true))
+                .arg(
+                    Arg::new("html")
+                        .long("html")
+                        .action(ArgAction::SetTrue)
+                        .help("Produce a standalone HTML page instead of terminal colours"),
+                )
+                .arg(Arg::new("output").short('o').long("output").help("Output file (default: stdout)")),
+        )
+        .subcommand(
+            Command::new("lint")
+                .visible_alias("trilint")
+                .about("Report lint warnings for a .tri file")
+                .arg(Arg::new("input").required(true))
+                .arg(Arg::new("config").short('c').long("config").help("File with one `rule = allow|warn|deny` per line"))
+                .arg(lint_level("allow", 'A', "Ignore a rule"))
+                .arg(lint_level("warn", 'W', "Report a rule as a warning"))
+                .arg(lint_level("deny", 'D', "Report a rule as an error")),
+        )
+}
+
+fn lint_level(name: &'static str, short: char, help: &'static str) -> Arg {
+    Arg::new(name)
+        .short(short)
+        .long(name)
+        .value_name("RULE")
+        .action(ArgAction::Append)
+        .value_parser(Rule::ALL.map(Rule::id))
+        .help(help)
+}
+
+fn main() {
+    let matches = cli().get_matches();
+    match matches.subcommand() {
+        Some(("check", sub)) => check(sub),
+        Some(("compile", sub)) => compile(sub),
+        Some(("run", sub)) => run(sub),
+        Some(("highlight", sub)) => highlight(sub),
+        Some(("lint", sub)) => lint(sub),
+        _ => unreachable!(),
+    }
+}
+
+fn input_path(matches: &ArgMatches) -> &str {
+    matches.get_one::<String>("input").unwrap()
+}
+
+fn read_source(path: &str) -> String {
+    match fs::read_to_string(path) {
+        Ok(src) => src,
+        Err(e) => {
+            eprintln!("Error al leer {}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn parse(src: &str, path: &str) -> Ast {
+    match SyntaxParser::new(src).parse_program() {
+        Ok(ast) => ast,
+        Err(err) => {
+            eprintln!("{}: Parse error: {}", path, err);
+            process::exit(1);
+        }
+    }
+}
+
+// Imprime los diagnósticos y devuelve si hubo algún error.
+fn report(path: &str, diagnostics: &[Diagnostic]) -> bool {
+    for diagnostic in diagnostics {
+        eprintln!("{}: {}", path, diagnostic);
+    }
+    diagnostics.iter().any(|d| d.is_error())
+}
+
+// Analiza el programa completo y termina si hay errores de contexto.
+fn analyze(ast: &Ast, path: &str) -> (Identification, TypeInfo) {
+    let identification = identify(ast);
+    let types = checker::check(ast, &identification);
+    let identification_failed = report(path, &identification.diagnostics);
+    if report(path, &types.diagnostics) || identification_failed {
+        process::exit(1);
+    }
+    if report(path, &fold::fold(ast, &identification, &types).diagnostics) {
+        process::exit(1);
+    }
+    (identification, types)
+}
+
+fn check(matches: &ArgMatches) {
+    let path = input_path(matches);
+    let src = read_source(path);
+    let ast = parse(&src, path);
+    analyze(&ast, path);
+}
+
+fn compile(matches: &ArgMatches) {
+    let path = input_path(matches);
+    let src = read_source(path);
+    let ast = parse(&src, path);
+    let (identification, types) = analyze(&ast, path);
+    let target = matches.get_one::<String>("target").unwrap().as_str();
+    let result = match target {
+        "c" => cgen::emit(&ast, &identification, &types).map(|c| (c.clone().into_bytes(), c)),
+        "wat" => wasmgen::emit(&ast, &identification, &types).map(|module| {
+            let wat = module.to_wat();
+            (wat.clone().into_bytes(), wat)
+        }),
+        "wasm" => wasmgen::emit(&ast, &identification, &types)
+            .and_then(|module| Ok((module.to_binary()?, module.to_wat()))),
+        "x86-64" => x86gen::emit(&ast, &identification, &types).map(|s| (s.clone().into_bytes(), s)),
+        _ => codegen::encode(&ast, &identification, &types).map(|object| (object.to_bytes(), object.listing())),
+    };
+    let (bytes, listing) = match result {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("{}: Code generation error: {}", path, err);
+            process::exit(1);
+        }
+    };
+    let output = match matches.get_one::<String>("output") {
+        Some(output) => output.clone(),
+        None => {
+            let extension = if target == "x86-64" { "s" } else { target };
+            Path::new(path).with_extension(extension).to_string_lossy().into_owned()
+        }
+    };
+    if let Err(e) = fs::write(&output, bytes) {
+        eprintln!("Error al escribir {}: {}", output, e);
+        process::exit(1);
+    }
+    if matches.get_flag("listing") {
+        print!("{}", listing);
+    }
+}
+
+fn run(matches: &ArgMatches) {
+    let path = input_path(matches);
+    let src = read_source(path);
+    let ast = parse(&src, path);
+    let (identification, types) = analyze(&ast, path);
+    if let Err(err) = interp::run(&ast, &identification, &types, BufReader::new(io::stdin()), io::stdout()) {
+        eprintln!("{}: Runtime error: {}", path, err);
+        process::exit(1);
+    }
+}
+
+fn highlight(matches: &ArgMatches) {
+    let path = input_path(matches);
+    let src = read_source(path);
+    let highlights = highlight::classify(&src);
+    let out = if matches.get_flag("html") {
+        highlight::to_html(&src, &highlights, path)
+    } else {
+        highlight::to_ansi(&src, &highlights)
+    };
+    match matches.get_one::<String>("output") {
+        Some(output) => {
+            if let Err(e) = fs::write(output, out) {
+                eprintln!("Error al escribir {}: {}", output, e);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", out),
+    }
+}
+
+// Solo reporta los hallazgos del lint; los errores de contexto los reporta `check`.
+fn lint(matches: &ArgMatches) {
+    let path = input_path(matches);
+    let mut config = match matches.get_one::<String>("config") {
+        Some(config_path) => match lint::Config::parse(&read_source(config_path)) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("{}: {}", config_path, err);
+                process::exit(1);
+            }
+        },
+        None => lint::Config::default(),
+    };
+    // Las opciones de la línea de comandos se aplican en el orden en que aparecen.
+    let mut overrides = Vec::new();
+    for (name, level) in [("allow", Level::Allow), ("warn", Level::Warn), ("deny", Level::Deny)] {
+        if let (Some(rules), Some(indices)) = (matches.get_many::<String>(name), matches.indices_of(name)) {
+            overrides.extend(indices.zip(rules).map(|(index, rule)| (index, Rule::from_id(rule).unwrap(), level)));
+        }
+    }
+    overrides.sort_by_key(|&(index, ..)| index);
+    for (_, rule, level) in overrides {
+        config.set(rule, level);
+    }
+
+    let src = read_source(path);
+    let ast = parse(&src, path);
+    let identification = identify(&ast);
+    let types = checker::check(&ast, &identification);
+    let constants = fold::fold(&ast, &identification, &types);
+    if report(path, &lint::lint(&src, &ast, &identification, &constants, &config)) {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,15 @@
+use std::io;
+use std::process;
+
+use tokenize::lsp;
+
+// Servidor del Language Server Protocol para archivos .tri, por stdin/stdout.
+fn main() {
+    match lsp::serve(io::stdin().lock(), io::stdout().lock()) {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("triangle-lsp: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+
+use crate::symbol::Symbol;
+
+// Tipos de Triangle. La equivalencia es estructural; `Error` es compatible con
+// todo para no repetir diagnósticos en cascada.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Int,
+    Char,
+    Bool,
+    Array(usize, Box<Type>),
+    Record(Vec<(Symbol, Type)>),
+    Error,
+}
+
+impl Type {
+    pub fn equivalent(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::Error, _) | (_, Type::Error) => true,
+            (Type::Array(n, a), Type::Array(m, b)) => n == m && a.equivalent(b),
+            (Type::Record(a), Type::Record(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b).all(|((fa, ta), (fb, tb))| fa == fb && ta.equivalent(tb))
+            }
+            _ => self == other,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        *self == Type::Error
+    }
+
+    // Palabras que ocupa un valor del tipo en memoria.
+    pub fn size(&self) -> usize {
+        match self {
+            Type::Array(n, elem) => n * elem.size(),
+            Type::Record(fields) => fields.iter().map(|(_, t)| t.size()).sum(),
+            _ => 1,
+        }
+    }
+
+    // Desplazamiento y tipo de un campo dentro de un registro.
+    pub fn field_offset(&self, name: Symbol) -> Option<(usize, &Type)> {
+        match self {
+            Type::Record(fields) => {
+                let mut offset = 0;
+                for (f, t) in fields {
+                    if *f == name {
+                        return Some((offset, t));
+                    }
+                    offset += t.size();
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn field(&self, name: Symbol) -> Option<&Type> {
+        match self {
+            Type::Record(fields) => fields.iter().find(|(f, _)| *f == name).map(|(_, t)| t),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Int => f.write_str("Integer"),
+            Type::Char => f.write_str("Char"),
+            Type::Bool => f.write_str("Boolean"),
+            Type::Array(size, elem) => write!(f, "array {} of {}", size, elem),
+            Type::Record(fields) => {
+                f.write_str("record ")?;
+                for (i, (name, ty)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{} : {}", name, ty)?;
+                }
+                f.write_str(" end")
+            }
+            Type::Error => f.write_str("<error>"),
+        }
+    }
+}
+
+// Parámetros de una rutina y, si es una función, el tipo del resultado.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub params: Vec<Param>,
+    pub result: Option<Type>,
+}
+
+// Clase de un parámetro formal: por valor, `var` o una rutina (`proc` si la
+// firma no tiene resultado, `func` si lo tiene).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Param {
+    Value(Type),
+    Var(Type),
+    Routine(Signature),
+}
+
+impl Signature {
+    pub fn equivalent(&self, other: &Signature) -> bool {
+        let result = match (&self.result, &other.result) {
+            (Some(a), Some(b)) => a.equivalent(b),
+            (None, None) => true,
+            _ => false,
+        };
+        result && self.params.len() == other.params.len() && self.params.iter().zip(&other.params).all(|(a, b)| a.equivalent(b))
+    }
+}
+
+impl Param {
+    pub fn equivalent(&self, other: &Param) -> bool {
+        match (self, other) {
+            (Param::Value(a), Param::Value(b)) | (Param::Var(a), Param::Var(b)) => a.equivalent(b),
+            (Param::Routine(a), Param::Routine(b)) => a.equivalent(b),
+            _ => false,
+        }
+    }
+
+    // Palabra con que se escribe la clase del parámetro.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Param::Value(_) => "value",
+            Param::Var(_) => "var",
+            Param::Routine(signature) if signature.result.is_some() => "func",
+            Param::Routine(_) => "proc",
+        }
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("(")?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", param)?;
+        }
+        f.write_str(")")?;
+        match &self.result {
+            Some(result) => write!(f, " : {}", result),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Param::Value(ty) => write!(f, "{}", ty),
+            Param::Var(ty) => write!(f, "var {}", ty),
+            Param::Routine(signature) => write!(f, "{} {}", self.kind(), signature),
+        }
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+// Representación mínima de un módulo WebAssembly, con todos los valores i32,
+// que se puede imprimir como texto (WAT) o codificar en binario (.wasm).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockType {
+    Empty,
+    I32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instr {
+    Unreachable,
+    Block(BlockType),
+    Loop(BlockType),
+    If(BlockType),
+    Else,
+    End,
+    Br(u32),
+    BrIf(u32),
+    Return,
+    Call(String),
+    // Llamada por la tabla: cantidad de parámetros y si devuelve valor.
+    CallIndirect(u32, bool),
+    Drop,
+    Select,
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+    I32Load(u32),
+    I32Store(u32),
+    I32Const(i32),
+    I32Eqz,
+    I32Eq,
+    I32Ne,
+    I32LtS,
+    I32GtS,
+    I32LeS,
+    I32GeS,
+    I32Add,
+    I32Sub,
+    I32Mul,
+    I32DivS,
+    I32RemS,
+    I32And,
+    I32Or,
+    MemoryCopy,
+    MemoryFill,
+}
+
+// Función importada del entorno anfitrión.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub module: String,
+    pub name: String,
+    pub params: u32,
+    pub result: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Global {
+    pub name: String,
+    pub init: i32,
+}
+
+// Los parámetros son los primeros locales; `locals` son los demás.
+#[derive(Debug, Clone, Default)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub result: bool,
+    pub locals: Vec<String>,
+    pub body: Vec<Instr>,
+    pub export: Option<String>,
+}
+
+impl Function {
+    pub fn new(name: &str, params: &[&str], result: bool) -> Self {
+        Function {
+            name: name.to_string(),
+            params: params.iter().map(|p| p.to_string()).collect(),
+            result,
+            ..Function::default()
+        }
+    }
+
+    // Agrega un local y devuelve su índice.
+    pub fn local(&mut self, name: &str) -> u32 {
+        self.locals.push(name.to_string());
+        (self.params.len() + self.locals.len() - 1) as u32
+    }
+
+    fn local_name(&self, index: u32) -> &str {
+        let index = index as usize;
+        if index < self.params.len() {
+            &self.params[index]
+        } else {
+            &self.locals[index - self.params.len()]
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Module {
+    pub imports: Vec<Import>,
+    pub globals: Vec<Global>,
+    pub functions: Vec<Function>,
+    // Funciones de la tabla, en orden de índice, para `call_indirect`.
+    pub table: Vec<String>,
+    pub memory_pages: u32,
+}
+
+impl Module {
+    // Índices de función: primero las importadas, después las definidas.
+    fn function_indices(&self) -> HashMap<&str, u32> {
+        let imported = self.imports.iter().map(|i| i.name.as_str());
+        let defined = self.functions.iter().map(|f| f.name.as_str());
+        imported.chain(defined).enumerate().map(|(i, name)| (name, i as u32)).collect()
+    }
+
+    pub fn to_wat(&self) -> String {
+        let mut out = String::from("(module\n");
+        for import in &self.imports {
+            writeln!(
+                out,
+                "  (import \"{}\" \"{}\" (func ${}{}{}))",
+                import.module,
+                import.name,
+                import.name,
+                " (param i32)".repeat(import.params as usize),
+                if import.result { " (result i32)" } else { "" }
+            )
+            .unwrap();
+        }
+        writeln!(out, "  (memory (export \"memory\") {})", self.memory_pages).unwrap();
+        if !self.table.is_empty() {
+            writeln!(out, "  (table {} funcref)", self.table.len()).unwrap();
+            let names: Vec<String> = self.table.iter().map(|name| format!(" ${}", name)).collect();
+            writeln!(out, "  (elem (i32.const 0){})", names.concat()).unwrap();
+        }
+        for global in &self.globals {
+            writeln!(out, "  (global ${} (mut i32) (i32.const {}))", global.name, global.init).unwrap();
+        }
+        for function in &self.functions {
+            write!(out, "\n  (func ${}", function.name).unwrap();
+            if let Some(export) = &function.export {
+                write!(out, " (export \"{}\")", export).unwrap();
+            }
+            for param in &function.params {
+                write!(out, " (param ${} i32)", param).unwrap();
+            }
+            if function.result {
+                out.push_str(" (result i32)");
+            }
+            out.push('\n');
+            for local in &function.locals {
+                writeln!(out, "    (local ${} i32)", local).unwrap();
+            }
+            let mut depth = 2;
+            for instr in &function.body {
+                if matches!(instr, Instr::End | Instr::Else) {
+                    depth -= 1;
+                }
+                writeln!(out, "{}{}", "  ".repeat(depth), self.instr_text(function, instr)).unwrap();
+                if matches!(instr, Instr::Block(_) | Instr::Loop(_) | Instr::If(_) | Instr::Else) {
+                    depth += 1;
+                }
+            }
+            out.push_str("  )\n");
+        }
+        out.push_str(")\n");
+        out
+    }
+
+    fn instr_text(&self, function: &Function, instr: &Instr) -> String {
+        let block = |name: &str, bt: &BlockType| match bt {
+            BlockType::Empty => name.to_string(),
+            BlockType::I32 => format!("{} (result i32)", name),
+        };
+        match instr {
+            Instr::Unreachable => "unreachable".to_string(),
+            Instr::Block(bt) => block("block", bt),
+            Instr::Loop(bt) => block("loop", bt),
+            Instr::If(bt) => block("if", bt),
+            Instr::Else => "else".to_string(),
+            Instr::End => "end".to_string(),
+            Instr::Br(depth) => format!("br {}", depth),
+            Instr::BrIf(depth) => format!("br_if {}", depth),
+            Instr::Return => "return".to_string(),
+            Instr::Call(name) => format!("call ${}", name),
+            Instr::CallIndirect(params, result) => format!(
+                "call_indirect{}{}",
+                " (param i32)".repeat(*params as usize),
+                if *result { " (result i32)" } else { "" }
+            ),
+            Instr::Drop => "drop".to_string(),
+            Instr::Select => "select".to_string(),
+            Instr::LocalGet(i) => format!("local.get ${}", function.local_name(*i)),
+            Instr::LocalSet(i) => format!("local.set ${}", function.local_name(*i)),
+            Instr::LocalTee(i) => format!("local.tee ${}", function.local_name(*i)),
+            Instr::GlobalGet(i) => format!("global.get ${}", self.globals[*i as usize].name),
+            Instr::GlobalSet(i) => format!("global.set ${}", self.globals[*i as usize].name),
+            Instr::I32Load(0) => "i32.load".to_string(),
+            Instr::I32Load(offset) => format!("i32.load offset={}", offset),
+            Instr::I32Store(0) => "i32.store".to_string(),
+            Instr::I32Store(offset) => format!("i32.store offset={}", offset),
+            Instr::I32Const(value) => format!("i32.const {}", value),
+            Instr::I32Eqz => "i32.eqz".to_string(),
+            Instr::I32Eq => "i32.eq".to_string(),
+            Instr::I32Ne => "i32.ne".to_string(),
+            Instr::I32LtS => "i32.lt_s".to_string(),
+            Instr::I32GtS => "i32.gt_s".to_string(),
+            Instr::I32LeS => "i32.le_s".to_string(),
+            Instr::I32GeS => "i32.ge_s".to_string(),
+            Instr::I32Add => "i32.add".to_string(),
+            Instr::I32Sub => "i32.sub".to_string(),
+            Instr::I32Mul => "i32.mul".to_string(),
+            Instr::I32DivS => "i32.div_s".to_string(),
+            Instr::I32RemS => "i32.rem_s".to_string(),
+            Instr::I32And => "i32.and".to_string(),
+            Instr::I32Or => "i32.or".to_string(),
+            Instr::MemoryCopy => "memory.copy".to_string(),
+            Instr::MemoryFill => "memory.fill".to_string(),
+        }
+    }
+
+    pub fn to_binary(&self) -> Result<Vec<u8>, String> {
+        let indices = self.function_indices();
+        let mut types: Vec<(u32, bool)> = Vec::new();
+        let mut type_of = |params: u32, result: bool| match types.iter().position(|&t| t == (params, result)) {
+            Some(i) => i as u32,
+            None => {
+                types.push((params, result));
+                types.len() as u32 - 1
+            }
+        };
+        let import_types: Vec<u32> = self.imports.iter().map(|i| type_of(i.params, i.result)).collect();
+        let function_types: Vec<u32> =
+            self.functions.iter().map(|f| type_of(f.params.len() as u32, f.result)).collect();
+        for instr in self.functions.iter().flat_map(|f| &f.body) {
+            if let Instr::CallIndirect(params, result) = instr {
+                type_of(*params, *result);
+            }
+        }
+
+        let mut out = b"\0asm".to_vec();
+        out.extend(1u32.to_le_bytes());
+
+        let mut section = Vec::new();
+        uleb(&mut section, types.len() as u64);
+        for &(params, result) in &types {
+            section.push(0x60);
+            uleb(&mut section, params as u64);
+            section.extend(std::iter::repeat_n(0x7f, params as usize));
+            uleb(&mut section, result as u64);
+            if result {
+                section.push(0x7f);
+            }
+        }
+        emit_section(&mut out, 1, &section);
+
+        section.clear();
+        uleb(&mut section, self.imports.len() as u64);
+        for (import, &ty) in self.imports.iter().zip(&import_types) {
+            name(&mut section, &import.module);
+            name(&mut section, &import.name);
+            section.push(0x00);
+            uleb(&mut section, ty as u64);
+        }
+        emit_section(&mut out, 2, &section);
+
+        section.clear();
+        uleb(&mut section, function_types.len() as u64);
+        for &ty in &function_types {
+            uleb(&mut section, ty as u64);
+        }
+        emit_section(&mut out, 3, &section);
+
+        if !self.table.is_empty() {
+            section.clear();
+            section.extend([0x01, 0x70, 0x00]);
+            uleb(&mut section, self.table.len() as u64);
+            emit_section(&mut out, 4, &section);
+        }
+
+        section.clear();
+        section.extend([0x01, 0x00]);
+        uleb(&mut section, self.memory_pages as u64);
+        emit_section(&mut out, 5, &section);
+
+        section.clear();
+        uleb(&mut section, self.globals.len() as u64);
+        for global in &self.globals {
+            section.extend([0x7f, 0x01, 0x41]);
+            sleb(&mut section, global.init as i64);
+            section.push(0x0b);
+        }
+        emit_section(&mut out, 6, &section);
+
+        section.clear();
+        let exports: Vec<_> = self.functions.iter().filter(|f| f.export.is_some()).collect();
+        uleb(&mut section, exports.len() as u64 + 1);
+        name(&mut section, "memory");
+        section.extend([0x02, 0x00]);
+        for function in exports {
+            name(&mut section, function.export.as_ref().unwrap());
+            section.push(0x00);
+            uleb(&mut section, indices[function.name.as_str()] as u64);
+        }
+        emit_section(&mut out, 7, &section);
+
+        if !self.table.is_empty() {
+            section.clear();
+            section.extend([0x01, 0x00, 0x41, 0x00, 0x0b]);
+            uleb(&mut section, self.table.len() as u64);
+            for name in &self.table {
+                let index = indices.get(name.as_str()).ok_or_else(|| format!("unknown function ${}", name))?;
+                uleb(&mut section, *index as u64);
+            }
+            emit_section(&mut out, 9, &section);
+        }
+
+        section.clear();
+        uleb(&mut section, self.functions.len() as u64);
+        for function in &self.functions {
+            let mut body = Vec::new();
+            if function.locals.is_empty() {
+                uleb(&mut body, 0);
+            } else {
+                uleb(&mut body, 1);
+                uleb(&mut body, function.locals.len() as u64);
+                body.push(0x7f);
+            }
+            for instr in &function.body {
+                encode_instr(&mut body, instr, &indices, &types)
+                    .map_err(|e| format!("{} in function ${}", e, function.name))?;
+            }
+            body.push(0x0b);
+            uleb(&mut section, body.len() as u64);
+            section.extend(body);
+        }
+        emit_section(&mut out, 10, &section);
+        Ok(out)
+    }
+}
+
+fn encode_instr(
+    out: &mut Vec<u8>,
+    instr: &Instr,
+    indices: &HashMap<&str, u32>,
+    types: &[(u32, bool)],
+) -> Result<(), String> {
+    let block_type = |bt: &BlockType| match bt {
+        BlockType::Empty => 0x40,
+        BlockType::I32 => 0x7f,
+    };
+    match instr {
+        Instr::Unreachable => out.push(0x00),
+        Instr::Block(bt) => out.extend([0x02, block_type(bt)]),
+        Instr::Loop(bt) => out.extend([0x03, block_type(bt)]),
+        Instr::If(bt) => out.extend([0x04, block_type(bt)]),
+        Instr::Else => out.push(0x05),
+        Instr::End => out.push(0x0b),
+        Instr::Br(depth) => {
+            out.push(0x0c);
+            uleb(out, *depth as u64);
+        }
+        Instr::BrIf(depth) => {
+            out.push(0x0d);
+            uleb(out, *depth as u64);
+        }
+        Instr::Return => out.push(0x0f),
+        Instr::Call(name) => {
+            let index = indices.get(name.as_str()).ok_or_else(|| format!("unknown function ${}", name))?;
+            out.push(0x10);
+            uleb(out, *index as u64);
+        }
+        Instr::CallIndirect(params, result) => {
+            let index = types.iter().position(|&t| t == (*params, *result)).unwrap();
+            out.push(0x11);
+            uleb(out, index as u64);
+            out.push(0x00);
+        }
+        Instr::Drop => out.push(0x1a),
+        Instr::Select => out.push(0x1b),
+        Instr::LocalGet(i) | Instr::LocalSet(i) | Instr::LocalTee(i) | Instr::GlobalGet(i) | Instr::GlobalSet(i) => {
+            out.push(match instr {
+                Instr::LocalGet(_) => 0x20,
+                Instr::LocalSet(_) => 0x21,
+                Instr::LocalTee(_) => 0x22,
+                Instr::GlobalGet(_) => 0x23,
+                _ => 0x24,
+            });
+            uleb(out, *i as u64);
+        }
+        Instr::I32Load(offset) | Instr::I32Store(offset) => {
+            out.extend([if matches!(instr, Instr::I32Load(_)) { 0x28 } else { 0x36 }, 0x02]);
+            uleb(out, *offset as u64);
+        }
+        Instr::I32Const(value) => {
+            out.push(0x41);
+            sleb(out, *value as i64);
+        }
+        Instr::I32Eqz => out.push(0x45),
+        Instr::I32Eq => out.push(0x46),
+        Instr::I32Ne => out.push(0x47),
+        Instr::I32LtS => out.push(0x48),
+        Instr::I32GtS => out.push(0x4a),
+        Instr::I32LeS => out.push(0x4c),
+        Instr::I32GeS => out.push(0x4e),
+        Instr::I32Add => out.push(0x6a),
+        Instr::I32Sub => out.push(0x6b),
+        Instr::I32Mul => out.push(0x6c),
+        Instr::I32DivS => out.push(0x6d),
+        Instr::I32RemS => out.push(0x6f),
+        Instr::I32And => out.push(0x71),
+        Instr::I32Or => out.push(0x72),
+        Instr::MemoryCopy => out.extend([0xfc, 10, 0x00, 0x00]),
+        Instr::MemoryFill => out.extend([0xfc, 11, 0x00]),
+    }
+    Ok(())
+}
+
+fn emit_section(out: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    out.push(id);
+    uleb(out, contents.len() as u64);
+    out.extend(contents);
+}
+
+fn name(out: &mut Vec<u8>, text: &str) {
+    uleb(out, text.len() as u64);
+    out.extend(text.as_bytes());
+}
+
+fn uleb(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn sleb(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
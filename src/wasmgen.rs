@@ -0,0 +1,779 @@
+use std::collections::HashMap;
+use std::mem;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::checker::TypeInfo;
+use crate::identification::{Decl, Identification};
+use crate::stdenv::{self, Primitive, Standard, MAXINT};
+use crate::types::{Param, Signature, Type};
+use crate::wasm::{BlockType, Function, Global, Import, Instr, Module};
+
+// Traductor a WebAssembly. Los marcos viven en una pila explícita en la
+// memoria lineal: cada rutina recibe en `$fr` la dirección de su marco, cuya
+// primera palabra es el enlace estático (el marco donde fue declarada),
+// seguida de los parámetros, el resultado en las funciones y las variables y
+// constantes del cuerpo. El que llama reserva solo la cabecera (enlace,
+// parámetros y resultado) y la rutina reserva el resto al entrar, así una
+// llamada por un parámetro `proc` o `func` no necesita conocer el marco. Esos
+// parámetros son clausuras de dos palabras: enlace estático e índice en la
+// tabla de funciones. La entrada y salida son funciones importadas de "env";
+// ver runtime/triangle-host.js.
+pub fn emit(ast: &Ast, identification: &Identification, types: &TypeInfo) -> Result<Module, String> {
+    let Some(root) = ast.root() else {
+        return Err("empty program".to_string());
+    };
+    let mut emitter = Emitter {
+        ast,
+        identification,
+        types,
+        frames: vec![Frame { routine: None, members: Vec::new(), size: 0 }],
+        owners: HashMap::new(),
+        module: Module { memory_pages: MEMORY_PAGES, ..Module::default() },
+        function: Function::new("main", &["fr"], false),
+        level: 0,
+    };
+    emitter.collect(root, 0, 0);
+    emitter.layout();
+
+    for (name, params, result) in IMPORTS {
+        emitter.module.imports.push(Import {
+            module: "env".to_string(),
+            name: name.to_string(),
+            params: *params,
+            result: *result,
+        });
+    }
+    emitter.module.globals.push(Global { name: "sp".to_string(), init: STACK_BASE + emitter.frames[0].size as i32 });
+    emitter.module.globals.push(Global { name: "depth".to_string(), init: 0 });
+    emitter.module.functions.extend(helpers());
+
+    emitter.function.params.clear();
+    emitter.function.local("fr");
+    emitter.function.export = Some("main".to_string());
+    emitter.push([Instr::I32Const(STACK_BASE), Instr::LocalSet(FR)]);
+    emitter.command(root)?;
+    let main = mem::take(&mut emitter.function);
+    emitter.module.functions.push(main);
+    Ok(emitter.module)
+}
+
+// Funciones del anfitrión: nombre, cantidad de parámetros y si devuelven valor.
+const IMPORTS: &[(&str, u32, bool)] = &[
+    ("get", 0, true),
+    ("put", 1, false),
+    ("getint", 2, true),
+    ("putint", 1, false),
+    ("geteol", 0, false),
+    ("puteol", 0, false),
+    ("eof", 0, true),
+    ("eol", 0, true),
+    ("error", 3, false),
+];
+
+// Códigos de error de ejecución que recibe `error(código, fila, columna)`.
+pub const ERRORS: &[&str] = &[
+    "integer overflow",
+    "division by zero",
+    "array index out of range",
+    "invalid character code",
+    "stack overflow",
+    "invalid integer in input",
+];
+
+const MEMORY_PAGES: u32 = 16;
+const STACK_BASE: i32 = 16;
+const STACK_LIMIT: i32 = (MEMORY_PAGES * 65536) as i32;
+const MAX_DEPTH: i32 = 2000;
+
+const SP: u32 = 0;
+const DEPTH: u32 = 1;
+const FR: u32 = 0;
+
+struct Frame {
+    routine: Option<NodeId>,
+    members: Vec<NodeId>,
+    size: u32,
+}
+
+// Dirección de un V-name: el código que deja la base en la pila y un
+// desplazamiento fijo en bytes.
+struct Place {
+    base: Vec<Instr>,
+    offset: u32,
+}
+
+struct Emitter<'a> {
+    ast: &'a Ast,
+    identification: &'a Identification,
+    types: &'a TypeInfo,
+    frames: Vec<Frame>,
+    // Para cada declaración: marco que la contiene, su nivel y desplazamiento.
+    owners: HashMap<NodeId, (usize, usize, u32)>,
+    module: Module,
+    function: Function,
+    level: usize,
+}
+
+fn bytes(ty: &Type) -> u32 {
+    ty.size() as u32 * 4
+}
+
+fn is_composite(ty: &Type) -> bool {
+    matches!(ty, Type::Array(..) | Type::Record(..))
+}
+
+// Un parámetro `var` guarda la dirección de la variable y uno `proc` o
+// `func` una clausura.
+fn param_bytes(param: &Param) -> u32 {
+    match param {
+        Param::Value(ty) => bytes(ty),
+        Param::Var(_) => 4,
+        Param::Routine(_) => 8,
+    }
+}
+
+// Tamaño de la cabecera del marco de una rutina con esta firma.
+fn header(signature: &Signature) -> u32 {
+    4 + signature.params.iter().map(param_bytes).sum::<u32>() + signature.result.as_ref().map_or(0, bytes)
+}
+
+// Desplazamiento del resultado, justo después de los parámetros.
+fn result_offset(signature: &Signature) -> u32 {
+    header(signature) - signature.result.as_ref().map_or(0, bytes)
+}
+
+impl Emitter<'_> {
+    fn error<T>(&self, id: NodeId, message: &str) -> Result<T, String> {
+        let node = &self.ast[id];
+        Err(format!("{} at row {}, col {}", message, node.row, node.col))
+    }
+
+    fn push(&mut self, instrs: impl IntoIterator<Item = Instr>) {
+        self.function.body.extend(instrs);
+    }
+
+    fn position(&self, id: NodeId) -> [Instr; 2] {
+        let node = &self.ast[id];
+        [Instr::I32Const(node.row as i32), Instr::I32Const(node.col as i32)]
+    }
+
+    // Genera con `f` y devuelve las instrucciones en lugar de agregarlas.
+    fn capture(&mut self, f: impl FnOnce(&mut Self) -> Result<(), String>) -> Result<Vec<Instr>, String> {
+        let saved = mem::take(&mut self.function.body);
+        let result = f(self);
+        let captured = mem::replace(&mut self.function.body, saved);
+        result.map(|_| captured)
+    }
+
+    fn collect(&mut self, id: NodeId, frame: usize, level: usize) {
+        match self.ast.kind(id) {
+            ASTNode::Const(..) | ASTNode::Var(..) => {
+                self.owners.insert(id, (frame, level, 0));
+                self.frames[frame].members.push(id);
+            }
+            ASTNode::Func(_, params, ..) | ASTNode::Proc(_, params, ..) => {
+                self.owners.insert(id, (frame, level, 0));
+                let inner = self.frames.len();
+                self.frames.push(Frame { routine: Some(id), members: Vec::new(), size: 0 });
+                for &param in params {
+                    self.owners.insert(param, (inner, level + 1, 0));
+                    self.frames[inner].members.push(param);
+                }
+                for child in self.ast.children(id).skip(params.len()) {
+                    self.collect(child, inner, level + 1);
+                }
+                return;
+            }
+            _ => {}
+        }
+        for child in self.ast.children(id) {
+            self.collect(child, frame, level);
+        }
+    }
+
+    fn layout(&mut self) {
+        for frame in &mut self.frames {
+            let mut offset = 4;
+            let mut members = frame.members.as_slice();
+            if let Some(signature) = frame.routine.and_then(|r| self.types.signatures.get(&r)) {
+                let (params, rest) = members.split_at(signature.params.len());
+                for (&member, param) in params.iter().zip(&signature.params) {
+                    self.owners.get_mut(&member).unwrap().2 = offset;
+                    offset += param_bytes(param);
+                }
+                offset = header(signature);
+                members = rest;
+            }
+            for &member in members {
+                self.owners.get_mut(&member).unwrap().2 = offset;
+                offset += bytes(self.types.type_of(member));
+            }
+            frame.size = offset;
+        }
+    }
+
+    // Instrucciones que dejan en la pila el marco de nivel `level`.
+    fn frame_at(&self, level: usize) -> Vec<Instr> {
+        let mut instrs = vec![Instr::LocalGet(FR)];
+        for _ in level..self.level {
+            instrs.push(Instr::I32Load(0));
+        }
+        instrs
+    }
+
+    fn command(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Let(declarations, command) => {
+                self.declaration(*declarations)?;
+                self.command(*command)?;
+            }
+            ASTNode::Command(commands) => {
+                for &command in commands {
+                    self.command(command)?;
+                }
+            }
+            ASTNode::Assign(vname, expr) => {
+                let place = self.vname(*vname)?;
+                self.store(place, *expr)?;
+            }
+            ASTNode::Call(_, args) => self.call(id, args, None)?,
+            ASTNode::If(cond, _, then_branch, _, else_branch) => {
+                self.expression(*cond)?;
+                self.push([Instr::If(BlockType::Empty)]);
+                self.command(*then_branch)?;
+                self.push([Instr::Else]);
+                self.command(*else_branch)?;
+                self.push([Instr::End]);
+            }
+            ASTNode::While(cond, body) => {
+                self.push([Instr::Block(BlockType::Empty), Instr::Loop(BlockType::Empty)]);
+                self.expression(*cond)?;
+                self.push([Instr::I32Eqz, Instr::BrIf(1)]);
+                self.command(*body)?;
+                self.push([Instr::Br(0), Instr::End, Instr::End]);
+            }
+            ASTNode::Empty => {}
+            _ => return self.error(id, "not a command"),
+        }
+        Ok(())
+    }
+
+    fn declaration(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Declaration(declarations) => {
+                for &declaration in declarations {
+                    self.declaration(declaration)?;
+                }
+            }
+            ASTNode::Const(_, expr) => {
+                let (_, _, offset) = self.owners[&id];
+                self.store(Place { base: vec![Instr::LocalGet(FR)], offset }, *expr)?;
+            }
+            ASTNode::Func(_, _, _, body) | ASTNode::Proc(_, _, body) => {
+                let body = *body;
+                self.routine(id, body)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn routine_name(&self, id: NodeId) -> String {
+        match self.ast.kind(id) {
+            ASTNode::Func(name, ..) if name.is_operator() => format!("r{}_operator", id.index()),
+            ASTNode::Func(name, ..) | ASTNode::Proc(name, ..) => format!("r{}_{}", id.index(), name),
+            _ => unreachable!(),
+        }
+    }
+
+    fn routine(&mut self, id: NodeId, body: NodeId) -> Result<(), String> {
+        let is_func = matches!(self.ast.kind(id), ASTNode::Func(..));
+        let signature = self.types.signatures[&id].clone();
+        let scalar_result = is_func && !signature.result.as_ref().is_some_and(is_composite);
+        let function = Function::new(&self.routine_name(id), &["fr"], scalar_result);
+        let saved = mem::replace(&mut self.function, function);
+        self.level += 1;
+        // Reserva el resto del marco, a continuación de la cabecera.
+        let frame = self.frames.iter().find(|f| f.routine == Some(id)).map_or(0, |f| f.size);
+        let locals = frame - header(&signature);
+        if locals > 0 {
+            self.push([Instr::I32Const(locals as i32)]);
+            let pos = self.position(id);
+            self.push(pos);
+            self.push([Instr::Call("alloc".to_string()), Instr::Drop]);
+        }
+        if is_func {
+            if scalar_result {
+                self.expression(body)?;
+            } else {
+                let offset = result_offset(&signature);
+                self.store(Place { base: vec![Instr::LocalGet(FR)], offset }, body)?;
+            }
+        } else {
+            self.command(body)?;
+        }
+        self.level -= 1;
+        let function = mem::replace(&mut self.function, saved);
+        self.module.functions.push(function);
+        Ok(())
+    }
+
+    // Llama a una rutina. Si `result` tiene un destino, copia allí el
+    // resultado compuesto de la función antes de liberar su marco. Un operador
+    // declarado en el programa se llama como una función.
+    fn call(&mut self, id: NodeId, args: &[NodeId], result: Option<Place>) -> Result<(), String> {
+        let decl = match self.types.operator(id).or_else(|| self.identification.decl(id)) {
+            Some(Decl::Standard(name)) => {
+                return match stdenv::lookup(name) {
+                    Some(Standard::Func(_, primitive)) | Some(Standard::Proc(_, primitive)) => {
+                        self.standard_call(id, primitive, args)
+                    }
+                    _ => self.error(id, "not a routine"),
+                };
+            }
+            Some(Decl::Node(decl)) => decl,
+            None => return self.error(id, "undeclared routine"),
+        };
+        let (_, level, offset) = self.owners[&decl];
+        let Some(signature) = self.types.signatures.get(&decl).cloned() else {
+            return self.error(id, "not a routine");
+        };
+        // Un parámetro `proc` o `func` se llama por la tabla con su clausura.
+        let closure = matches!(self.ast.kind(decl), ASTNode::ProcParam(..) | ASTNode::FuncParam(..))
+            .then(|| Place { base: self.frame_at(level), offset });
+
+        let callee = self.function.local(&format!("t{}", self.function.locals.len()));
+        self.push([Instr::I32Const(header(&signature) as i32)]);
+        let pos = self.position(id);
+        self.push(pos.clone());
+        self.push([Instr::Call("alloc".to_string()), Instr::LocalTee(callee)]);
+        match &closure {
+            Some(closure) => {
+                self.push(closure.base.clone());
+                self.push([Instr::I32Load(closure.offset)]);
+            }
+            None => {
+                let link = self.frame_at(level);
+                self.push(link);
+            }
+        }
+        self.push([Instr::I32Store(0)]);
+        let mut offset = 4;
+        for (param, &arg) in signature.params.iter().zip(args) {
+            self.argument(Place { base: vec![Instr::LocalGet(callee)], offset }, arg)?;
+            offset += param_bytes(param);
+        }
+        self.push(pos);
+        self.push([Instr::Call("enter".to_string()), Instr::LocalGet(callee)]);
+        match closure {
+            Some(closure) => {
+                let scalar_result = signature.result.as_ref().is_some_and(|ty| !is_composite(ty));
+                self.push(closure.base);
+                self.push([Instr::I32Load(closure.offset + 4), Instr::CallIndirect(1, scalar_result)]);
+            }
+            None => self.push([Instr::Call(self.routine_name(decl))]),
+        }
+        self.push([Instr::Call("leave".to_string())]);
+        if let Some(dest) = result {
+            let size = signature.result.as_ref().map_or(0, bytes);
+            self.address(dest);
+            self.push([
+                Instr::LocalGet(callee),
+                Instr::I32Const(result_offset(&signature) as i32),
+                Instr::I32Add,
+                Instr::I32Const(size as i32),
+                Instr::MemoryCopy,
+            ]);
+        }
+        self.push([Instr::LocalGet(callee), Instr::GlobalSet(SP)]);
+        Ok(())
+    }
+
+    // Guarda en `place` un argumento: el valor, la dirección de la variable o
+    // la clausura de la rutina.
+    fn argument(&mut self, place: Place, arg: NodeId) -> Result<(), String> {
+        match self.ast.kind(arg) {
+            ASTNode::VarActual(vname) => {
+                let variable = self.vname(*vname)?;
+                self.push(place.base);
+                self.address(variable);
+                self.push([Instr::I32Store(place.offset)]);
+            }
+            ASTNode::ProcActual(_) | ASTNode::FuncActual(_) => {
+                let Some(Decl::Node(decl)) = self.identification.decl(arg) else {
+                    return self.error(arg, "not a routine");
+                };
+                let (_, level, offset) = self.owners[&decl];
+                if let ASTNode::ProcParam(..) | ASTNode::FuncParam(..) = self.ast.kind(decl) {
+                    self.address(place);
+                    let source = Place { base: self.frame_at(level), offset };
+                    self.address(source);
+                    self.push([Instr::I32Const(8), Instr::MemoryCopy]);
+                    return Ok(());
+                }
+                let name = self.routine_name(decl);
+                let index = match self.module.table.iter().position(|n| *n == name) {
+                    Some(index) => index,
+                    None => {
+                        self.module.table.push(name);
+                        self.module.table.len() - 1
+                    }
+                };
+                self.push(place.base.clone());
+                let link = self.frame_at(level);
+                self.push(link);
+                self.push([Instr::I32Store(place.offset)]);
+                self.push(place.base);
+                self.push([Instr::I32Const(index as i32), Instr::I32Store(place.offset + 4)]);
+            }
+            _ => self.store(place, arg)?,
+        }
+        Ok(())
+    }
+
+    fn standard_call(&mut self, id: NodeId, primitive: Primitive, args: &[NodeId]) -> Result<(), String> {
+        let host = |name: &str| Instr::Call(name.to_string());
+        match primitive {
+            Primitive::Ord => self.expression(args[0])?,
+            Primitive::Chr => {
+                self.expression(args[0])?;
+                let pos = self.position(id);
+                self.push(pos);
+                self.push([host("chr")]);
+            }
+            Primitive::Eof => self.push([host("eof")]),
+            Primitive::Eol => self.push([host("eol")]),
+            Primitive::Get | Primitive::GetInt => {
+                let place = self.vname(args[0])?;
+                self.push(place.base);
+                if primitive == Primitive::Get {
+                    self.push([host("get")]);
+                } else {
+                    let pos = self.position(id);
+                    self.push(pos);
+                    self.push([host("getint")]);
+                }
+                self.push([Instr::I32Store(place.offset)]);
+            }
+            Primitive::Put | Primitive::PutInt => {
+                self.expression(args[0])?;
+                self.push([host(if primitive == Primitive::Put { "put" } else { "putint" })]);
+            }
+            Primitive::GetEol => self.push([host("geteol")]),
+            Primitive::PutEol => self.push([host("puteol")]),
+            _ => return self.error(id, "not a routine"),
+        }
+        Ok(())
+    }
+
+    fn address(&mut self, place: Place) {
+        self.push(place.base);
+        if place.offset > 0 {
+            self.push([Instr::I32Const(place.offset as i32), Instr::I32Add]);
+        }
+    }
+
+    // Guarda el valor de `expr` en `place`.
+    fn store(&mut self, place: Place, expr: NodeId) -> Result<(), String> {
+        let ty = self.types.type_of(expr).clone();
+        if !is_composite(&ty) {
+            self.push(place.base);
+            self.expression(expr)?;
+            self.push([Instr::I32Store(place.offset)]);
+            return Ok(());
+        }
+        // El destino se calcula una sola vez y se guarda en un local.
+        let dest = self.function.local(&format!("t{}", self.function.locals.len()));
+        self.address(place);
+        self.push([Instr::LocalSet(dest)]);
+        let dest = || Place { base: vec![Instr::LocalGet(dest)], offset: 0 };
+        match self.ast.kind(expr) {
+            ASTNode::Expression(inner) => self.store(dest(), *inner)?,
+            ASTNode::Call(_, args) => self.call(expr, args, Some(dest()))?,
+            ASTNode::Operator(_, left, right) => self.call(expr, &[*left, *right], Some(dest()))?,
+            ASTNode::Unary(_, operand) => self.call(expr, &[*operand], Some(dest()))?,
+            ASTNode::LetExpression(declarations, inner) => {
+                self.declaration(*declarations)?;
+                self.store(dest(), *inner)?;
+            }
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                self.expression(*cond)?;
+                self.push([Instr::If(BlockType::Empty)]);
+                self.store(dest(), *then_expr)?;
+                self.push([Instr::Else]);
+                self.store(dest(), *else_expr)?;
+                self.push([Instr::End]);
+            }
+            ASTNode::ArrayAggregate(elems) => {
+                let Type::Array(_, elem) = &ty else { unreachable!() };
+                for (i, &e) in elems.iter().enumerate() {
+                    self.store(Place { offset: i as u32 * bytes(elem), ..dest() }, e)?;
+                }
+            }
+            ASTNode::RecordAggregate(fields) => {
+                for &field in fields {
+                    if let ASTNode::FieldInit(name, e) = self.ast.kind(field) {
+                        let (offset, _) = ty.field_offset(*name).unwrap();
+                        self.store(Place { offset: offset as u32 * 4, ..dest() }, *e)?;
+                    }
+                }
+            }
+            _ => {
+                self.address(dest());
+                let source = self.vname(expr)?;
+                self.address(source);
+                self.push([Instr::I32Const(bytes(&ty) as i32), Instr::MemoryCopy]);
+            }
+        }
+        Ok(())
+    }
+
+    // Deja en la pila la dirección de un valor compuesto; si no es un V-name
+    // lo copia a un temporal en la pila de marcos.
+    fn composite_address(&mut self, expr: NodeId) -> Result<(), String> {
+        match self.ast.kind(expr) {
+            ASTNode::Identifier(_) | ASTNode::Field(..) | ASTNode::Index(..) => {
+                let place = self.vname(expr)?;
+                self.address(place);
+            }
+            ASTNode::Expression(inner) => self.composite_address(*inner)?,
+            _ => {
+                let size = bytes(self.types.type_of(expr));
+                let temp = self.function.local(&format!("t{}", self.function.locals.len()));
+                self.push([Instr::I32Const(size as i32)]);
+                let pos = self.position(expr);
+                self.push(pos);
+                self.push([Instr::Call("alloc".to_string()), Instr::LocalSet(temp)]);
+                self.store(Place { base: vec![Instr::LocalGet(temp)], offset: 0 }, expr)?;
+                self.push([Instr::LocalGet(temp)]);
+            }
+        }
+        Ok(())
+    }
+
+    fn expression(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Number(n) => {
+                if *n > MAXINT {
+                    return self.error(id, "integer literal too large");
+                }
+                self.push([Instr::I32Const(*n as i32)]);
+            }
+            ASTNode::Char(c) => self.push([Instr::I32Const(*c as i32)]),
+            ASTNode::Expression(inner) => self.expression(*inner)?,
+            ASTNode::Identifier(_) | ASTNode::Field(..) | ASTNode::Index(..) => {
+                if let Some(Decl::Standard(name)) = self.identification.decl(id) {
+                    if let Some(Standard::Const(_, value)) = stdenv::lookup(name) {
+                        self.push([Instr::I32Const(value as i32)]);
+                        return Ok(());
+                    }
+                }
+                let place = self.vname(id)?;
+                self.push(place.base);
+                self.push([Instr::I32Load(place.offset)]);
+            }
+            ASTNode::Call(_, args) => self.call(id, args, None)?,
+            ASTNode::Operator(_, left, right) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*left, *right], None)?
+            }
+            ASTNode::Unary(_, operand) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*operand], None)?
+            }
+            ASTNode::Operator(_, left, right) => {
+                let (left, right) = (*left, *right);
+                let primitive = self.operator(id);
+                if matches!(primitive, Some(Primitive::Eq | Primitive::Ne))
+                    && is_composite(self.types.type_of(left))
+                {
+                    let saved = self.function.local(&format!("t{}", self.function.locals.len()));
+                    self.push([Instr::GlobalGet(SP), Instr::LocalSet(saved)]);
+                    self.composite_address(left)?;
+                    self.composite_address(right)?;
+                    let words = self.types.type_of(left).size() as i32;
+                    self.push([Instr::I32Const(words), Instr::Call("eq".to_string())]);
+                    if primitive == Some(Primitive::Ne) {
+                        self.push([Instr::I32Eqz]);
+                    }
+                    self.push([Instr::LocalGet(saved), Instr::GlobalSet(SP)]);
+                    return Ok(());
+                }
+                self.expression(left)?;
+                self.expression(right)?;
+                let pos = self.position(id);
+                let checked = |name: &str| [pos[0].clone(), pos[1].clone(), Instr::Call(name.to_string())];
+                match primitive {
+                    Some(Primitive::Eq) => self.push([Instr::I32Eq]),
+                    Some(Primitive::Ne) => self.push([Instr::I32Ne]),
+                    Some(Primitive::And) => self.push([Instr::I32And]),
+                    Some(Primitive::Or) => self.push([Instr::I32Or]),
+                    Some(Primitive::Lt) => self.push([Instr::I32LtS]),
+                    Some(Primitive::Le) => self.push([Instr::I32LeS]),
+                    Some(Primitive::Gt) => self.push([Instr::I32GtS]),
+                    Some(Primitive::Ge) => self.push([Instr::I32GeS]),
+                    Some(Primitive::Add) => {
+                        self.push([Instr::I32Add]);
+                        self.push(checked("check"));
+                    }
+                    Some(Primitive::Sub) => {
+                        self.push([Instr::I32Sub]);
+                        self.push(checked("check"));
+                    }
+                    Some(Primitive::Mul) => {
+                        self.push([Instr::I32Mul]);
+                        self.push(checked("check"));
+                    }
+                    Some(Primitive::Div) => self.push(checked("div")),
+                    Some(Primitive::Mod) => self.push(checked("mod")),
+                    _ => return self.error(id, "not a binary operator"),
+                }
+            }
+            ASTNode::Unary(_, operand) => {
+                self.expression(*operand)?;
+                match self.operator(id) {
+                    Some(Primitive::Not) => self.push([Instr::I32Eqz]),
+                    _ => return self.error(id, "not a unary operator"),
+                }
+            }
+            // Las constantes de la expresión `let` se guardan en el marco.
+            ASTNode::LetExpression(declarations, expr) => {
+                self.declaration(*declarations)?;
+                self.expression(*expr)?;
+            }
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                self.expression(*cond)?;
+                self.push([Instr::If(BlockType::I32)]);
+                self.expression(*then_expr)?;
+                self.push([Instr::Else]);
+                self.expression(*else_expr)?;
+                self.push([Instr::End]);
+            }
+            _ => return self.error(id, "not a scalar expression"),
+        }
+        Ok(())
+    }
+
+    fn operator(&self, id: NodeId) -> Option<Primitive> {
+        let Decl::Standard(name) = self.types.operator(id)? else {
+            return None;
+        };
+        match stdenv::lookup(name)? {
+            Standard::Unary(.., primitive) | Standard::Binary(.., primitive) | Standard::Equality(primitive) => {
+                Some(primitive)
+            }
+            _ => None,
+        }
+    }
+
+    fn vname(&mut self, id: NodeId) -> Result<Place, String> {
+        match self.ast.kind(id) {
+            ASTNode::Identifier(_) => match self.identification.decl(id) {
+                Some(Decl::Node(decl)) if self.owners.contains_key(&decl) => {
+                    let (_, level, offset) = self.owners[&decl];
+                    let mut base = self.frame_at(level);
+                    if let ASTNode::VarParam(..) = self.ast.kind(decl) {
+                        base.push(Instr::I32Load(offset));
+                        return Ok(Place { base, offset: 0 });
+                    }
+                    Ok(Place { base, offset })
+                }
+                _ => self.error(id, "not a variable"),
+            },
+            ASTNode::Field(record, field) => {
+                let Some((offset, _)) = self.types.type_of(*record).field_offset(*field) else {
+                    return self.error(id, "no such field");
+                };
+                let place = self.vname(*record)?;
+                Ok(Place { offset: place.offset + offset as u32 * 4, ..place })
+            }
+            ASTNode::Index(array, index) => {
+                let Type::Array(len, elem) = self.types.type_of(*array).clone() else {
+                    return self.error(id, "not an array");
+                };
+                let index = *index;
+                let place = self.vname(*array)?;
+                let pos = self.position(index);
+                let mut base = place.base;
+                base.extend(self.capture(|e| e.expression(index))?);
+                base.push(Instr::I32Const(len as i32));
+                base.extend(pos);
+                base.extend([
+                    Instr::Call("index".to_string()),
+                    Instr::I32Const(bytes(&elem) as i32),
+                    Instr::I32Mul,
+                    Instr::I32Add,
+                ]);
+                Ok(Place { base, offset: place.offset })
+            }
+            ASTNode::VarActual(vname) => self.vname(*vname),
+            _ => self.error(id, "not a variable"),
+        }
+    }
+}
+
+// Rutinas auxiliares escritas a mano: chequeos de rango, marcos y comparación
+// de valores compuestos.
+fn helpers() -> Vec<Function> {
+    use Instr::*;
+    let fail = |code: i32, row: u32, col: u32| {
+        [I32Const(code), LocalGet(row), LocalGet(col), Call("error".to_string()), Unreachable]
+    };
+
+    let mut check = Function::new("check", &["value", "row", "col"], true);
+    check.body.extend([LocalGet(0), I32Const(MAXINT as i32), I32GtS, LocalGet(0), I32Const(-MAXINT as i32)]);
+    check.body.extend([I32LtS, I32Or, If(BlockType::Empty)]);
+    check.body.extend(fail(0, 1, 2));
+    check.body.extend([End, LocalGet(0)]);
+
+    let mut div = Function::new("div", &["left", "right", "row", "col"], true);
+    div.body.extend([LocalGet(1), I32Eqz, If(BlockType::Empty)]);
+    div.body.extend(fail(1, 2, 3));
+    div.body.extend([End, LocalGet(0), LocalGet(1), I32DivS, LocalGet(2), LocalGet(3), Call("check".to_string())]);
+
+    // Resto siempre no negativo, como `rem_euclid`.
+    let mut modulo = Function::new("mod", &["left", "right", "row", "col"], true);
+    let rem = modulo.local("rem");
+    modulo.body.extend([LocalGet(1), I32Eqz, If(BlockType::Empty)]);
+    modulo.body.extend(fail(1, 2, 3));
+    modulo.body.extend([End, LocalGet(0), LocalGet(1), I32RemS, LocalTee(rem), I32Const(0), I32LtS]);
+    modulo.body.extend([If(BlockType::I32), LocalGet(rem), LocalGet(1), I32Const(0), LocalGet(1), I32Sub]);
+    modulo.body.extend([LocalGet(1), I32Const(0), I32GeS, Select, I32Add, Else, LocalGet(rem), End]);
+
+    let mut index = Function::new("index", &["index", "bound", "row", "col"], true);
+    index.body.extend([LocalGet(0), I32Const(0), I32LtS, LocalGet(0), LocalGet(1), I32GeS, I32Or]);
+    index.body.push(If(BlockType::Empty));
+    index.body.extend(fail(2, 2, 3));
+    index.body.extend([End, LocalGet(0)]);
+
+    let mut chr = Function::new("chr", &["code", "row", "col"], true);
+    chr.body.extend([LocalGet(0), I32Const(0), I32LtS, LocalGet(0), I32Const(0x10FFFF), I32GtS, I32Or]);
+    chr.body.extend([LocalGet(0), I32Const(0xD800), I32GeS, LocalGet(0), I32Const(0xDFFF), I32LeS, I32And]);
+    chr.body.extend([I32Or, If(BlockType::Empty)]);
+    chr.body.extend(fail(3, 1, 2));
+    chr.body.extend([End, LocalGet(0)]);
+
+    let mut enter = Function::new("enter", &["row", "col"], false);
+    enter.body.extend([GlobalGet(DEPTH), I32Const(1), I32Add, GlobalSet(DEPTH)]);
+    enter.body.extend([GlobalGet(DEPTH), I32Const(MAX_DEPTH), I32GtS, If(BlockType::Empty)]);
+    enter.body.extend(fail(4, 0, 1));
+    enter.body.push(End);
+
+    let mut leave = Function::new("leave", &[], false);
+    leave.body.extend([GlobalGet(DEPTH), I32Const(1), I32Sub, GlobalSet(DEPTH)]);
+
+    // Reserva `size` bytes en cero en la pila de marcos y devuelve su dirección.
+    let mut alloc = Function::new("alloc", &["size", "row", "col"], true);
+    let address = alloc.local("address");
+    alloc.body.extend([GlobalGet(SP), LocalTee(address), LocalGet(0), I32Add, GlobalSet(SP)]);
+    alloc.body.extend([GlobalGet(SP), I32Const(STACK_LIMIT), I32GtS, If(BlockType::Empty)]);
+    alloc.body.extend(fail(4, 1, 2));
+    alloc.body.extend([End, LocalGet(address), I32Const(0), LocalGet(0), MemoryFill, LocalGet(address)]);
+
+    let mut eq = Function::new("eq", &["a", "b", "words"], true);
+    eq.body.extend([Block(BlockType::Empty), Loop(BlockType::Empty), LocalGet(2), I32Eqz, BrIf(1)]);
+    eq.body.extend([LocalGet(0), I32Load(0), LocalGet(1), I32Load(0), I32Ne, If(BlockType::Empty)]);
+    eq.body.extend([I32Const(0), Return, End]);
+    eq.body.extend([LocalGet(0), I32Const(4), I32Add, LocalSet(0), LocalGet(1), I32Const(4), I32Add, LocalSet(1)]);
+    eq.body.extend([LocalGet(2), I32Const(1), I32Sub, LocalSet(2), Br(0), End, End, I32Const(1)]);
+
+    vec![check, div, modulo, index, chr, enter, leave, alloc, eq]
+}
@@ -0,0 +1,769 @@
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::mem;
+
+use crate::ast::{ASTNode, Ast, NodeId};
+use crate::checker::TypeInfo;
+use crate::fold::{self, Constants};
+use crate::identification::{Decl, Identification};
+use crate::stdenv::{self, Primitive, Standard, MAXINT};
+use crate::types::{Param, Signature, Type};
+
+// Traductor a ensamblador x86-64 de GNU (sintaxis Intel) para Linux. Es una
+// máquina de pila como TAM sobre la pila nativa: las expresiones escalares
+// dejan su valor en rax y los operandos intermedios se guardan con push. Cada
+// rutina tiene su marco en rbp; el llamador apila los argumentos y luego el
+// enlace estático, que queda en [rbp+16], con los parámetros desde [rbp+24] y
+// las variables y constantes del cuerpo por debajo de rbp. Los valores
+// compuestos se copian palabra a palabra (8 bytes) y una función compuesta
+// deja su resultado en un espacio que reserva el llamador sobre los
+// argumentos. Un parámetro `proc` o `func` es una clausura de dos palabras:
+// el enlace estático y la dirección del código. La entrada y salida y los chequeos están en
+// runtime/triangle-x86_64.s, que se agrega al final. Las expresiones
+// constantes se cargan ya evaluadas.
+pub fn emit(ast: &Ast, identification: &Identification, types: &TypeInfo) -> Result<String, String> {
+    let Some(root) = ast.root() else {
+        return Err("empty program".to_string());
+    };
+    let mut emitter = Emitter {
+        ast,
+        identification,
+        types,
+        constants: fold::fold(ast, identification, types),
+        frames: vec![Frame { routine: None, params: Vec::new(), members: Vec::new() }],
+        owners: HashMap::new(),
+        sizes: HashMap::new(),
+        code: String::new(),
+        functions: String::new(),
+        labels: 0,
+        level: 0,
+    };
+    emitter.collect(root, 0, 0);
+    emitter.layout();
+
+    emitter.prologue("tri_main", emitter.sizes[&None]);
+    emitter.command(root)?;
+    emitter.epilogue();
+    let main = mem::take(&mut emitter.code);
+
+    let mut out = String::from("        .intel_syntax noprefix\n        .text\n");
+    out.push_str(&main);
+    out.push_str(&emitter.functions);
+    out.push('\n');
+    out.push_str(RUNTIME);
+    Ok(out)
+}
+
+pub const RUNTIME: &str = include_str!("../runtime/triangle-x86_64.s");
+
+const WORD: i64 = 8;
+// Desplazamiento del primer parámetro respecto de rbp: rbp anterior,
+// dirección de retorno y enlace estático.
+const PARAMS: i64 = 24;
+
+struct Frame {
+    routine: Option<NodeId>,
+    params: Vec<NodeId>,
+    members: Vec<NodeId>,
+}
+
+// Operando de memoria `qword ptr [reg+disp]`.
+#[derive(Clone, Copy)]
+struct Operand {
+    reg: &'static str,
+    disp: i64,
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.disp {
+            0 => write!(f, "qword ptr [{}]", self.reg),
+            d if d < 0 => write!(f, "qword ptr [{}{}]", self.reg, d),
+            d => write!(f, "qword ptr [{}+{}]", self.reg, d),
+        }
+    }
+}
+
+impl Operand {
+    fn address(&self) -> String {
+        self.to_string().trim_start_matches("qword ptr ").to_string()
+    }
+}
+
+struct Emitter<'a> {
+    ast: &'a Ast,
+    identification: &'a Identification,
+    types: &'a TypeInfo,
+    constants: Constants,
+    frames: Vec<Frame>,
+    // Para cada declaración: nivel y desplazamiento respecto del rbp de su marco.
+    owners: HashMap<NodeId, (usize, i64)>,
+    // Bytes de variables y constantes locales de cada marco.
+    sizes: HashMap<Option<NodeId>, i64>,
+    code: String,
+    functions: String,
+    labels: usize,
+    level: usize,
+}
+
+fn bytes(ty: &Type) -> i64 {
+    ty.size() as i64 * WORD
+}
+
+// Un parámetro `var` ocupa una palabra con la dirección de la variable y
+// uno `proc` o `func` dos con la clausura.
+fn param_bytes(param: &Param) -> i64 {
+    match param {
+        Param::Value(ty) => bytes(ty),
+        Param::Var(_) => WORD,
+        Param::Routine(_) => 2 * WORD,
+    }
+}
+
+fn params_size(signature: &Signature) -> i64 {
+    signature.params.iter().map(param_bytes).sum()
+}
+
+fn is_composite(ty: &Type) -> bool {
+    matches!(ty, Type::Array(..) | Type::Record(..))
+}
+
+impl Emitter<'_> {
+    fn error<T>(&self, id: NodeId, message: &str) -> Result<T, String> {
+        let node = &self.ast[id];
+        Err(format!("{} at row {}, col {}", message, node.row, node.col))
+    }
+
+    fn emit(&mut self, instr: impl fmt::Display) {
+        writeln!(self.code, "        {}", instr).unwrap();
+    }
+
+    fn label(&mut self) -> String {
+        self.labels += 1;
+        format!(".L{}", self.labels)
+    }
+
+    fn place(&mut self, label: &str) {
+        writeln!(self.code, "{}:", label).unwrap();
+    }
+
+    // Deja la posición de `id` en rsi y rdx para los chequeos del runtime.
+    fn position(&mut self, id: NodeId) {
+        let (row, col) = (self.ast[id].row, self.ast[id].col);
+        self.emit(format!("mov esi, {}", row));
+        self.emit(format!("mov edx, {}", col));
+    }
+
+    fn collect(&mut self, id: NodeId, frame: usize, level: usize) {
+        match self.ast.kind(id) {
+            ASTNode::Const(..) | ASTNode::Var(..) => {
+                self.owners.insert(id, (level, 0));
+                self.frames[frame].members.push(id);
+            }
+            ASTNode::Func(_, params, ..) | ASTNode::Proc(_, params, ..) => {
+                self.owners.insert(id, (level, 0));
+                let inner = self.frames.len();
+                self.frames.push(Frame { routine: Some(id), params: params.clone(), members: Vec::new() });
+                for &param in params {
+                    self.owners.insert(param, (level + 1, 0));
+                }
+                for child in self.ast.children(id).skip(params.len()) {
+                    self.collect(child, inner, level + 1);
+                }
+                return;
+            }
+            _ => {}
+        }
+        for child in self.ast.children(id) {
+            self.collect(child, frame, level);
+        }
+    }
+
+    fn layout(&mut self) {
+        for frame in &self.frames {
+            let mut offset = PARAMS;
+            if let Some(signature) = frame.routine.map(|r| &self.types.signatures[&r]) {
+                for (&param, kind) in frame.params.iter().zip(&signature.params) {
+                    self.owners.get_mut(&param).unwrap().1 = offset;
+                    offset += param_bytes(kind);
+                }
+            }
+            let locals: i64 = frame.members.iter().map(|&m| bytes(self.types.type_of(m))).sum();
+            let mut offset = -locals;
+            for &member in &frame.members {
+                self.owners.get_mut(&member).unwrap().1 = offset;
+                offset += bytes(self.types.type_of(member));
+            }
+            self.sizes.insert(frame.routine, locals);
+        }
+    }
+
+    fn result_size(&self, routine: NodeId) -> i64 {
+        match self.types.signatures.get(&routine).and_then(|s| s.result.as_ref()) {
+            Some(result) if is_composite(result) => bytes(result),
+            _ => 0,
+        }
+    }
+
+    // Entrada a una rutina: arma el marco y pone en cero sus variables.
+    fn prologue(&mut self, name: &str, locals: i64) {
+        writeln!(self.code, "\n{}:", name).unwrap();
+        self.emit("push rbp");
+        self.emit("mov rbp, rsp");
+        if locals > 0 {
+            self.emit(format!("sub rsp, {}", locals));
+            self.emit("mov rdi, rsp");
+            self.emit(format!("mov ecx, {}", locals / WORD));
+            self.emit("xor eax, eax");
+            self.emit("rep stosq");
+        }
+    }
+
+    fn epilogue(&mut self) {
+        self.emit("leave");
+        self.emit("ret");
+    }
+
+    // Deja en `reg` el rbp del marco de nivel `level`.
+    fn frame_at(&mut self, level: usize, reg: &'static str) {
+        let hops = self.level - level;
+        if hops == 0 {
+            self.emit(format!("mov {}, rbp", reg));
+            return;
+        }
+        self.emit(format!("mov {}, qword ptr [rbp+16]", reg));
+        for _ in 1..hops {
+            self.emit(format!("mov {0}, qword ptr [{0}+16]", reg));
+        }
+    }
+
+    fn command(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Let(declarations, command) => {
+                self.declaration(*declarations)?;
+                self.command(*command)?;
+            }
+            ASTNode::Command(commands) => {
+                for &command in commands {
+                    self.command(command)?;
+                }
+            }
+            ASTNode::Assign(vname, expr) => {
+                let expr = *expr;
+                if is_composite(self.types.type_of(expr)) {
+                    let target = self.vname(*vname, "rax")?;
+                    self.emit(format!("lea rax, {}", target.address()));
+                    self.emit("push rax");
+                    self.store(expr)?;
+                    self.emit("add rsp, 8");
+                } else {
+                    self.assign(*vname, |e| e.expression(expr))?;
+                }
+            }
+            ASTNode::Call(_, args) => self.call(id, args)?,
+            ASTNode::If(cond, _, then_branch, _, else_branch) => {
+                let (else_label, end) = (self.label(), self.label());
+                self.expression(*cond)?;
+                self.emit("test rax, rax");
+                self.emit(format!("jz {}", else_label));
+                self.command(*then_branch)?;
+                self.emit(format!("jmp {}", end));
+                self.place(&else_label);
+                self.command(*else_branch)?;
+                self.place(&end);
+            }
+            ASTNode::While(cond, body) => {
+                let (body_label, test) = (self.label(), self.label());
+                self.emit(format!("jmp {}", test));
+                self.place(&body_label);
+                self.command(*body)?;
+                self.place(&test);
+                self.expression(*cond)?;
+                self.emit("test rax, rax");
+                self.emit(format!("jnz {}", body_label));
+            }
+            ASTNode::Empty => {}
+            _ => return self.error(id, "not a command"),
+        }
+        Ok(())
+    }
+
+    fn declaration(&mut self, id: NodeId) -> Result<(), String> {
+        match self.ast.kind(id) {
+            ASTNode::Declaration(declarations) => {
+                for &declaration in declarations {
+                    self.declaration(declaration)?;
+                }
+            }
+            ASTNode::Const(_, expr) => {
+                let (_, disp) = self.owners[&id];
+                let expr = *expr;
+                if is_composite(self.types.type_of(expr)) {
+                    self.emit(format!("lea rax, [rbp{:+}]", disp));
+                    self.emit("push rax");
+                    self.store(expr)?;
+                    self.emit("add rsp, 8");
+                } else {
+                    self.expression(expr)?;
+                    self.emit(format!("mov {}, rax", Operand { reg: "rbp", disp }));
+                }
+            }
+            ASTNode::Func(_, _, _, body) | ASTNode::Proc(_, _, body) => {
+                let body = *body;
+                self.routine(id, body)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn routine_name(&self, id: NodeId) -> String {
+        match self.ast.kind(id) {
+            ASTNode::Func(name, ..) if name.is_operator() => format!("r{}_operator", id.index()),
+            ASTNode::Func(name, ..) | ASTNode::Proc(name, ..) => format!("r{}_{}", id.index(), name),
+            _ => unreachable!(),
+        }
+    }
+
+    fn routine(&mut self, id: NodeId, body: NodeId) -> Result<(), String> {
+        let saved = mem::take(&mut self.code);
+        self.level += 1;
+        let name = self.routine_name(id);
+        self.prologue(&name, self.sizes[&Some(id)]);
+        if !matches!(self.ast.kind(id), ASTNode::Func(..)) {
+            self.command(body)?;
+        } else if is_composite(self.types.type_of(body)) {
+            let result = PARAMS + params_size(&self.types.signatures[&id]);
+            self.emit(format!("lea rax, [rbp+{}]", result));
+            self.emit("push rax");
+            self.store(body)?;
+            self.emit("add rsp, 8");
+        } else {
+            self.expression(body)?;
+        }
+        self.epilogue();
+        self.level -= 1;
+        let function = mem::replace(&mut self.code, saved);
+        self.functions.push_str(&function);
+        Ok(())
+    }
+
+    // Llama a una rutina. Una función escalar deja su resultado en rax; una
+    // compuesta lo deja en el tope de la pila y el llamador debe sacarlo. Un
+    // operador declarado en el programa se llama como una función.
+    fn call(&mut self, id: NodeId, args: &[NodeId]) -> Result<(), String> {
+        let decl = match self.types.operator(id).or_else(|| self.identification.decl(id)) {
+            Some(Decl::Standard(name)) => {
+                return match stdenv::lookup(name) {
+                    Some(Standard::Func(_, primitive)) | Some(Standard::Proc(_, primitive)) => {
+                        self.standard_call(id, primitive, args)
+                    }
+                    _ => self.error(id, "not a routine"),
+                };
+            }
+            Some(Decl::Node(decl)) => decl,
+            None => return self.error(id, "undeclared routine"),
+        };
+        let Some(signature) = self.types.signatures.get(&decl).cloned() else {
+            return self.error(id, "not a routine");
+        };
+        let (level, disp) = self.owners[&decl];
+        let closure = matches!(self.ast.kind(decl), ASTNode::ProcParam(..) | ASTNode::FuncParam(..));
+        let (size, result) = (params_size(&signature), self.result_size(decl));
+        if size + result > 0 {
+            self.emit(format!("sub rsp, {}", size + result));
+        }
+        let mut slot = 0;
+        for (param, &arg) in signature.params.iter().zip(args) {
+            self.argument(slot, arg)?;
+            slot += param_bytes(param);
+        }
+        // Un parámetro `proc` o `func` se llama con el enlace y el código de
+        // su clausura.
+        self.frame_at(level, "rax");
+        if closure {
+            self.emit(format!("mov rax, {}", Operand { reg: "rax", disp }));
+        }
+        self.emit("push rax");
+        self.position(id);
+        self.emit("call tri_enter");
+        if closure {
+            self.frame_at(level, "rax");
+            self.emit(format!("call {}", Operand { reg: "rax", disp: disp + WORD }));
+        } else {
+            self.emit(format!("call {}", self.routine_name(decl)));
+        }
+        self.emit("call tri_leave");
+        self.emit(format!("add rsp, {}", size + WORD));
+        Ok(())
+    }
+
+    // Guarda un argumento en el espacio reservado a `slot` bytes de rsp.
+    fn argument(&mut self, slot: i64, arg: NodeId) -> Result<(), String> {
+        match self.ast.kind(arg) {
+            ASTNode::VarActual(vname) => {
+                let target = self.vname(*vname, "rax")?;
+                self.emit(format!("lea rax, {}", target.address()));
+                self.emit(format!("mov {}, rax", Operand { reg: "rsp", disp: slot }));
+            }
+            ASTNode::ProcActual(_) | ASTNode::FuncActual(_) => {
+                let Some(Decl::Node(decl)) = self.identification.decl(arg) else {
+                    return self.error(arg, "not a routine");
+                };
+                let (level, disp) = self.owners[&decl];
+                self.frame_at(level, "rax");
+                if let ASTNode::ProcParam(..) | ASTNode::FuncParam(..) = self.ast.kind(decl) {
+                    for word in [0, WORD] {
+                        self.emit(format!("mov rcx, {}", Operand { reg: "rax", disp: disp + word }));
+                        self.emit(format!("mov {}, rcx", Operand { reg: "rsp", disp: slot + word }));
+                    }
+                } else {
+                    self.emit(format!("mov {}, rax", Operand { reg: "rsp", disp: slot }));
+                    self.emit(format!("lea rax, [rip+{}]", self.routine_name(decl)));
+                    self.emit(format!("mov {}, rax", Operand { reg: "rsp", disp: slot + WORD }));
+                }
+            }
+            _ if is_composite(self.types.type_of(arg)) => {
+                self.emit(format!("lea rax, [rsp+{}]", slot));
+                self.emit("push rax");
+                self.store(arg)?;
+                self.emit("add rsp, 8");
+            }
+            _ => {
+                self.expression(arg)?;
+                self.emit(format!("mov {}, rax", Operand { reg: "rsp", disp: slot }));
+            }
+        }
+        Ok(())
+    }
+
+    fn standard_call(&mut self, id: NodeId, primitive: Primitive, args: &[NodeId]) -> Result<(), String> {
+        match primitive {
+            Primitive::Ord => self.expression(args[0])?,
+            Primitive::Chr => {
+                self.expression(args[0])?;
+                self.position(id);
+                self.emit("call tri_chr");
+            }
+            Primitive::Eof => self.emit("call tri_eof"),
+            Primitive::Eol => self.emit("call tri_eol"),
+            Primitive::Get => self.assign(args[0], |e| {
+                e.emit("call tri_getchar");
+                Ok(())
+            })?,
+            Primitive::GetInt => self.assign(args[0], |e| {
+                e.position(id);
+                e.emit("call tri_getint");
+                Ok(())
+            })?,
+            Primitive::Put | Primitive::PutInt => {
+                self.expression(args[0])?;
+                self.emit("mov rdi, rax");
+                self.emit(if primitive == Primitive::Put { "call tri_put" } else { "call tri_putint" });
+            }
+            Primitive::GetEol => self.emit("call tri_geteol"),
+            Primitive::PutEol => self.emit("call tri_puteol"),
+            _ => return self.error(id, "not a routine"),
+        }
+        Ok(())
+    }
+
+    // Guarda en `vname` el escalar que `value` deja en rax.
+    fn assign(&mut self, vname: NodeId, value: impl FnOnce(&mut Self) -> Result<(), String>) -> Result<(), String> {
+        if self.is_static(vname) {
+            value(self)?;
+            let target = self.vname(vname, "rcx")?;
+            self.emit(format!("mov {}, rax", target));
+        } else {
+            let target = self.vname(vname, "rax")?;
+            self.emit(format!("lea rax, {}", target.address()));
+            self.emit("push rax");
+            value(self)?;
+            self.emit("pop rcx");
+            self.emit("mov qword ptr [rcx], rax");
+        }
+        Ok(())
+    }
+
+    // Copia el valor compuesto de `expr` a la dirección que está en el tope
+    // de la pila.
+    fn store(&mut self, expr: NodeId) -> Result<(), String> {
+        let ty = self.types.type_of(expr).clone();
+        match self.ast.kind(expr) {
+            ASTNode::Expression(inner) => self.store(*inner)?,
+            // Un valor compuesto que devuelve un operador viene de una función
+            // declarada en el programa; sus operandos son los hijos del nodo.
+            ASTNode::Call(..) | ASTNode::Operator(..) | ASTNode::Unary(..) => {
+                let size = bytes(&ty);
+                let args: Vec<NodeId> = self.ast.children(expr).collect();
+                self.call(expr, &args)?;
+                self.emit(format!("mov rdi, qword ptr [rsp+{}]", size));
+                self.emit("mov rsi, rsp");
+                self.emit(format!("mov ecx, {}", size / WORD));
+                self.emit("rep movsq");
+                self.emit(format!("add rsp, {}", size));
+            }
+            ASTNode::LetExpression(declarations, inner) => {
+                self.declaration(*declarations)?;
+                self.store(*inner)?;
+            }
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                let (else_label, end) = (self.label(), self.label());
+                self.expression(*cond)?;
+                self.emit("test rax, rax");
+                self.emit(format!("jz {}", else_label));
+                self.store(*then_expr)?;
+                self.emit(format!("jmp {}", end));
+                self.place(&else_label);
+                self.store(*else_expr)?;
+                self.place(&end);
+            }
+            ASTNode::ArrayAggregate(elems) => {
+                let Type::Array(_, elem) = &ty else { unreachable!() };
+                for (i, &e) in elems.iter().enumerate() {
+                    self.store_at(i as i64 * bytes(elem), e)?;
+                }
+            }
+            ASTNode::RecordAggregate(fields) => {
+                for &field in fields {
+                    if let ASTNode::FieldInit(name, e) = self.ast.kind(field) {
+                        let (offset, _) = ty.field_offset(*name).unwrap();
+                        self.store_at(offset as i64 * WORD, *e)?;
+                    }
+                }
+            }
+            _ => {
+                let source = self.vname(expr, "rax")?;
+                self.emit(format!("lea rsi, {}", source.address()));
+                self.emit("mov rdi, qword ptr [rsp]");
+                self.emit(format!("mov ecx, {}", bytes(&ty) / WORD));
+                self.emit("rep movsq");
+            }
+        }
+        Ok(())
+    }
+
+    // Guarda un componente de un agregado a `offset` bytes del destino.
+    fn store_at(&mut self, offset: i64, expr: NodeId) -> Result<(), String> {
+        if is_composite(self.types.type_of(expr)) {
+            self.emit("mov rax, qword ptr [rsp]");
+            self.emit(format!("add rax, {}", offset));
+            self.emit("push rax");
+            self.store(expr)?;
+            self.emit("add rsp, 8");
+        } else {
+            self.expression(expr)?;
+            self.emit("mov rcx, qword ptr [rsp]");
+            self.emit(format!("mov {}, rax", Operand { reg: "rcx", disp: offset }));
+        }
+        Ok(())
+    }
+
+    // Deja en rax la dirección de un valor compuesto. Si no es un V-name lo
+    // copia a un temporal en la pila y devuelve cuántos bytes ocupa.
+    fn composite_address(&mut self, expr: NodeId) -> Result<i64, String> {
+        match self.ast.kind(expr) {
+            ASTNode::Identifier(_) | ASTNode::Field(..) | ASTNode::Index(..) => {
+                let source = self.vname(expr, "rax")?;
+                self.emit(format!("lea rax, {}", source.address()));
+                Ok(0)
+            }
+            ASTNode::Expression(inner) => self.composite_address(*inner),
+            _ => {
+                let size = bytes(self.types.type_of(expr));
+                self.emit(format!("sub rsp, {}", size));
+                self.emit("mov rax, rsp");
+                self.emit("push rax");
+                self.store(expr)?;
+                self.emit("pop rax");
+                Ok(size)
+            }
+        }
+    }
+
+    fn expression(&mut self, id: NodeId) -> Result<(), String> {
+        if let Some(value) = self.constants.value(id) {
+            self.emit(format!("mov rax, {}", value.to_int()));
+            return Ok(());
+        }
+        match self.ast.kind(id) {
+            ASTNode::Number(n) => {
+                if *n > MAXINT {
+                    return self.error(id, "integer literal too large");
+                }
+                self.emit(format!("mov eax, {}", n));
+            }
+            ASTNode::Char(c) => self.emit(format!("mov eax, {}", *c as u32)),
+            ASTNode::Expression(inner) => self.expression(*inner)?,
+            ASTNode::Identifier(_) | ASTNode::Field(..) | ASTNode::Index(..) => {
+                if let Some(Decl::Standard(name)) = self.identification.decl(id) {
+                    if let Some(Standard::Const(_, value)) = stdenv::lookup(name) {
+                        self.emit(format!("mov rax, {}", value));
+                        return Ok(());
+                    }
+                }
+                let source = self.vname(id, "rax")?;
+                self.emit(format!("mov rax, {}", source));
+            }
+            ASTNode::Call(_, args) => self.call(id, args)?,
+            ASTNode::Operator(_, left, right) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*left, *right])?
+            }
+            ASTNode::Unary(_, operand) if matches!(self.types.operator(id), Some(Decl::Node(_))) => {
+                self.call(id, &[*operand])?
+            }
+            ASTNode::Operator(_, left, right) => {
+                let (left, right) = (*left, *right);
+                let primitive = self.operator(id);
+                if matches!(primitive, Some(Primitive::Eq | Primitive::Ne))
+                    && is_composite(self.types.type_of(left))
+                {
+                    let words = self.types.type_of(left).size();
+                    let left_temp = self.composite_address(left)?;
+                    self.emit("push rax");
+                    let right_temp = self.composite_address(right)?;
+                    self.emit("mov rsi, rax");
+                    self.emit(format!("mov rdi, qword ptr [rsp+{}]", right_temp));
+                    self.emit(format!("mov ecx, {}", words));
+                    self.emit("xor eax, eax");
+                    self.emit("repe cmpsq");
+                    self.emit(if primitive == Some(Primitive::Eq) { "sete al" } else { "setne al" });
+                    self.emit(format!("add rsp, {}", left_temp + WORD + right_temp));
+                    return Ok(());
+                }
+                self.expression(left)?;
+                self.emit("push rax");
+                self.expression(right)?;
+                self.emit("mov rcx, rax");
+                self.emit("pop rax");
+                let compare = |e: &mut Self, set: &str| {
+                    e.emit("cmp rax, rcx");
+                    e.emit(format!("{} al", set));
+                    e.emit("movzx eax, al");
+                };
+                let checked = |e: &mut Self, instr: &str, routine: &str| {
+                    if !instr.is_empty() {
+                        e.emit(instr);
+                    }
+                    e.position(id);
+                    e.emit(format!("call {}", routine));
+                };
+                match primitive {
+                    Some(Primitive::Eq) => compare(self, "sete"),
+                    Some(Primitive::Ne) => compare(self, "setne"),
+                    Some(Primitive::Lt) => compare(self, "setl"),
+                    Some(Primitive::Le) => compare(self, "setle"),
+                    Some(Primitive::Gt) => compare(self, "setg"),
+                    Some(Primitive::Ge) => compare(self, "setge"),
+                    Some(Primitive::And) => self.emit("and rax, rcx"),
+                    Some(Primitive::Or) => self.emit("or rax, rcx"),
+                    Some(Primitive::Add) => checked(self, "add rax, rcx", "tri_check"),
+                    Some(Primitive::Sub) => checked(self, "sub rax, rcx", "tri_check"),
+                    Some(Primitive::Mul) => checked(self, "imul rax, rcx", "tri_check"),
+                    Some(Primitive::Div) => checked(self, "", "tri_div"),
+                    Some(Primitive::Mod) => checked(self, "", "tri_mod"),
+                    _ => return self.error(id, "not a binary operator"),
+                }
+            }
+            ASTNode::Unary(_, operand) => {
+                self.expression(*operand)?;
+                match self.operator(id) {
+                    Some(Primitive::Not) => self.emit("xor eax, 1"),
+                    _ => return self.error(id, "not a unary operator"),
+                }
+            }
+            // Las constantes de la expresión `let` se guardan en el marco.
+            ASTNode::LetExpression(declarations, expr) => {
+                self.declaration(*declarations)?;
+                self.expression(*expr)?;
+            }
+            ASTNode::IfExpression(cond, then_expr, else_expr) => {
+                let (else_label, end) = (self.label(), self.label());
+                self.expression(*cond)?;
+                self.emit("test rax, rax");
+                self.emit(format!("jz {}", else_label));
+                self.expression(*then_expr)?;
+                self.emit(format!("jmp {}", end));
+                self.place(&else_label);
+                self.expression(*else_expr)?;
+                self.place(&end);
+            }
+            _ => return self.error(id, "not a scalar expression"),
+        }
+        Ok(())
+    }
+
+    fn operator(&self, id: NodeId) -> Option<Primitive> {
+        let Decl::Standard(name) = self.types.operator(id)? else {
+            return None;
+        };
+        match stdenv::lookup(name)? {
+            Standard::Unary(.., primitive) | Standard::Binary(.., primitive) | Standard::Equality(primitive) => {
+                Some(primitive)
+            }
+            _ => None,
+        }
+    }
+
+    // Un V-name sin índices tiene dirección fija respecto de algún marco.
+    fn is_static(&self, id: NodeId) -> bool {
+        match self.ast.kind(id) {
+            ASTNode::Field(record, _) | ASTNode::VarActual(record) => self.is_static(*record),
+            ASTNode::Index(..) => false,
+            _ => true,
+        }
+    }
+
+    // Genera el código que deja la base de un V-name en `reg` y devuelve el
+    // operando con el que se accede a él. Usa rax y rcx.
+    fn vname(&mut self, id: NodeId, reg: &'static str) -> Result<Operand, String> {
+        match self.ast.kind(id) {
+            ASTNode::Identifier(_) => match self.identification.decl(id) {
+                Some(Decl::Node(decl)) if self.owners.contains_key(&decl) => {
+                    let (level, disp) = self.owners[&decl];
+                    let base = if level == self.level {
+                        Operand { reg: "rbp", disp }
+                    } else {
+                        self.frame_at(level, reg);
+                        Operand { reg, disp }
+                    };
+                    if let ASTNode::VarParam(..) = self.ast.kind(decl) {
+                        self.emit(format!("mov {}, {}", reg, base));
+                        return Ok(Operand { reg, disp: 0 });
+                    }
+                    Ok(base)
+                }
+                _ => self.error(id, "not a variable"),
+            },
+            ASTNode::Field(record, field) => {
+                let Some((offset, _)) = self.types.type_of(*record).field_offset(*field) else {
+                    return self.error(id, "no such field");
+                };
+                let base = self.vname(*record, reg)?;
+                Ok(Operand { disp: base.disp + offset as i64 * WORD, ..base })
+            }
+            ASTNode::Index(array, index) => {
+                let Type::Array(len, elem) = self.types.type_of(*array).clone() else {
+                    return self.error(id, "not an array");
+                };
+                let index = *index;
+                let base = self.vname(*array, "rax")?;
+                self.emit(format!("lea rax, {}", base.address()));
+                self.emit("push rax");
+                self.expression(index)?;
+                self.emit(format!("mov ecx, {}", len));
+                self.position(index);
+                self.emit("call tri_index");
+                self.emit(format!("imul rax, rax, {}", bytes(&elem)));
+                self.emit("pop rcx");
+                self.emit("add rax, rcx");
+                if reg != "rax" {
+                    self.emit(format!("mov {}, rax", reg));
+                }
+                Ok(Operand { reg, disp: 0 })
+            }
+            ASTNode::VarActual(vname) => self.vname(*vname, reg),
+            _ => self.error(id, "not a variable"),
+        }
+    }
+}
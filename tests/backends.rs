@@ -0,0 +1,216 @@
+// Compara la salida de cada backend con la del intérprete sobre los programas
+// de tests/programs, incluido el error de ejecución y su posición. Un
+// programa `x.tri` lee `x.in` como entrada si existe.
+mod common;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{ChildStdout, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use tokenize::ast::Ast;
+use tokenize::checker::TypeInfo;
+use tokenize::identification::Identification;
+use tokenize::tam::ObjectFile;
+use tokenize::{cgen, codegen, interp, machine, wasmgen, x86gen};
+
+struct Program {
+    path: PathBuf,
+    input: Vec<u8>,
+    ast: Ast,
+    identification: Identification,
+    types: TypeInfo,
+}
+
+// Salida estándar y el error de ejecución, con su posición, si lo hubo.
+type Outcome = (String, Result<(), String>);
+
+fn programs() -> Vec<Program> {
+    common::programs()
+        .into_iter()
+        .map(|(path, src)| {
+            let common::Analysis { ast, identification, types, errors } = common::analyze(&src);
+            assert!(errors.is_empty(), "{}: {:?}", path.display(), errors);
+            let input = fs::read(path.with_extension("in")).unwrap_or_default();
+            Program { path, input, ast, identification, types }
+        })
+        .collect()
+}
+
+fn interpret(program: &Program) -> Outcome {
+    let mut output = Vec::new();
+    let result = interp::run(&program.ast, &program.identification, &program.types, &program.input[..], &mut output);
+    (String::from_utf8(output).unwrap(), result)
+}
+
+fn execute(program: &Program, command: &mut Command) -> Outcome {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| panic!("{}: {}", program.path.display(), e));
+    child.stdin.take().unwrap().write_all(&program.input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let result = if output.status.success() {
+        Ok(())
+    } else {
+        // Los ejecutables informan "Runtime error: <mensaje> at row r, col c".
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        Err(stderr
+            .lines()
+            .find_map(|line| line.split_once("Runtime error: ").map(|(_, message)| message.to_string()))
+            .unwrap_or(stderr))
+    };
+    (String::from_utf8(output.stdout).unwrap(), result)
+}
+
+fn assert_matches_interpreter(backend: &str, run: impl Fn(&Program) -> Outcome) {
+    for program in programs() {
+        let expected = interpret(&program);
+        let actual = run(&program);
+        assert_eq!(actual, expected, "{} backend differs on {}", backend, program.path.display());
+    }
+}
+
+fn has_tool(tool: &str) -> bool {
+    Command::new(tool).arg("--version").stdout(Stdio::null()).stderr(Stdio::null()).status().is_ok()
+}
+
+#[test]
+fn tam_matches_interpreter() {
+    assert_matches_interpreter("TAM", |program| {
+        let object = codegen::encode(&program.ast, &program.identification, &program.types).unwrap();
+        let object = ObjectFile::from_bytes(&object.to_bytes()).unwrap();
+        let mut output = Vec::new();
+        let result = machine::run(&object, &program.input[..], &mut output);
+        (String::from_utf8(output).unwrap(), result)
+    });
+}
+
+#[test]
+fn c_matches_interpreter() {
+    if !has_tool("cc") {
+        eprintln!("cc not found, skipping the C backend");
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    assert_matches_interpreter("C", |program| {
+        let name = program.path.file_stem().unwrap();
+        let source = dir.path().join(name).with_extension("c");
+        let binary = dir.path().join(name);
+        fs::write(&source, cgen::emit(&program.ast, &program.identification, &program.types).unwrap()).unwrap();
+        let status = Command::new("cc")
+            .args(["-std=c99", "-Wall", "-Werror", "-Wno-infinite-recursion", "-o"])
+            .arg(&binary)
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success(), "cc failed on {}", source.display());
+        execute(program, &mut Command::new(&binary))
+    });
+}
+
+#[test]
+fn wasm_matches_interpreter() {
+    if !has_tool("node") {
+        eprintln!("node not found, skipping the WebAssembly backend");
+        return;
+    }
+    let host = Path::new(env!("CARGO_MANIFEST_DIR")).join("runtime/triangle-host.js");
+    let dir = tempfile::tempdir().unwrap();
+    assert_matches_interpreter("WebAssembly", |program| {
+        let module = wasmgen::emit(&program.ast, &program.identification, &program.types).unwrap();
+        let binary = dir.path().join(program.path.file_stem().unwrap()).with_extension("wasm");
+        fs::write(&binary, module.to_binary().unwrap()).unwrap();
+        execute(program, Command::new("node").arg(&host).arg(&binary))
+    });
+}
+
+// El anfitrión lee stdin recién cuando el programa la pide y escribe la salida
+// antes de esperar entrada, así que sirve con una entrada que sigue abierta.
+#[test]
+fn wasm_host_reads_input_on_demand() {
+    if !has_tool("node") {
+        eprintln!("node not found, skipping the WebAssembly backend");
+        return;
+    }
+    let host = Path::new(env!("CARGO_MANIFEST_DIR")).join("runtime/triangle-host.js");
+    let dir = tempfile::tempdir().unwrap();
+    let spawn = |name: &str, src: &str| {
+        let common::Analysis { ast, identification, types, errors } = common::analyze(src);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let binary = dir.path().join(name).with_extension("wasm");
+        fs::write(&binary, wasmgen::emit(&ast, &identification, &types).unwrap().to_binary().unwrap()).unwrap();
+        let mut child = Command::new("node")
+            .arg(&host)
+            .arg(&binary)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let (stdin, stdout) = (child.stdin.take().unwrap(), child.stdout.take().unwrap());
+        (child, stdin, stdout)
+    };
+    // Lee en otro hilo para no colgar el test si el anfitrión no responde.
+    let read = |mut stdout: ChildStdout| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut byte = [0];
+            stdout.read_exact(&mut byte).unwrap();
+            sender.send((byte[0], stdout)).unwrap();
+        });
+        receiver.recv_timeout(Duration::from_secs(10)).expect("no output from the host")
+    };
+
+    let (mut child, _stdin, stdout) = spawn("silent", "putint(1)");
+    assert_eq!(read(stdout).0, b'1');
+    assert!(child.wait().unwrap().success());
+
+    let (mut child, mut stdin, stdout) = spawn("prompt", "let var c : Char in begin put('>'); get(var c); put(c) end");
+    let (prompt, stdout) = read(stdout);
+    assert_eq!(prompt, b'>');
+    stdin.write_all(b"x").unwrap();
+    drop(stdin);
+    assert_eq!(read(stdout).0, b'x');
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn x86_matches_interpreter() {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) || !has_tool("as") || !has_tool("ld") {
+        eprintln!("no x86-64 Linux toolchain, skipping the x86-64 backend");
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    assert_matches_interpreter("x86-64", |program| {
+        let binary = dir.path().join(program.path.file_stem().unwrap());
+        let source = binary.with_extension("s");
+        let object = binary.with_extension("o");
+        fs::write(&source, x86gen::emit(&program.ast, &program.identification, &program.types).unwrap()).unwrap();
+        let status = Command::new("as").arg("-o").arg(&object).arg(&source).status().unwrap();
+        assert!(status.success(), "as failed on {}", source.display());
+        let status = Command::new("ld").arg("-o").arg(&binary).arg(&object).status().unwrap();
+        assert!(status.success(), "ld failed on {}", object.display());
+        execute(program, &mut Command::new(&binary))
+    });
+}
+
+#[test]
+fn tam_rejects_values_that_do_not_fit() {
+    for (src, message) in [
+        ("put('😀')", "value out of range for TAM at row 1, col 5"),
+        ("let var a : array 40000 of Integer in a[0] := 1", "value out of range for TAM at row 1, col 5"),
+        (
+            "let var a : array 30000 of Integer; var b : array 30000 of Integer in b[0] := 1",
+            "frame too large for TAM at row 1, col 37",
+        ),
+    ] {
+        let common::Analysis { ast, identification, types, errors } = common::analyze(src);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(codegen::encode(&ast, &identification, &types).unwrap_err(), message, "{}", src);
+    }
+}
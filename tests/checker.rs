@@ -0,0 +1,83 @@
+// Chequeo de tipos: cada diagnóstico con su mensaje y el texto que abarca.
+mod common;
+
+use tokenize::ast::ASTNode;
+
+fn diagnostics(src: &str) -> Vec<(String, String)> {
+    let analysis = common::analyze(src);
+    assert!(analysis.identification.diagnostics.is_empty(), "{:?}", analysis.identification.diagnostics);
+    analysis
+        .types
+        .diagnostics
+        .iter()
+        .map(|d| (d.message.clone(), src[d.span.start..d.span.end].to_string()))
+        .collect()
+}
+
+#[test]
+fn reports_type_errors_with_their_spans() {
+    let src = "let
+  const k ~ 1;
+  type P ~ record x : Integer end;
+  var n : Integer; var c : Char; var p : P; var a : array 2 of Integer;
+  func f (m : Integer) : Integer ~ 'z';
+  proc q (var m : Integer) ~ m := 0
+in begin
+  n := c;
+  k := 2;
+  if n then n := 1 else n := 2;
+  while c + 1 > 0 do n := 0;
+  q(var k);
+  n := p.y + a['b'];
+  putint(f(true));
+  f(1);
+  c := n
+end";
+    let expected = [
+        ("function \"f\" must return Integer, found Char", "'z'"),
+        ("type mismatch in assignment: expected Integer, found Char", "n := c"),
+        ("cannot assign to a constant", "k"),
+        ("condition must be Boolean, found Integer", "n"),
+        ("operator \"+\" expects Integer and Integer, found Char and Integer", "c + 1"),
+        ("cannot pass a constant as a var parameter", "k"),
+        ("record x : Integer end has no field \"y\"", "p.y"),
+        ("array index must be Integer, found Char", "'b'"),
+        ("argument 1 of \"f\" must be Integer, found Boolean", "true"),
+        ("\"f\" is a function, not a procedure", "f(1)"),
+        ("type mismatch in assignment: expected Char, found Integer", "c := n"),
+    ];
+    let expected: Vec<(String, String)> = expected.iter().map(|&(m, t)| (m.to_string(), t.to_string())).collect();
+    assert_eq!(diagnostics(src), expected);
+}
+
+#[test]
+fn gives_types_to_expressions() {
+    let src = "let var a : array 3 of Char in a[0] := chr(ord(a[1]) + 1)";
+    let analysis = common::analyze(src);
+    assert!(analysis.errors.is_empty(), "{:?}", analysis.errors);
+    let types: Vec<String> = analysis
+        .ast
+        .ids()
+        .filter(|&id| !matches!(analysis.ast.kind(id), ASTNode::Var(..) | ASTNode::ArrayType(..) | ASTNode::TypeName(_)))
+        .filter(|&id| !analysis.types.type_of(id).is_error())
+        .map(|id| {
+            let span = analysis.ast.span(id);
+            format!("{} : {}", &src[span.start..span.end], analysis.types.type_of(id))
+        })
+        .collect();
+    assert_eq!(
+        types,
+        [
+            "a : array 3 of Char",
+            "0 : Integer",
+            "a[0] : Char",
+            "a : array 3 of Char",
+            "1 : Integer",
+            "a[1] : Char",
+            "ord(a[1]) : Integer",
+            "1 : Integer",
+            "ord(a[1]) + 1 : Integer",
+            "chr(ord(a[1]) + 1) : Char",
+        ]
+    );
+}
@@ -0,0 +1,60 @@
+// Comando vacío y secuencias de comandos: `if`, `while` y `let` toman un solo
+// comando y el `;` arma la secuencia en orden.
+mod common;
+
+use common::{errors, parse};
+use tokenize::ast::{ASTNode, Ast, NodeId};
+use tokenize::parser::SyntaxParser;
+
+fn kind(ast: &Ast, id: NodeId) -> &'static str {
+    match ast.kind(id) {
+        ASTNode::Empty => "empty",
+        ASTNode::Assign(..) => "assign",
+        ASTNode::Call(..) => "call",
+        ASTNode::If(..) => "if",
+        ASTNode::Let(..) => "let",
+        ASTNode::While(..) => "while",
+        ASTNode::Command(_) => "sequence",
+        _ => "other",
+    }
+}
+
+fn sequence(ast: &Ast, id: NodeId) -> Vec<&'static str> {
+    ast.children(id).map(|child| kind(ast, child)).collect()
+}
+
+#[test]
+fn parses_empty_commands() {
+    for src in ["", "begin end", "let var x : Integer in", "while false do ;"] {
+        let ast = parse(src);
+        assert!(ast.ids().any(|id| matches!(ast.kind(id), ASTNode::Empty)), "{}", src);
+    }
+
+    let ast = parse("let var x : Integer; proc p () ~ in begin if x > 0 then else x := 1; p(); end");
+    let ASTNode::Let(_, body) = ast.kind(ast.root().unwrap()) else { panic!() };
+    assert_eq!(sequence(&ast, *body), ["if", "call", "empty"]);
+    let tree = ast.to_custom_string(ast.root().unwrap());
+    assert!(tree.contains("proc(\n   name(\"p\"),\n   params([]),\n   body(\n      emptyCmd\n   )\n)"), "{}", tree);
+    assert!(tree.contains("then(\n      emptyCmd\n   )"), "{}", tree);
+}
+
+#[test]
+fn sequences_single_commands() {
+    let ast = parse("let var x : Integer in x := 1; if x > 0 then x := 2 else x := 3; while x < 5 do x := x + 1; p()");
+    assert_eq!(sequence(&ast, ast.root().unwrap()), ["let", "if", "while", "call"]);
+
+    // El segundo uso de `k` queda fuera del bloque del `let`.
+    let errors = errors("let const k ~ 1 in putint(k); putint(k)");
+    assert_eq!(errors, ["error: undeclared identifier \"k\" at row 1, col 38"]);
+}
+
+#[test]
+fn rejects_declarations_as_commands() {
+    for (src, error) in [
+        ("while false do begin var x : Integer; x := 1 end", "Unexpected command token: Var at row 1, col 22"),
+        ("begin const k ~ 1 end", "Unexpected command token: Const at row 1, col 7"),
+        ("if true then func f () : Integer ~ 1 else", "Unexpected command token: Func at row 1, col 14"),
+    ] {
+        assert_eq!(SyntaxParser::new(src).parse_program().unwrap_err(), error, "{}", src);
+    }
+}
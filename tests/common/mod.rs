@@ -0,0 +1,48 @@
+// Ayudas compartidas por las pruebas de integración; cada prueba usa solo
+// algunas.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tokenize::ast::Ast;
+use tokenize::checker::{self, TypeInfo};
+use tokenize::identification::{identify, Identification};
+use tokenize::parser::SyntaxParser;
+
+// Programa ya identificado y chequeado, con sus diagnósticos como texto.
+pub struct Analysis {
+    pub ast: Ast,
+    pub identification: Identification,
+    pub types: TypeInfo,
+    pub errors: Vec<String>,
+}
+
+pub fn parse(src: &str) -> Ast {
+    SyntaxParser::new(src).parse_program().unwrap_or_else(|e| panic!("{}\n{}", e, src))
+}
+
+pub fn analyze(src: &str) -> Analysis {
+    let ast = parse(src);
+    let identification = identify(&ast);
+    let types = checker::check(&ast, &identification);
+    let errors = identification.diagnostics.iter().chain(&types.diagnostics).map(|d| d.to_string()).collect();
+    Analysis { ast, identification, types, errors }
+}
+
+pub fn errors(src: &str) -> Vec<String> {
+    analyze(src).errors
+}
+
+// Los programas de tests/programs con su texto, ordenados por nombre.
+pub fn programs() -> Vec<(PathBuf, String)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/programs");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tri"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no programs in {}", dir.display());
+    paths.into_iter().map(|path| (path.clone(), fs::read_to_string(path).unwrap())).collect()
+}
@@ -0,0 +1,100 @@
+// El árbol concreto reproduce el texto fuente y el AST derivado de él es el
+// mismo que el del parser.
+mod common;
+
+use tokenize::cst::{self, NodeKind, SyntaxElement, SyntaxNode};
+use tokenize::lexer::Lexer;
+use tokenize::parser::SyntaxParser;
+use tokenize::token::TokenType;
+
+fn parse(src: &str) -> SyntaxNode {
+    SyntaxParser::new(src).parse_cst().unwrap_or_else(|e| panic!("{}\n{}", e, src))
+}
+
+fn find(node: &SyntaxNode, kind: NodeKind) -> Vec<SyntaxNode> {
+    let mut found = Vec::new();
+    if node.kind() == kind {
+        found.push(node.clone());
+    }
+    for child in node.children() {
+        found.extend(find(&child, kind));
+    }
+    found
+}
+
+#[test]
+fn round_trips_every_program() {
+    for (path, src) in common::programs() {
+        let root = parse(&src);
+        assert_eq!(root.to_string(), src, "{}", path.display());
+        assert_eq!(root.span().end, src.len());
+        // Cada nodo del AST empieza en un token, con la fila y columna del lexer.
+        // Un comando vacío no tiene tokens: queda en el lugar del que le sigue.
+        let tokens: Vec<_> = Lexer::new(&src).collect();
+        let ast = cst::lower(&root);
+        for id in ast.ids() {
+            let node = &ast[id];
+            let token = tokens.iter().find(|t| t.span.start == node.span.start).unwrap();
+            assert_eq!((node.row, node.col), (token.row, token.col), "{}", path.display());
+            assert!(node.span.is_empty() || tokens.iter().any(|t| t.span.end == node.span.end), "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn keeps_trivia_and_punctuation() {
+    let src = "! encabezado\r\nlet var x : Integer in\n\tbegin x := (1 + 2) * 3 ; ! fin\n  putint( x ) end  \n\n";
+    let root = parse(src);
+    assert_eq!(root.to_string(), src);
+
+    let tokens = root.tokens();
+    let comments: Vec<_> = tokens.iter().filter(|t| t.kind() == TokenType::Comment).map(|t| t.text()).collect();
+    assert_eq!(comments, ["! encabezado\r", "! fin"]);
+    assert_eq!(tokens.last().unwrap().kind(), TokenType::EOF);
+    // Cada token queda en su posición del texto.
+    for token in &tokens {
+        assert_eq!(&src[token.span().start..token.span().end], token.text());
+    }
+
+    // begin … end y los paréntesis tienen su propio nodo con sus tokens.
+    let block = &find(&root, NodeKind::Block)[0];
+    let kinds: Vec<_> = block.child_tokens().map(|t| t.kind()).collect();
+    assert_eq!(kinds, [TokenType::Begin, TokenType::End]);
+    let paren = &find(&root, NodeKind::Paren)[0];
+    assert_eq!(paren.to_string(), "(1 + 2) ");
+    assert_eq!(&src[paren.token_span().start..paren.token_span().end], "(1 + 2)");
+    assert_eq!(paren.parent().unwrap().kind(), NodeKind::Binary);
+
+    let semicolons = block
+        .children_with_tokens()
+        .flat_map(|child| match child {
+            SyntaxElement::Node(node) => node.child_tokens().collect(),
+            SyntaxElement::Token(token) => vec![token],
+        })
+        .filter(|t| t.kind() == TokenType::Semicolon)
+        .count();
+    assert_eq!(semicolons, 1);
+
+    // El AST derivado tiene las mismas posiciones que el lexer.
+    let ast = cst::lower(&root);
+    let root_id = ast.root().unwrap();
+    assert_eq!((ast[root_id].row, ast[root_id].col), (2, 1));
+    let call = ast.ids().find(|&id| matches!(ast.kind(id), tokenize::ast::ASTNode::Call(..))).unwrap();
+    assert_eq!((ast[call].row, ast[call].col), (4, 3));
+}
+
+#[test]
+fn rejects_tokens_after_the_program() {
+    for (src, message) in [
+        ("putint(1) putint(2); puteol()", "Unexpected token after the program: Identifier at row 1, col 11"),
+        ("begin putint(1) end end x := 1 ) ( garbage", "Unexpected token after the program: End at row 1, col 21"),
+    ] {
+        assert_eq!(SyntaxParser::new(src).parse_cst().unwrap_err(), message);
+    }
+}
+
+#[test]
+fn rejects_oversized_literals() {
+    let src = "putint(99999999999999999999)";
+    assert_eq!(SyntaxParser::new(src).parse_cst().unwrap_err(), "Integer literal out of range at row 1, col 8");
+}
@@ -0,0 +1,51 @@
+// Expresiones `let D in E` e `if E1 then E2 else E3` en cualquier lugar donde
+// va una expresión.
+mod common;
+
+use common::{errors, parse};
+use tokenize::ast::ASTNode;
+
+#[test]
+fn parses_let_and_if_expressions() {
+    let src = "let var n : Integer in n := 1 + let const k ~ n in if k > 0 then k else 0 - k";
+    let ast = parse(src);
+    let kinds: Vec<&str> = ast
+        .ids()
+        .filter_map(|id| match ast.kind(id) {
+            ASTNode::LetExpression(..) => Some("let"),
+            ASTNode::IfExpression(..) => Some("if"),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(kinds, ["if", "let"]);
+    // La rama else se extiende lo más posible: `0 - k` es toda la rama.
+    let branch = ast
+        .ids()
+        .find_map(|id| match ast.kind(id) {
+            ASTNode::IfExpression(_, _, else_expr) => Some(*else_expr),
+            _ => None,
+        })
+        .unwrap();
+    assert!(matches!(ast.kind(branch), ASTNode::Operator(..)));
+    let tree = ast.to_custom_string(ast.root().unwrap());
+    assert!(tree.contains("letExp(\n   const(\n   name(\"k\")"), "{}", tree);
+    assert!(tree.contains("ifExp(\n   cond(op(\n   >,"), "{}", tree);
+}
+
+#[test]
+fn checks_let_and_if_expressions() {
+    let src = "let var n : Integer; var c : Char in begin
+  n := if n then 1 else 2;
+  n := if n > 0 then 1 else 'a';
+  c := let const d ~ 'x' in d;
+  n := (let const k ~ 2 in k) + k
+end";
+    assert_eq!(
+        errors(src),
+        [
+            "error: undeclared identifier \"k\" at row 5, col 33",
+            "error: condition must be Boolean, found Integer at row 2, col 11",
+            "error: branches of an if expression must agree: found Integer and Char at row 3, col 8",
+        ]
+    );
+}
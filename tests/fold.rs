@@ -0,0 +1,51 @@
+// Diagnósticos del plegado de constantes: errores que se conocen antes de
+// ejecutar el programa.
+mod common;
+
+use tokenize::fold;
+
+fn fold_errors(src: &str) -> Vec<String> {
+    let common::Analysis { ast, identification, types, errors } = common::analyze(src);
+    assert!(errors.is_empty(), "{:?}", errors);
+    fold::fold(&ast, &identification, &types).diagnostics.iter().map(|d| d.to_string()).collect()
+}
+
+#[test]
+fn reports_errors_in_constant_expressions() {
+    let src = "let var n : Integer; var c : Char in begin
+  n := 7 / (3 - 3);
+  n := 7 // 0;
+  n := 200 * 200;
+  n := 0 - 32767 - 2;
+  c := chr(0 - 1);
+  n := n / 0
+end";
+    assert_eq!(
+        fold_errors(src),
+        [
+            "error: division by zero in constant expression at row 2, col 8",
+            "error: division by zero in constant expression at row 3, col 8",
+            "error: integer overflow in constant expression at row 4, col 8",
+            "error: integer overflow in constant expression at row 5, col 8",
+            "error: invalid character code in constant expression at row 6, col 8",
+        ]
+    );
+}
+
+#[test]
+fn reports_literals_and_array_sizes_out_of_range() {
+    let src = "let var a : array 40000 of Integer; var n : Integer in begin n := 32767; n := 40000 end";
+    assert_eq!(
+        fold_errors(src),
+        [
+            "error: array size out of range at row 1, col 13",
+            "error: integer literal out of range at row 1, col 79",
+        ]
+    );
+}
+
+#[test]
+fn folds_constants_without_errors() {
+    let src = "let const k ~ 6 * 7; var c : Char in begin c := chr(ord('a') + 1); putint(k // 5) end";
+    assert_eq!(fold_errors(src), Vec::<String>::new());
+}
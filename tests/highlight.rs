@@ -0,0 +1,45 @@
+// Categorías del resaltado sobre un programa chico: cada token con la suya,
+// y los nombres según su declaración.
+use tokenize::highlight::classify;
+
+fn categories(src: &str) -> Vec<String> {
+    classify(src).iter().map(|h| format!("{} {}", &src[h.span.start..h.span.end], h.category.name())).collect()
+}
+
+#[test]
+fn classifies_every_token() {
+    let src = "let const k ~ 2; type R ~ record f : Integer end; var r : R;
+  func g (n : Integer) : Integer ~ n * k
+in begin r.f := g(ord('a') - 1); putint(x) end ! fin $";
+    let expected = [
+        "let keyword", "const keyword", "k constant", "~ punctuation", "2 number", "; punctuation",
+        "type keyword", "R type", "~ punctuation", "record keyword", "f field", ": punctuation", "Integer type",
+        "end keyword", "; punctuation", "var keyword", "r variable", ": punctuation", "R type", "; punctuation",
+        "func keyword", "g function", "( punctuation", "n parameter", ": punctuation", "Integer type",
+        ") punctuation", ": punctuation", "Integer type", "~ punctuation", "n parameter", "* operator",
+        "k constant", "in keyword", "begin keyword", "r variable", ". punctuation", "f field", ":= punctuation",
+        "g function", "( punctuation", "ord function", "( punctuation", "'a' char", ") punctuation",
+        "- operator", "1 number", ") punctuation", "; punctuation", "putint procedure", "( punctuation",
+        // `x` no está declarado.
+        "x identifier", ") punctuation", "end keyword", "! fin $ comment",
+    ];
+    assert_eq!(categories(src), expected);
+}
+
+#[test]
+fn leaves_names_unresolved_when_parsing_fails() {
+    assert_eq!(
+        categories("let var x : Integer in x := $"),
+        [
+            "let keyword",
+            "var keyword",
+            "x identifier",
+            ": punctuation",
+            "Integer identifier",
+            "in keyword",
+            "x identifier",
+            ":= punctuation",
+            "$ illegal",
+        ]
+    );
+}
@@ -0,0 +1,82 @@
+// Identificación: cada uso de un nombre apunta a la declaración visible más
+// interna, y los nombres repetidos o sin declarar se reportan.
+mod common;
+
+use common::parse;
+use tokenize::ast::ASTNode;
+use tokenize::identification::{identify, Decl};
+
+// Cada identificador usado, en orden de aparición, con la posición de su
+// declaración o "standard".
+fn resolved(src: &str) -> Vec<String> {
+    let ast = parse(src);
+    let identification = identify(&ast);
+    ast.ids()
+        .filter_map(|id| match ast.kind(id) {
+            ASTNode::Identifier(name) | ASTNode::Call(name, _) | ASTNode::TypeName(name) => Some((id, *name)),
+            _ => None,
+        })
+        .map(|(id, name)| match identification.decl(id) {
+            Some(Decl::Node(decl)) => format!("{} -> row {}, col {}", name, ast[decl].row, ast[decl].col),
+            Some(Decl::Standard(_)) => format!("{} -> standard", name),
+            None => format!("{} -> none", name),
+        })
+        .collect()
+}
+
+fn diagnostics(src: &str) -> Vec<String> {
+    identify(&parse(src)).diagnostics.iter().map(|d| d.to_string()).collect()
+}
+
+#[test]
+fn resolves_to_the_innermost_declaration() {
+    let src = "let var x : Integer; func f (x : Char) : Char ~ x
+in let var x : Boolean in begin x := true; putint(0) end";
+    assert_eq!(
+        resolved(src),
+        [
+            "Integer -> standard",
+            "Char -> standard",
+            "Char -> standard",
+            "x -> row 1, col 30",
+            "Boolean -> standard",
+            "x -> row 2, col 8",
+            "true -> standard",
+            "putint -> standard",
+        ]
+    );
+}
+
+#[test]
+fn sees_routines_in_their_own_body_but_not_parameters_outside() {
+    let src = "let proc p (n : Integer) ~ if n > 0 then p(n - 1) else putint(n) in p(n)";
+    assert_eq!(
+        resolved(src),
+        [
+            "Integer -> standard",
+            "n -> row 1, col 13",
+            "n -> row 1, col 13",
+            "p -> row 1, col 5",
+            "n -> row 1, col 13",
+            "putint -> standard",
+            "n -> none",
+            "p -> row 1, col 5",
+        ]
+    );
+}
+
+#[test]
+fn reports_duplicates_and_undeclared_names() {
+    let src = "let var x : Integer; const x ~ 1; const k ~ k; var y : Nothing
+in begin z := x; putint(k) end";
+    // `k` no se ve en su propia expresión, pero sí después.
+    assert_eq!(
+        diagnostics(src),
+        [
+            "error: duplicate declaration of \"x\" (first declared at row 1, col 5) at row 1, col 22",
+            "error: undeclared identifier \"k\" at row 1, col 45",
+            "error: undeclared identifier \"Nothing\" at row 1, col 56",
+            "error: undeclared identifier \"z\" at row 2, col 10",
+        ]
+    );
+}
@@ -0,0 +1,113 @@
+// Compara el análisis incremental de Document con un análisis completo del
+// texto después de cada edición.
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use tokenize::incremental::{Document, TextEdit};
+use tokenize::lexer::Lexer;
+use tokenize::parser::SyntaxParser;
+use tokenize::token::{Span, Token};
+
+fn assert_matches_full_parse(document: &Document, context: &str) {
+    let text = document.text();
+    let tokens: Vec<Token> = Lexer::new(text).collect();
+    assert_eq!(document.tokens(), &tokens[..], "tokens differ {}:\n{}", context, text);
+    let full = SyntaxParser::new(text).parse_program();
+    match (document.ast(), &full) {
+        (Ok(ast), Ok(full)) => {
+            assert_eq!(document.cst().unwrap().to_string(), text, "tree text differs {}", context);
+            assert_eq!(format!("{:?}", ast), format!("{:?}", full), "trees differ {}:\n{}", context, text)
+        }
+        (Err(error), Err(full)) => assert_eq!(error, full, "errors differ {}:\n{}", context, text),
+        (ast, full) => panic!("{}: incremental {:?}, full {:?}\n{}", context, ast.err(), full.as_ref().err(), text),
+    }
+}
+
+fn replace(document: &mut Document, needle: &str, nth: usize, text: &str) {
+    let start = document.text().match_indices(needle).nth(nth).unwrap().0;
+    document.edit(&TextEdit::new(Span::new(start, start + needle.len()), text)).unwrap();
+}
+
+#[test]
+fn scripted_edits() {
+    let src = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/programs/nested.tri")).unwrap();
+    let mut document = Document::new(src.as_str());
+    assert_matches_full_parse(&document, "after opening");
+    let nodes = document.ast().unwrap().len();
+
+    // Cambio en la última línea: todo lo anterior se copia.
+    replace(&mut document, "puteol()\nend", 0, "puteol();\n  putint(depth)\nend");
+    assert_matches_full_parse(&document, "after appending a command");
+    assert!(document.reused() * 2 > nodes, "reused {} of {} nodes", document.reused(), nodes);
+
+    // Cambio al principio que agrega líneas: lo siguiente se copia desplazado.
+    replace(&mut document, "var depth : Integer;", 0, "var depth : Integer;\n  var extra : Char;");
+    assert_matches_full_parse(&document, "after inserting a declaration");
+    assert!(document.reused() > 0);
+
+    // Romper y arreglar el programa escribiendo de a un carácter.
+    replace(&mut document, "acc := 0;", 0, "acc := ");
+    assert_matches_full_parse(&document, "after deleting an expression");
+    for (i, c) in "10 * 2;".char_indices() {
+        let at = document.text().match_indices("acc := ").nth(1).unwrap().0 + "acc := ".len() + i;
+        document.edit(&TextEdit::new(Span::new(at, at), c)).unwrap();
+        assert_matches_full_parse(&document, &format!("after typing {:?}", c));
+    }
+    assert!(document.ast().is_ok());
+
+    // Borrar un salto de línea une dos líneas.
+    replace(&mut document, "\nin\n", 0, " in\n");
+    assert_matches_full_parse(&document, "after joining lines");
+
+    // Texto no ASCII y comentarios.
+    replace(&mut document, "outer(7);", 0, "outer(7); ! ¿otra vez?\n  outer(ord('ñ'));");
+    assert_matches_full_parse(&document, "after inserting non-ASCII text");
+
+    assert!(document.edit(&TextEdit::new(Span::new(3, 1), "")).is_err());
+    let past_end = document.text().len() + 1;
+    assert!(document.edit(&TextEdit::new(Span::new(past_end, past_end), "x")).is_err());
+}
+
+// Generador congruencial lineal: ediciones pseudoaleatorias reproducibles.
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % n as u64) as usize
+    }
+}
+
+const SNIPPETS: [&str; 14] =
+    ["", " ", "\n", "x", ";", "begin ", " end", "(", ")", "1 + ", "! nota\n", "'a'", "\n\n  ", "é"];
+
+#[test]
+fn random_edits() {
+    let mut random = Random(0x7472_6961);
+    let mut reused = 0;
+    for (path, src) in common::programs() {
+        let mut document = Document::new(src.as_str());
+        assert_matches_full_parse(&document, &format!("opening {}", path.display()));
+        for step in 0..150 {
+            let text = document.text();
+            let boundaries: Vec<usize> =
+                text.char_indices().map(|(i, _)| i).chain(std::iter::once(text.len())).collect();
+            let start = boundaries[random.below(boundaries.len())];
+            let end = boundaries.iter().copied().filter(|&b| b >= start).take(1 + random.below(8)).last().unwrap();
+            let removed = text[start..end].to_string();
+            let snippet = SNIPPETS[random.below(SNIPPETS.len())];
+            document.edit(&TextEdit::new(Span::new(start, end), snippet)).unwrap();
+            let context = format!("in {} at step {} replacing {:?} with {:?}", path.display(), step, removed, snippet);
+            assert_matches_full_parse(&document, &context);
+            // Deshacer de vez en cuando para volver a programas válidos.
+            if random.below(2) == 0 {
+                document.edit(&TextEdit::new(Span::new(start, start + snippet.len()), removed)).unwrap();
+                assert_matches_full_parse(&document, &format!("undoing {}", context));
+                reused += document.reused();
+            }
+        }
+    }
+    assert!(reused > 0, "no subtree was reused");
+}
@@ -0,0 +1,43 @@
+// El intérprete admite MAX_DEPTH llamadas anidadas y más allá informa el
+// desborde en la llamada que lo causa, sin agotar la pila del proceso.
+mod common;
+
+use tokenize::interp;
+
+fn run(src: &str) -> Result<String, String> {
+    run_with_input(src, b"")
+}
+
+fn run_with_input(src: &str, input: &[u8]) -> Result<String, String> {
+    let common::Analysis { ast, identification, types, errors } = common::analyze(src);
+    assert!(errors.is_empty(), "{:?}", errors);
+    let mut output = Vec::new();
+    interp::run(&ast, &identification, &types, input, &mut output)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+fn depth(n: i32) -> String {
+    format!(
+        "let func depth (n : Integer) : Integer ~ if n = 0 then 0 else 1 + depth(n - 1)
+in putint(depth({}))",
+        n
+    )
+}
+
+#[test]
+fn runs_deep_recursion() {
+    assert_eq!(run(&depth(1999)).unwrap(), "1999");
+}
+
+#[test]
+fn reports_stack_overflow_at_the_call() {
+    assert_eq!(run(&depth(5000)).unwrap_err(), "stack overflow at row 1, col 67");
+}
+
+#[test]
+fn get_reads_what_put_writes() {
+    let echo = "let var c : Char in while \\ eof() do begin get(var c); putint(ord(c)); put(c) end";
+    assert_eq!(run_with_input(echo, "aé中🙂".as_bytes()).unwrap(), "97a233é20013中128578🙂");
+    // Las secuencias inválidas o truncadas dan U+FFFD.
+    assert_eq!(run_with_input(echo, b"\x80\xc3x\xed\xa0\x80\xf0\x9f").unwrap(), "65533�65533�120x65533�65533�");
+}
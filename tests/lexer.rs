@@ -0,0 +1,42 @@
+// `StreamLexer` produce los mismos tokens que `Lexer`, con las mismas
+// posiciones, incluido el EOF.
+mod common;
+
+use std::io::Cursor;
+
+use tokenize::lexer::{Lexer, StreamLexer};
+use tokenize::token::Token;
+
+fn assert_same_tokens(src: &str) {
+    let expected: Vec<Token> = Lexer::new(src).collect();
+    let streamed: Vec<Token> = StreamLexer::new(Cursor::new(src)).collect();
+    assert_eq!(streamed, expected, "{:?}", src);
+}
+
+#[test]
+fn streams_every_program_like_the_lexer() {
+    for (path, src) in common::programs() {
+        assert!(src.ends_with('\n'), "{}", path.display());
+        assert_same_tokens(&src);
+        assert_same_tokens(src.trim_end());
+    }
+}
+
+#[test]
+fn places_eof_like_the_lexer() {
+    for src in ["", "\n", "x", "x\n", "x\n\n", "x ! comentario", "x\r\n", "  \n  ", "'ñ' ñ\n"] {
+        assert_same_tokens(src);
+    }
+}
+
+#[test]
+fn peeks_ahead_like_the_lexer() {
+    let src = "let var x : Integer in\nx := 1\n";
+    let mut lexer = Lexer::new(src);
+    let mut stream = StreamLexer::new(Cursor::new(src));
+    for k in [0, 3, 9, 10, 20] {
+        assert_eq!(stream.peek_nth(k).cloned(), lexer.peek_nth(k).cloned(), "peek_nth({})", k);
+    }
+    assert_eq!(stream.next_token(), lexer.next_token());
+    assert_eq!(stream.peek_nth(8).cloned(), lexer.peek_nth(8).cloned());
+}
@@ -0,0 +1,65 @@
+// Reglas del lint sobre tests/lint/rules.tri, que tiene un caso de cada una.
+use std::fs;
+use std::path::Path;
+
+use tokenize::checker;
+use tokenize::diagnostic::{Diagnostic, Severity};
+use tokenize::fold;
+use tokenize::identification::identify;
+use tokenize::lint::{self, Config, Level, Rule};
+use tokenize::parser::SyntaxParser;
+
+fn lint_file(config: &Config) -> Vec<Diagnostic> {
+    let src = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lint/rules.tri")).unwrap();
+    let ast = SyntaxParser::new(&src).parse_program().unwrap();
+    let identification = identify(&ast);
+    let types = checker::check(&ast, &identification);
+    let constants = fold::fold(&ast, &identification, &types);
+    lint::lint(&src, &ast, &identification, &constants, config)
+}
+
+fn found(diagnostics: &[Diagnostic]) -> Vec<(String, usize)> {
+    diagnostics
+        .iter()
+        .map(|d| {
+            let rule = d.message.rsplit_once('[').unwrap().1.trim_end_matches(']');
+            (rule.to_string(), d.row)
+        })
+        .collect()
+}
+
+#[test]
+fn reports_each_rule_once_per_case() {
+    let diagnostics = lint_file(&Config::default());
+    let expected = [
+        ("unused-declaration", 4),
+        ("unused-declaration", 5),
+        ("unassigned-var-parameter", 6),
+        ("shadowed-name", 8),
+        ("self-assignment", 16),
+        ("assign-to-constant", 17),
+        ("self-assignment", 19),
+        ("constant-condition", 20),
+    ];
+    let expected: Vec<_> = expected.iter().map(|&(rule, row)| (rule.to_string(), row)).collect();
+    // `x := x` (fila 10) y el `if true` (fila 19) están permitidos con comentarios.
+    assert_eq!(found(&diagnostics), expected);
+    assert!(diagnostics.iter().all(|d| d.row != 10));
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    assert!(diagnostics[0].message.starts_with("unused variable \"unused\""));
+}
+
+#[test]
+fn applies_configured_levels() {
+    let mut config = Config::parse("# solo errores\nself-assignment = deny\n\nunused-declaration = allow\n").unwrap();
+    config.set(Rule::ShadowedName, Level::Allow);
+    let diagnostics = lint_file(&config);
+    let rules: Vec<_> = found(&diagnostics).into_iter().map(|(rule, _)| rule).collect();
+    assert!(!rules.iter().any(|r| r == "unused-declaration" || r == "shadowed-name"));
+    let denied: Vec<_> = diagnostics.iter().filter(|d| d.is_error()).map(|d| d.row).collect();
+    assert_eq!(denied, [16, 19]);
+
+    assert!(Config::parse("unused = deny").unwrap_err().contains("Unknown lint rule \"unused\" at line 1"));
+    assert!(Config::parse("self-assignment = never").unwrap_err().contains("Unknown lint level"));
+    assert!(Config::parse("self-assignment").unwrap_err().contains("Expected `rule = level` at line 1"));
+}
@@ -0,0 +1,163 @@
+// Cliente JSON-RPC guionado contra el binario triangle-lsp.
+use std::io::{BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{json, Value};
+use tokenize::lsp::{read_message, write_message};
+
+const URI: &str = "file:///tmp/program.tri";
+
+const PROGRAM: &str = "let
+  const shift ~ ord('a') - ord('A');
+  var count : Integer;
+  func up (c : Char) : Char ~ chr(ord(c) - shift)
+in
+begin
+  count := 0;
+  put(up('x'));
+  count := count + 1
+end
+";
+
+struct Client {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    next_id: i64,
+    // Notificaciones recibidas mientras se esperaba una respuesta.
+    notifications: Vec<Value>,
+}
+
+impl Client {
+    fn start() -> Client {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_triangle-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap());
+        Client { child, input, output, next_id: 0, notifications: Vec::new() }
+    }
+
+    fn send(&mut self, message: Value) {
+        write_message(&mut self.input, &message).unwrap();
+        self.input.flush().unwrap();
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({"jsonrpc": "2.0", "method": method, "params": params}));
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}));
+        loop {
+            let message = read_message(&mut self.output).unwrap().expect("server closed the connection");
+            if message.get("id") == Some(&json!(id)) {
+                assert!(message.get("error").is_none(), "{} failed: {}", method, message);
+                return message["result"].clone();
+            }
+            self.notifications.push(message);
+        }
+    }
+
+    // Diagnósticos que publica el servidor después de abrir o cambiar el documento.
+    fn diagnostics(&mut self) -> Vec<Value> {
+        let message = match self.notifications.pop() {
+            Some(message) => message,
+            None => read_message(&mut self.output).unwrap().unwrap(),
+        };
+        assert_eq!(message["method"], "textDocument/publishDiagnostics");
+        message["params"]["diagnostics"].as_array().unwrap().clone()
+    }
+
+    fn open(&mut self, text: &str) -> Vec<Value> {
+        let document = json!({"uri": URI, "languageId": "triangle", "version": 1, "text": text});
+        self.notify("textDocument/didOpen", json!({"textDocument": document}));
+        self.diagnostics()
+    }
+
+    fn change(&mut self, version: i64, text: &str) -> Vec<Value> {
+        let params = json!({"textDocument": {"uri": URI, "version": version}, "contentChanges": [{"text": text}]});
+        self.notify("textDocument/didChange", params);
+        self.diagnostics()
+    }
+
+    fn at(&mut self, method: &str, line: usize, needle: &str, nth: usize) -> Value {
+        let character = PROGRAM.lines().nth(line).unwrap().match_indices(needle).nth(nth).unwrap().0;
+        let position = json!({"line": line, "character": character});
+        let mut params = json!({"textDocument": {"uri": URI}, "position": position});
+        if method == "textDocument/references" {
+            params["context"] = json!({"includeDeclaration": true});
+        }
+        self.request(method, params)
+    }
+}
+
+fn start(range: &Value) -> (u64, u64) {
+    (range["start"]["line"].as_u64().unwrap(), range["start"]["character"].as_u64().unwrap())
+}
+
+fn labels(completion: &Value) -> Vec<String> {
+    completion["items"].as_array().unwrap().iter().map(|item| item["label"].as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn scripted_session() {
+    let mut client = Client::start();
+    let init = client.request("initialize", json!({"processId": null, "rootUri": null, "capabilities": {}}));
+    assert_eq!(init["capabilities"]["hoverProvider"], true);
+    assert_eq!(init["capabilities"]["textDocumentSync"], 1);
+    client.notify("initialized", json!({}));
+
+    let broken = client.open("let var x : Integer in x :=");
+    assert_eq!(broken.len(), 1);
+    assert_eq!(start(&broken[0]["range"]), (0, 27));
+
+    let undeclared = client.change(2, &PROGRAM.replace("count := 0", "total := 0"));
+    assert_eq!(undeclared.len(), 1);
+    assert!(undeclared[0]["message"].as_str().unwrap().contains("undeclared identifier \"total\""));
+    assert_eq!(start(&undeclared[0]["range"]), (6, 2));
+
+    assert!(client.change(3, PROGRAM).is_empty());
+
+    let hover = client.at("textDocument/hover", 3, "shift", 0);
+    assert!(hover["contents"]["value"].as_str().unwrap().contains("const shift : Integer ~ 32"));
+    let hover = client.at("textDocument/hover", 7, "up", 0);
+    assert!(hover["contents"]["value"].as_str().unwrap().contains("func up (c : Char) : Char"));
+    let hover = client.at("textDocument/hover", 7, "put", 0);
+    assert!(hover["contents"]["value"].as_str().unwrap().contains("proc put (Char)"));
+
+    let definition = client.at("textDocument/definition", 8, "count", 1);
+    assert_eq!(definition["uri"], URI);
+    assert_eq!(start(&definition["range"]), (2, 6));
+    assert_eq!(client.at("textDocument/definition", 7, "put", 0), Value::Null);
+
+    let references = client.at("textDocument/references", 2, "count", 0);
+    let starts: Vec<_> = references.as_array().unwrap().iter().map(|r| start(&r["range"])).collect();
+    assert_eq!(starts, [(2, 6), (6, 2), (8, 2), (8, 11)]);
+
+    let symbols = client.request("textDocument/documentSymbol", json!({"textDocument": {"uri": URI}}));
+    let names: Vec<_> = symbols.as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["shift", "count", "up"]);
+    assert_eq!(symbols[2]["children"][0]["name"], "c");
+
+    let completion = client.at("textDocument/completion", 7, "put", 0);
+    let items = labels(&completion);
+    for expected in ["count", "shift", "up", "putint", "while"] {
+        assert!(items.iter().any(|label| label == expected), "missing {} in {:?}", expected, items);
+    }
+    assert!(!items.iter().any(|label| label == "c"));
+    let completion = client.at("textDocument/completion", 3, "chr", 0);
+    assert!(labels(&completion).iter().any(|label| label == "c"));
+    // En `shift` todavía no se declararon `count` ni `up`.
+    let items = labels(&client.at("textDocument/completion", 1, "ord", 0));
+    assert!(items.iter().any(|label| label == "shift"), "{:?}", items);
+    assert!(!items.iter().any(|label| label == "count" || label == "up"), "{:?}", items);
+
+    assert_eq!(client.request("shutdown", Value::Null), Value::Null);
+    client.notify("exit", Value::Null);
+    assert!(client.child.wait().unwrap().success());
+}
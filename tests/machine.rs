@@ -0,0 +1,53 @@
+// La máquina TAM termina con un error, no con un pánico, ante código objeto
+// válido que desborda la pila o usa operandos negativos.
+use tokenize::machine;
+use tokenize::tamasm::assemble;
+
+fn run(asm: &str) -> Result<String, String> {
+    let object = assemble(asm).unwrap_or_else(|e| panic!("{}\n{}", e, asm));
+    let mut output = Vec::new();
+    machine::run(&object, &b""[..], &mut output)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn reports_stack_underflow() {
+    for (asm, message) in [
+        ("RETURN(3) 0\nHALT", "stack underflow at code address 0"),
+        ("LOADL 1\nPOP(2) 0\nHALT", "stack underflow at code address 1"),
+        ("LOADL 1\nPOP(1) 1\nHALT", "stack underflow at code address 1"),
+        ("LOADL 1\nLOADL 5\nCALL(SB) eq\nHALT", "stack underflow at code address 2"),
+    ] {
+        assert_eq!(run(asm).unwrap_err(), message, "{}", asm);
+    }
+}
+
+#[test]
+fn reports_negative_operands() {
+    for (asm, message) in [
+        ("PUSH -1\nHALT", "negative word count at code address 0"),
+        ("LOADL 1\nPOP(1) -1\nHALT", "negative word count at code address 1"),
+        ("CALL(SB) L1\nHALT\nL1: RETURN(0) -2", "negative word count at code address 2"),
+        ("LOADL 1\nLOADL 1\nLOADL -1\nCALL(SB) eq\nHALT", "negative size at code address 3"),
+        ("LOADL -5\nCALL(SB) new\nHALT", "negative size at code address 1"),
+    ] {
+        assert_eq!(run(asm).unwrap_err(), message, "{}", asm);
+    }
+}
+
+#[test]
+fn reports_invalid_returns() {
+    for (asm, message) in [
+        ("LOADL 1\nRETURN(1) 0\nHALT", "invalid return at code address 1"),
+        ("CALL(SB) L1\nHALT\nL1: LOADL -7\nSTORE(1) 1[LB]\nRETURN(0) 0", "invalid return at code address 4"),
+        ("CALL(SB) L1\nHALT\nL1: RETURN(0) 9", "invalid return at code address 2"),
+    ] {
+        assert_eq!(run(asm).unwrap_err(), message, "{}", asm);
+    }
+}
+
+#[test]
+fn runs_well_formed_code() {
+    let asm = "LOADL 7\nCALL(SB) L1\nCALL(SB) putint\nHALT\nL1: LOAD(1) -1[LB]\nLOADL 1\nCALL(SB) add\nRETURN(1) 1";
+    assert_eq!(run(asm).unwrap(), "8");
+}
@@ -0,0 +1,86 @@
+// Operadores declarados en el programa: se eligen por el tipo de los
+// operandos entre todas las declaraciones visibles, incluida la estándar.
+mod common;
+
+use common::{analyze, errors, Analysis};
+use tokenize::ast::ASTNode;
+use tokenize::identification::Decl;
+use tokenize::symbol::Symbol;
+
+#[test]
+fn resolves_operators_by_operand_types() {
+    let src = "let type V ~ record x : Integer end;
+  func + (a : V, b : V) : V ~ {x ~ a.x + b.x};
+  func - (a : V) : V ~ {x ~ 0 - a.x};
+  var v : V; var n : Integer
+in begin
+  v := v + -v; n := n + 1; n := 0 - n
+end";
+    let Analysis { ast, types, errors, .. } = analyze(src);
+    assert_eq!(errors, Vec::<String>::new());
+    let tree = ast.to_custom_string(ast.root().unwrap());
+    assert!(tree.contains("name(\"+\")"), "{}", tree);
+    // Los usos de operadores, hijos antes que padres: `-v`, `v + -v`,
+    // `n + 1` y `0 - n`.
+    let chosen: Vec<String> = ast
+        .ids()
+        .filter(|&id| matches!(ast.kind(id), ASTNode::Operator(..) | ASTNode::Unary(..)) && ast[id].row == 6)
+        .map(|id| match types.operator(id) {
+            Some(Decl::Node(decl)) => format!("row {}", ast[decl].row),
+            Some(Decl::Standard(name)) => format!("standard {}", name),
+            None => "none".to_string(),
+        })
+        .collect();
+    assert_eq!(chosen, ["row 3", "row 2", "standard +", "standard -"]);
+}
+
+#[test]
+fn reports_bad_operator_declarations_and_uses() {
+    let src = "let type V ~ record x : Integer end;
+  func + (a : V, b : V) : V ~ a;
+  func * (a : V, b : V, c : V) : V ~ a;
+  func @ (var a : V) : V ~ a;
+  func ? (a : V) : Integer ~ a.x;
+  var v : V; var b : Boolean; var n : Integer
+in begin
+  v := v + 1;
+  b := b + b;
+  n := ?b;
+  n := v - v
+end";
+    assert_eq!(
+        errors(src),
+        [
+            "error: operator \"*\" must have one or two value parameters at row 3, col 3",
+            "error: operator \"@\" must have one or two value parameters at row 4, col 3",
+            "error: no operator \"+\" accepts record x : Integer end and Integer at row 8, col 8",
+            "error: no operator \"+\" accepts Boolean and Boolean at row 9, col 8",
+            "error: operator \"?\" expects record x : Integer end, found Boolean at row 10, col 8",
+            "error: operator \"-\" expects Integer and Integer, found record x : Integer end and record x : Integer end at row 11, col 8",
+        ]
+    );
+}
+
+#[test]
+fn names_starting_with_any_letter_are_not_operators() {
+    assert!(!Symbol::intern("ñ").is_operator());
+    assert!(Symbol::intern("+").is_operator());
+    let src = "let func ñ (a : Integer, b : Integer, c : Integer) : Integer ~ a + b + c in putint(ñ(1, 2, 3))";
+    assert_eq!(errors(src), Vec::<String>::new());
+}
+
+#[test]
+fn reports_operators_redeclared_with_the_same_signature() {
+    let src = "let type N ~ Integer;
+  func + (a : Boolean, b : Boolean) : Boolean ~ a \\/ b;
+  func + (a : Boolean, b : Boolean) : Integer ~ 0;
+  func + (a : Integer, b : N) : Integer ~ a;
+  func - (a : Boolean) : Boolean ~ \\ a;
+  func - (a : Integer) : Boolean ~ a > 0
+in let func + (a : Boolean, b : Boolean) : Boolean ~ a in putint(1 + 1)";
+    assert_eq!(
+        errors(src),
+        ["error: operator \"+\" already declared with this signature in this block (first declared at row 2, col 3) \
+          at row 3, col 3"]
+    );
+}
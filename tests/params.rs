@@ -0,0 +1,89 @@
+// Parámetros formales por valor, var, proc y func, y su concordancia con los
+// argumentos de cada llamada.
+mod common;
+
+use common::{errors, parse};
+use tokenize::ast::ASTNode;
+
+#[test]
+fn distinguishes_parameter_kinds() {
+    let src = "let func capital (var chr : Char) : Char ~ chr; \
+               func lower (chr : Char) : Char ~ chr; \
+               proc each (proc p (n : Integer), func f (c : Char) : Char) ~ p(ord(f('a'))) \
+               in putint(0)";
+    let ast = parse(src);
+    let kinds: Vec<&str> = ast
+        .ids()
+        .filter_map(|id| match ast.kind(id) {
+            ASTNode::ValueParam(..) => Some("value"),
+            ASTNode::VarParam(..) => Some("var"),
+            ASTNode::ProcParam(..) => Some("proc"),
+            ASTNode::FuncParam(..) => Some("func"),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(kinds, ["var", "value", "value", "proc", "value", "func"]);
+    let root = ast.root().unwrap();
+    let tree = ast.to_custom_string(root);
+    assert!(tree.contains("varParam(name(\"chr\"),typeName(\"Char\"))"), "{}", tree);
+    assert!(tree.contains("valueParam(name(\"chr\"),typeName(\"Char\"))"), "{}", tree);
+}
+
+#[test]
+fn parses_actual_parameter_forms() {
+    let src = "let var c : Char; var r : record x : Integer end; \
+               proc p (var x : Integer) ~ x := 1; \
+               proc q (proc s (var x : Integer), func f (n : Integer) : Char) ~ s(var r.x); \
+               func g (n : Integer) : Char ~ chr(n) \
+               in begin get(var c); p(var r.x); q(proc p, func g) end";
+    assert_eq!(errors(src), Vec::<String>::new());
+    let ast = parse(src);
+    let tree = ast.to_custom_string(ast.root().unwrap());
+    assert!(tree.contains("call(\"q\",params([procActual(\"p\"),funcActual(\"g\")]))"), "{}", tree);
+    assert!(tree.contains("varActual(ref([\"r\", \"x\"]))"), "{}", tree);
+}
+
+#[test]
+fn reports_disagreeing_arguments() {
+    let src = "let const k ~ 1; var n : Integer; var c : Char;
+  proc p (var x : Integer, y : Integer) ~ x := y;
+  proc q (func f (n : Integer) : Integer) ~ putint(f(1));
+  func twice (n : Integer) : Integer ~ n * 2;
+  func first (c : Char) : Integer ~ ord(c)
+in begin
+  p(n, var n);
+  p(var c, 1);
+  p(var k, 1);
+  q(proc p); q(func first); q(func n); q(func twice);
+  get(c)
+end";
+    assert_eq!(
+        errors(src),
+        [
+            "error: argument 1 of \"p\" must be a var actual parameter, found a value actual parameter at row 7, col 5",
+            "error: argument 2 of \"p\" must be a value actual parameter, found a var actual parameter at row 7, col 8",
+            "error: argument 1 of \"p\" must be var Integer, found var Char at row 8, col 5",
+            "error: cannot pass a constant as a var parameter at row 9, col 9",
+            "error: argument 1 of \"q\" must be a func actual parameter, found a proc actual parameter at row 10, col 5",
+            "error: argument 1 of \"q\" must be func (Integer) : Integer, found func (Char) : Integer at row 10, col 16",
+            "error: \"n\" is not a function at row 10, col 31",
+            "error: argument 1 of \"get\" must be a var actual parameter, found a value actual parameter at row 11, col 7",
+        ]
+    );
+}
+
+#[test]
+fn rejects_standard_routines_as_arguments() {
+    let src = "let proc each (proc p (n : Integer)) ~ p(1);
+  proc apply (func f (c : Char) : Integer) ~ putint(f('a'))
+in begin
+  each(proc putint); apply(func ord)
+end";
+    assert_eq!(
+        errors(src),
+        [
+            "error: standard routine \"putint\" cannot be passed as a parameter at row 4, col 8",
+            "error: standard routine \"ord\" cannot be passed as a parameter at row 4, col 28",
+        ]
+    );
+}
@@ -0,0 +1,29 @@
+// Símbolos internados: el mismo texto da el mismo símbolo desde cualquier
+// hilo, y `as_str` devuelve el texto sin importar cuántos nombres haya.
+use std::thread;
+
+use tokenize::symbol::{sym, Symbol};
+
+#[test]
+fn predefined_symbols_keep_their_numbers() {
+    assert_eq!(sym::ADD.as_str(), "+");
+    assert_eq!(Symbol::intern("while"), sym::WHILE);
+    assert!(sym::WHILE.is_keyword() && !sym::INTEGER.is_keyword());
+}
+
+#[test]
+fn interns_from_many_threads() {
+    let names: Vec<String> = (0..5000).map(|i| format!("nombre{}", i)).collect();
+    let interned: Vec<Vec<Symbol>> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..4)
+            .map(|_| scope.spawn(|| names.iter().map(|name| Symbol::intern(name)).collect::<Vec<_>>()))
+            .collect();
+        workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    });
+    for symbols in &interned {
+        assert_eq!(symbols, &interned[0]);
+    }
+    for (name, symbol) in names.iter().zip(&interned[0]) {
+        assert_eq!(symbol.as_str(), name);
+    }
+}
@@ -0,0 +1,29 @@
+// Ensamblar lo desensamblado devuelve el mismo código objeto, con su mapa de
+// fuentes, y desensamblarlo otra vez da el mismo texto.
+mod common;
+
+use tokenize::codegen;
+use tokenize::tamasm::{assemble, disassemble};
+
+#[test]
+fn disassembly_round_trips() {
+    for (path, src) in common::programs() {
+        let common::Analysis { ast, identification, types, .. } = common::analyze(&src);
+        let object = codegen::encode(&ast, &identification, &types).unwrap();
+        for source in [None, Some(src.as_str())] {
+            let text = disassemble(&object, source);
+            let assembled = assemble(&text).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            assert_eq!(assembled, object, "{}", path.display());
+            assert_eq!(disassemble(&assembled, source), text, "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn assembles_without_source_map() {
+    let object = assemble("L0:  LOADL 1\n     JUMPIF(1) L0   ; vuelve\n     HALT\n").unwrap();
+    assert_eq!(object.code.len(), 3);
+    assert!(object.source_map.is_empty());
+    let object = assemble("LOADL 1   ; 0 (row 2, col 7)\nHALT\n").unwrap();
+    assert_eq!(object.source_map, [(2, 7), (0, 0)]);
+}